@@ -1,26 +1,79 @@
+use crate::error::ConspireError;
 use crate::Plot;
 
-use std::error;
+use std::io::Write;
 
 mod plotly;
 mod util;
 mod common;
+mod scale;
+mod time;
+mod treemap;
+mod svg;
+mod matplotlib;
+mod gnuplot;
+mod vegalite;
+mod pgfplots;
+mod terminal;
+#[cfg(feature = "plotters")]
+mod plotters;
 
-type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
+pub use gnuplot::GnuplotTerminal;
+pub use plotly::PlotlyJs;
+pub(crate) use scale::Rgb;
+#[cfg(feature = "plotters")]
+pub use self::plotters::PlottersFormat;
+
+type Result<T> = std::result::Result<T, ConspireError>;
 
 #[derive(Clone)]
 pub enum Backend {
+    /// Interactive plots in the browser, using Plotly.js
     Plotly,
+    /// A self-contained SVG image, drawn by Conspire itself
+    Svg,
+    /// Text drawn with braille characters and ANSI colors, printed to the terminal. The width and height are given in
+    /// characters.
+    Terminal { width: usize, height: usize },
+    /// A Python script drawing the plot with matplotlib, which is only run (using `python3`) when displaying
+    Matplotlib,
+    /// A gnuplot script with the data inlined, which is run through gnuplot when it's installed
+    Gnuplot(GnuplotTerminal),
+    /// A Vega-Lite (v5) JSON specification, which is embedded in a page using vega-embed when displaying
+    VegaLite,
+    /// A LaTeX fragment drawing the plot with PGFPlots, for including in documents
+    Pgfplots,
+    /// A PNG or SVG image drawn with the plotters crate, without needing a browser or any external program. Only
+    /// available with the `plotters` feature.
+    #[cfg(feature = "plotters")]
+    Plotters(PlottersFormat),
 }
 
 impl Backend {
-    pub(crate) fn to_struct(self) -> impl Renderable {
+    pub(crate) fn to_struct(&self, plotly_js: &PlotlyJs) -> Box<dyn Renderable> {
         match self {
-            Backend::Plotly => plotly::Plotly {},
+            Backend::Plotly => Box::new(plotly::Plotly::new(plotly_js.clone())),
+            Backend::Svg => Box::new(svg::Svg::default()),
+            Backend::Terminal { width, height } => Box::new(terminal::Terminal::new(*width, *height)),
+            Backend::Matplotlib => Box::new(matplotlib::Matplotlib {}),
+            Backend::Gnuplot(terminal) => Box::new(gnuplot::Gnuplot::new(*terminal)),
+            Backend::VegaLite => Box::new(vegalite::VegaLite {}),
+            Backend::Pgfplots => Box::new(pgfplots::Pgfplots {}),
+            #[cfg(feature = "plotters")]
+            Backend::Plotters(format) => Box::new(self::plotters::Plotters::new(*format)),
         }
     }
 }
 
 pub trait Renderable {
     fn render(&self, data: &[Plot], display: bool) -> Result<()>;
+
+    /// Write the rendered plot to any writer, rather than to the backend's usual output
+    fn write(&self, data: &[Plot], writer: &mut dyn Write) -> Result<()>;
+
+    /// Render the plot as an HTML fragment, for embedding in pages such as notebooks. Backends without an HTML
+    /// representation fall back to an inline SVG image.
+    fn html_fragment(&self, data: &[Plot]) -> Result<String> {
+        svg::Svg::default().document(data)
+    }
 }
@@ -0,0 +1,21 @@
+use std::fmt;
+
+/// A key/value pair, rendered by each backend in whatever form it requires
+pub struct AttributePair {
+    pub key: String,
+    pub value: String,
+}
+
+impl AttributePair {
+    pub fn new<T: fmt::Display>(key: &str, value: &T) -> Self {
+        Self {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+/// Stringify a vector of data into a comma-separated list surrounded by brackets
+pub fn stringify_data_vec<T: fmt::Debug>(data: &[T]) -> String {
+    format!("{:?}", data)
+}
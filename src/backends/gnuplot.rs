@@ -0,0 +1,671 @@
+use super::scale::{
+    bar_offset, box_shapes, contour_lines, dimension_position, dimension_scale, flat, grid_centers, heatmap_cells,
+    histogram_bars, layer_color, layer_name, line_runs, pie_slices, point_colors, point_sizes, violin_shapes, Axes,
+    ColorMap, Rgb, Scale, PALETTE,
+};
+use super::treemap::{tiles, Rect};
+use super::Renderable;
+use crate::data::MatrixData;
+use crate::error::ConspireError;
+use crate::stats::ViolinInner;
+use crate::Plot;
+
+use std::fmt::{self, Write as FmtWrite};
+use std::io::{self, Write};
+
+type Result<T> = std::result::Result<T, ConspireError>;
+
+/// The gnuplot terminal, i.e. the output format, to render to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GnuplotTerminal {
+    /// A PNG image, written to `render.png`
+    PngCairo,
+    /// An SVG image, written to `render.svg`
+    Svg,
+    /// Text, printed to stdout
+    Dumb,
+}
+
+impl GnuplotTerminal {
+    fn setup(self) -> &'static str {
+        match self {
+            GnuplotTerminal::PngCairo => "set terminal pngcairo size 800,500\nset output 'render.png'",
+            GnuplotTerminal::Svg => "set terminal svg size 800,500\nset output 'render.svg'",
+            GnuplotTerminal::Dumb => "set terminal dumb size 80,25",
+        }
+    }
+
+    fn output(self) -> Option<&'static str> {
+        match self {
+            GnuplotTerminal::PngCairo => Some("render.png"),
+            GnuplotTerminal::Svg => Some("render.svg"),
+            GnuplotTerminal::Dumb => None,
+        }
+    }
+}
+
+/// Writes a gnuplot script, with the data inlined, and runs it through gnuplot if it's installed
+pub struct Gnuplot {
+    terminal: GnuplotTerminal,
+}
+
+impl Gnuplot {
+    pub fn new(terminal: GnuplotTerminal) -> Self {
+        Self { terminal }
+    }
+
+    pub(crate) fn script(&self, data: &[Plot]) -> Result<String> {
+        flat("gnuplot", data, |_| true)?;
+        let mut script = String::new();
+        writeln!(script, "{}", self.terminal.setup())?;
+        writeln!(
+            script,
+            "set palette defined (0 '#440154', 1 '#3b528b', 2 '#21918c', 3 '#5ec962', 4 '#fde725')"
+        )?;
+        writeln!(script, "set style fill solid 0.8 border -1")?;
+        writeln!(script, "set key outside right top")?;
+
+        let mut elements: Vec<String> = Vec::new();
+        let mut command = "plot";
+
+        match Axes::fit(data) {
+            Some(axes) => {
+                axis(&mut script, "x", &axes.x)?;
+                axis(&mut script, "y", &axes.y)?;
+
+                let bars = data.iter().filter(|p| matches!(p, Plot::Bar { .. })).count();
+                let horizontal_bars = data
+                    .iter()
+                    .filter(|p| matches!(p, Plot::HorizontalBar { .. }))
+                    .count();
+                let (mut bar, mut horizontal_bar) = (0, 0);
+
+                for (i, plot) in data.iter().enumerate() {
+                    let block = format!("$data{}", i);
+                    match plot {
+                        Plot::Scatter { x, y, color, size, name } => {
+                            let xs = axes.x.positions(x);
+                            let colors = point_colors(color, xs.len(), PALETTE[i % PALETTE.len()]);
+                            let sizes = point_sizes(size, xs.len(), 6.0);
+                            let rows = xs
+                                .iter()
+                                .zip(axes.y.positions(y))
+                                .zip(sizes.iter().zip(colors))
+                                .filter(|((x, y), _)| x.is_finite() && y.is_finite())
+                                .map(|((x, y), (s, c))| vec![*x, y, s / 6.0, f64::from(rgb(c))]);
+                            datablock(&mut script, &block, rows)?;
+                            elements.push(format!(
+                                "{} using 1:2:3:4 with points pt 7 ps variable lc rgb variable title {}",
+                                block,
+                                string(&layer_name(i, name))
+                            ));
+                        }
+                        Plot::Line { x, y, missing, color, size, name } => {
+                            // gnuplot breaks a line at every blank line, which is an empty row between the runs
+                            let (xs, ys) = (axes.x.positions(x), axes.y.positions(y));
+                            let runs: Vec<Vec<Vec<f64>>> = line_runs(x, y, *missing)
+                                .into_iter()
+                                .map(|run| run.into_iter().map(|i| vec![xs[i], ys[i]]).collect())
+                                .collect();
+                            datablock(&mut script, &block, runs.join(&Vec::new()).into_iter())?;
+                            elements.push(format!(
+                                "{} using 1:2 with lines lw {} lc rgb '{}' title {}",
+                                block,
+                                point_sizes(size, 1, 1.5)[0],
+                                layer_color(i, color).hex(),
+                                string(&layer_name(i, name))
+                            ));
+                        }
+                        Plot::Bar { x, y, color, name } => {
+                            let width = axes.x.bar_width(x);
+                            let offset = axes.x.data_width(bar_offset(bar, bars, width));
+                            let bar_width = axes.x.data_width(width / bars as f64);
+                            let xs = axes.x.positions(x);
+                            let colors = point_colors(color, xs.len(), PALETTE[i % PALETTE.len()]);
+                            let rows = xs
+                                .iter()
+                                .zip(axes.y.positions(y))
+                                .zip(colors)
+                                .filter(|((x, y), _)| x.is_finite() && y.is_finite())
+                                .map(|((x, y), c)| vec![x + offset, y, bar_width, f64::from(rgb(c))]);
+                            datablock(&mut script, &block, rows)?;
+                            elements.push(format!(
+                                "{} using 1:2:3:4 with boxes lc rgb variable title {}",
+                                block,
+                                string(&layer_name(i, name))
+                            ));
+                            bar += 1;
+                        }
+                        Plot::HorizontalBar { x, y, color, name } => {
+                            let height = axes.y.bar_width(y);
+                            let offset = axes.y.data_width(bar_offset(horizontal_bar, horizontal_bars, height));
+                            let half = axes.y.data_width(height / horizontal_bars as f64) / 2.0;
+                            let xs = axes.x.positions(x);
+                            let colors = point_colors(color, xs.len(), PALETTE[i % PALETTE.len()]);
+                            let rows = xs
+                                .iter()
+                                .zip(axes.y.positions(y))
+                                .zip(colors)
+                                .filter(|((x, y), _)| x.is_finite() && y.is_finite())
+                                .map(|((x, y), c)| {
+                                    let y = y + offset;
+                                    vec![x / 2.0, y, x.min(0.0), x.max(0.0), y - half, y + half, f64::from(rgb(c))]
+                                });
+                            datablock(&mut script, &block, rows)?;
+                            elements.push(format!(
+                                "{} using 1:2:3:4:5:6:7 with boxxyerror lc rgb variable title {}",
+                                block,
+                                string(&layer_name(i, name))
+                            ));
+                            horizontal_bar += 1;
+                        }
+                        Plot::Box {
+                            x,
+                            y,
+                            options,
+                            color,
+                            name,
+                        } => {
+                            let shapes = box_shapes(i, x, y, *options, color, name, &axes);
+                            let mut titled = Vec::new();
+                            for (b, shape) in shapes.iter().enumerate() {
+                                let c = shape.color.hex();
+                                // Each color gets a single key entry, on its first box
+                                let title = if titled.contains(&shape.label) {
+                                    String::from("''")
+                                } else {
+                                    titled.push(shape.label.to_string());
+                                    string(&shape.label)
+                                };
+
+                                let outline = format!("$box{}_{}", i, b);
+                                datablock(&mut script, &outline, shape.outline().into_iter().map(|(x, y)| vec![x, y]))?;
+                                elements.push(format!(
+                                    "{} using 1:2 with filledcurves closed fs transparent solid 0.5 border lc rgb '{}' \
+                                     title {}",
+                                    outline, c, title
+                                ));
+
+                                let lines = format!("$lines{}_{}", i, b);
+                                let segment = |[(x1, y1), (x2, y2)]: [(f64, f64); 2]| vec![x1, y1, x2 - x1, y2 - y1];
+                                let mut rows: Vec<Vec<f64>> = vec![segment(shape.median())];
+                                rows.extend(shape.whiskers().into_iter().map(segment));
+                                rows.extend(shape.mean().map(segment));
+                                datablock(&mut script, &lines, rows.into_iter())?;
+                                elements.push(format!(
+                                    "{} every ::0::0 using 1:2:3:4 with vectors nohead lw 2 lc rgb '{}' notitle",
+                                    lines, c
+                                ));
+                                elements.push(format!(
+                                    "{} every ::1::4 using 1:2:3:4 with vectors nohead lc rgb '{}' notitle",
+                                    lines, c
+                                ));
+                                if shape.mean().is_some() {
+                                    elements.push(format!(
+                                        "{} every ::5::5 using 1:2:3:4 with vectors nohead dt 2 lc rgb '{}' notitle",
+                                        lines, c
+                                    ));
+                                }
+
+                                let outliers = shape.outliers();
+                                if !outliers.is_empty() {
+                                    let block = format!("$outliers{}_{}", i, b);
+                                    datablock(&mut script, &block, outliers.into_iter().map(|(x, y)| vec![x, y]))?;
+                                    elements.push(format!(
+                                        "{} using 1:2 with points pt 6 lc rgb '{}' notitle",
+                                        block, c
+                                    ));
+                                }
+                            }
+                        }
+                        Plot::Violin {
+                            x,
+                            group,
+                            bandwidth,
+                            inner,
+                            color,
+                            name,
+                        } => {
+                            let c = layer_color(i, color).hex();
+                            let shapes = violin_shapes(x, group, &layer_name(i, name), *bandwidth, &axes.y);
+                            for (g, shape) in shapes.iter().enumerate() {
+                                let outline = format!("$violin{}_{}", i, g);
+                                let rows = shape.outline().into_iter().map(|(x, y)| vec![x, y]);
+                                datablock(&mut script, &outline, rows)?;
+                                // Only the first violin of a layer gets a key entry
+                                let title = if g == 0 { string(&layer_name(i, name)) } else { String::from("''") };
+                                elements.push(format!(
+                                    "{} using 1:2 with filledcurves closed fs transparent solid 0.5 border lc rgb '{}' \
+                                     title {}",
+                                    outline, c, title
+                                ));
+
+                                let stats = &shape.violin.stats;
+                                let (y, half) = (shape.center, shape.box_half());
+                                let inner_block = format!("$inner{}_{}", i, g);
+                                match inner {
+                                    ViolinInner::Box => {
+                                        let rows = vec![vec![stats.median, y, stats.q1, stats.q3, y - half, y + half]];
+                                        datablock(&mut script, &inner_block, rows.into_iter())?;
+                                        elements.push(format!(
+                                            "{} using 1:2:3:4:5:6 with boxxyerror fs solid 1 lc rgb '{}' notitle",
+                                            inner_block, c
+                                        ));
+                                        let whiskers = format!("$whiskers{}_{}", i, g);
+                                        let length = stats.upper_whisker - stats.lower_whisker;
+                                        let rows = vec![vec![stats.lower_whisker, y, length, 0.0]];
+                                        datablock(&mut script, &whiskers, rows.into_iter())?;
+                                        elements.push(format!(
+                                            "{} using 1:2:3:4 with vectors nohead lc rgb '{}' notitle",
+                                            whiskers, c
+                                        ));
+                                        let median = format!("$median{}_{}", i, g);
+                                        let rows = vec![vec![stats.median, y - half, 0.0, 2.0 * half]];
+                                        datablock(&mut script, &median, rows.into_iter())?;
+                                        elements.push(format!(
+                                            "{} using 1:2:3:4 with vectors nohead lw 2 lc rgb 'white' notitle",
+                                            median
+                                        ));
+                                    }
+                                    ViolinInner::Quartiles => {
+                                        let rows = vec![stats.q1, stats.median, stats.q3].into_iter().map(|q| {
+                                            let (bottom, top) = shape.span(q);
+                                            vec![q, bottom, 0.0, top - bottom]
+                                        });
+                                        datablock(&mut script, &inner_block, rows)?;
+                                        // The quartiles are dashed, and the median in between them solid
+                                        for (row, dashtype) in &[(0, 2), (1, 1), (2, 2)] {
+                                            elements.push(format!(
+                                                "{} every ::{r}::{r} using 1:2:3:4 with vectors nohead dt {} \
+                                                 lc rgb '{}' notitle",
+                                                inner_block,
+                                                dashtype,
+                                                c,
+                                                r = row
+                                            ));
+                                        }
+                                    }
+                                    ViolinInner::None => {}
+                                }
+                            }
+                        }
+                        Plot::Histogram { x, binning, color, name } => {
+                            let rows = histogram_bars(x, *binning, &axes.x)
+                                .into_iter()
+                                .map(|(start, end, count)| vec![(start + end) / 2.0, count, end - start]);
+                            datablock(&mut script, &block, rows)?;
+                            elements.push(format!(
+                                "{} using 1:2:3 with boxes lc rgb '{}' title {}",
+                                block,
+                                layer_color(i, color).hex(),
+                                string(&layer_name(i, name))
+                            ));
+                        }
+                        Plot::Heatmap { x, y, z, options, .. } => {
+                            // Cells are drawn as boxes around their center, so grids with uneven spacing keep it
+                            let map = ColorMap::heatmap(z, options);
+                            palette(&mut script, &map)?;
+
+                            let cells = heatmap_cells(x, y, z, options);
+                            for cell in &cells {
+                                if let Some(text) = &cell.text {
+                                    writeln!(
+                                        script,
+                                        "set label {} at {},{} center front tc rgb '{}'",
+                                        string(text),
+                                        (cell.left + cell.right) / 2.0,
+                                        (cell.bottom + cell.top) / 2.0,
+                                        map.color(cell.value).contrast().hex()
+                                    )?;
+                                }
+                            }
+                            let rows = cells.iter().map(|c| {
+                                vec![
+                                    (c.left + c.right) / 2.0,
+                                    (c.bottom + c.top) / 2.0,
+                                    (c.right - c.left).abs() / 2.0,
+                                    (c.top - c.bottom).abs() / 2.0,
+                                    c.value,
+                                ]
+                            });
+                            datablock(&mut script, &block, rows)?;
+                            elements.push(format!(
+                                "{} using 1:2:3:4:5 with boxxyerror fs solid 1.0 noborder lc palette notitle",
+                                block
+                            ));
+                        }
+                        Plot::Contour { x, y, z, options, .. } => {
+                            // Every segment is a separate line, between blank lines, colored by its level
+                            let map = ColorMap::grid(z, &options.colorscale);
+                            palette(&mut script, &map)?;
+                            let lines = contour_lines(x, y, z, options);
+                            let rows = lines.iter().flat_map(|line| {
+                                line.segments.iter().flat_map(move |[(x0, y0), (x1, y1)]| {
+                                    vec![vec![*x0, *y0, line.level], vec![*x1, *y1, line.level], Vec::new()]
+                                })
+                            });
+                            datablock(&mut script, &block, rows)?;
+                            elements.push(format!("{} using 1:2:3 with lines lw 1.5 lc palette notitle", block));
+                        }
+                        Plot::Pie { .. }
+                        | Plot::Surface { .. }
+                        | Plot::Treemap { .. }
+                        | Plot::ParallelCoordinates { .. } => {}
+                    }
+                }
+            }
+            // Surfaces are never drawn alongside other plots, so they get a 3D plot of their own
+            None if data.iter().any(|p| matches!(p, Plot::Surface { .. })) => {
+                command = "splot";
+                writeln!(script, "set pm3d depthorder\nset hidden3d")?;
+                for (i, plot) in data.iter().enumerate() {
+                    if let Plot::Surface { x, y, z, colorscale, name } = plot {
+                        let MatrixData::Quantitative(rows) = z;
+                        let columns = rows.first().map_or(0, |r| r.len());
+                        let (xs, ys) = (grid_centers(x, columns), grid_centers(y, rows.len()));
+                        palette(&mut script, &ColorMap::grid(z, colorscale))?;
+                        // gnuplot reads a grid as rows of points, with a blank line after every row
+                        let block = format!("$data{}", i);
+                        let points = rows.iter().zip(&ys).flat_map(|(row, y)| {
+                            let row = row.iter().zip(&xs).map(move |(z, x)| vec![*x, *y, *z]);
+                            row.chain(Some(Vec::new()))
+                        });
+                        datablock(&mut script, &block, points)?;
+                        elements.push(format!(
+                            "{} using 1:2:3 with pm3d title {}",
+                            block,
+                            string(&layer_name(i, name))
+                        ));
+                    }
+                }
+            }
+            None => {
+                // Only pie charts, treemaps and parallel coordinates, which are drawn side by side on a hidden square
+                // grid
+                let panels: Vec<(usize, &Plot)> = data
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, p)| {
+                        matches!(p, Plot::Pie { .. } | Plot::Treemap { .. } | Plot::ParallelCoordinates { .. })
+                    })
+                    .collect();
+                writeln!(script, "unset border\nunset tics\nset size ratio -1")?;
+                writeln!(script, "set xrange [-1.2:{}]\nset yrange [-1.2:1.2]", panels.len() as f64 * 2.4 - 1.2)?;
+
+                for (i, (idx, plot)) in panels.iter().enumerate() {
+                    let center = i as f64 * 2.4;
+                    match plot {
+                        Plot::Pie { x, color, .. } => {
+                            let block = format!("$pie{}", i);
+                            let slices = pie_slices(x);
+                            let total: f64 = slices.iter().map(|(_, v)| v).sum();
+                            let colors: Vec<Rgb> = match color {
+                                Some(_) => point_colors(color, slices.len(), PALETTE[0]),
+                                None => (0..slices.len()).map(|i| PALETTE[i % PALETTE.len()]).collect(),
+                            };
+
+                            // Slices start at twelve o'clock and go clockwise, while gnuplot measures angles
+                            // counterclockwise from three o'clock
+                            let mut angle = 90.0;
+                            let mut rows = Vec::new();
+                            for (_, value) in &slices {
+                                let sweep = value / total * 360.0;
+                                rows.push(vec![center, 0.0, 1.0, angle - sweep, angle]);
+                                angle -= sweep;
+                            }
+                            datablock(&mut script, &block, rows.into_iter())?;
+
+                            for (s, ((label, _), c)) in slices.iter().zip(colors).enumerate() {
+                                elements.push(format!(
+                                    "{} every ::{s}::{s} using 1:2:3:4:5 with circles lc rgb '{}' title {}",
+                                    block,
+                                    c.hex(),
+                                    string(label),
+                                    s = s
+                                ));
+                            }
+                        }
+                        Plot::Treemap {
+                            label,
+                            parent,
+                            value,
+                            color,
+                            ..
+                        } => {
+                            let block = format!("$treemap{}", i);
+                            let rect = Rect { x0: center - 1.1, y0: -1.1, x1: center + 1.1, y1: 1.1 };
+                            let tiles = tiles(label, parent, value, color, rect);
+                            for tile in tiles.iter().filter(|t| t.leaf) {
+                                writeln!(
+                                    script,
+                                    "set label {} at {},{} center tc rgb '{}' front",
+                                    string(&tile.label),
+                                    (tile.rect.x0 + tile.rect.x1) / 2.0,
+                                    (tile.rect.y0 + tile.rect.y1) / 2.0,
+                                    tile.text_color().hex()
+                                )?;
+                            }
+                            let rows = tiles.iter().map(|t| {
+                                let Rect { x0, y0, x1, y1 } = t.rect;
+                                vec![(x0 + x1) / 2.0, (y0 + y1) / 2.0, x0, x1, y0, y1, f64::from(rgb(t.color))]
+                            });
+                            datablock(&mut script, &block, rows)?;
+                            elements.push(format!(
+                                "{} using 1:2:3:4:5:6:7 with boxxyerror fs solid 1 border rgb 'white' lc rgb variable \
+                                 notitle",
+                                block
+                            ));
+                        }
+                        Plot::ParallelCoordinates { dimensions, color, .. } => {
+                            // The lines of the data points are separated by blank lines, and each dimension's axis
+                            // is an arrow without a head, with its ticks as labels
+                            let block = format!("$parallel{}", i);
+                            let scales: Vec<Scale> = dimensions.iter().map(|(_, d)| dimension_scale(d)).collect();
+                            let units: Vec<Vec<f64>> =
+                                dimensions.iter().zip(&scales).map(|((_, d), s)| s.units(d)).collect();
+                            let len = units.first().map_or(0, |u| u.len());
+                            let colors = point_colors(color, len, PALETTE[idx % PALETTE.len()]);
+                            let x = |d: usize| center - 1.0 + 2.0 * dimension_position(d, dimensions.len());
+                            let y = |unit: f64| unit * 2.0 - 1.0;
+
+                            writeln!(script, "{} << EOD", block)?;
+                            for (p, c) in colors.iter().enumerate() {
+                                for (d, u) in units.iter().enumerate() {
+                                    writeln!(script, "{} {} {}", x(d), y(u[p]), rgb(*c))?;
+                                }
+                                writeln!(script)?;
+                            }
+                            writeln!(script, "EOD")?;
+                            elements.push(format!("{} using 1:2:3 with lines lc rgb variable notitle", block));
+
+                            for (d, ((name, _), scale)) in dimensions.iter().zip(&scales).enumerate() {
+                                writeln!(
+                                    script,
+                                    "set arrow from {x},-1 to {x},1 nohead front lc rgb 'black'",
+                                    x = x(d)
+                                )?;
+                                writeln!(script, "set label {} at {},-1.1 center", string(name), x(d))?;
+                                for tick in scale.ticks(5) {
+                                    writeln!(
+                                        script,
+                                        "set label {} at {},{} right offset -0.5,0",
+                                        string(&tick.label),
+                                        x(d),
+                                        y(tick.position)
+                                    )?;
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        writeln!(script, "{} {}", command, elements.join(", \\\n     "))?;
+        Ok(script)
+    }
+}
+
+impl Renderable for Gnuplot {
+    fn render(&self, data: &[Plot], display: bool) -> Result<()> {
+        use super::util::{open_in_viewer, viewer_error, write_to_file};
+        use std::path::Path;
+        use std::process::{Command, Stdio};
+
+        let script = self.script(data)?;
+        write_to_file(Path::new("render.gp"), &script)?;
+
+        // Without gnuplot installed, the script is left for the user to run elsewhere
+        let mut gnuplot = match Command::new("gnuplot").stdin(Stdio::piped()).spawn() {
+            Ok(child) => child,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(viewer_error("gnuplot", e)),
+        };
+        gnuplot
+            .stdin
+            .take()
+            .expect("gnuplot was started with a piped stdin")
+            .write_all(script.as_bytes())?;
+        let status = gnuplot.wait().map_err(|e| viewer_error("gnuplot", e))?;
+        if !status.success() {
+            return Err(viewer_error("gnuplot", io::Error::other(format!("exited with {}", status))));
+        }
+
+        if let (true, Some(output)) = (display, self.terminal.output()) {
+            open_in_viewer(Path::new(output))?;
+        }
+        Ok(())
+    }
+
+    fn write(&self, data: &[Plot], writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(self.script(data)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Set the range of an axis, labelling each category on a band scale, and round dates and times on a time scale
+fn axis(script: &mut String, name: &str, scale: &Scale) -> fmt::Result {
+    writeln!(script, "set {}range [{}:{}]", name, scale.value(0.0), scale.value(1.0))?;
+    match scale {
+        Scale::Band(categories) => {
+            let tics: Vec<String> = categories
+                .iter()
+                .enumerate()
+                .map(|(i, c)| format!("{} {}", string(c), i))
+                .collect();
+            writeln!(script, "set {}tics ({})", name, tics.join(", "))?;
+        }
+        Scale::Time { .. } => {
+            let tics: Vec<String> = scale
+                .ticks(5)
+                .iter()
+                .map(|t| format!("{} {}", string(&t.label), t.value))
+                .collect();
+            writeln!(script, "set {}tics ({})", name, tics.join(", "))?;
+        }
+        Scale::Linear { .. } => {}
+    }
+    Ok(())
+}
+
+/// A color as a single integer, for use with `lc rgb variable`
+fn rgb(color: Rgb) -> u32 {
+    (u32::from(color.0) << 16) | (u32::from(color.1) << 8) | u32::from(color.2)
+}
+
+/// An inline data block, with one row of whitespace-separated numbers per line
+fn datablock(script: &mut String, name: &str, rows: impl Iterator<Item = Vec<f64>>) -> fmt::Result {
+    writeln!(script, "{} << EOD", name)?;
+    for row in rows {
+        let row: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+        writeln!(script, "{}", row.join(" "))?;
+    }
+    writeln!(script, "EOD")
+}
+
+/// Color the palette, and with it the colorbar, by a color map
+fn palette(script: &mut String, map: &ColorMap) -> fmt::Result {
+    let stops: Vec<String> = map.stops.iter().map(|(t, c)| format!("{} '{}'", t, c.hex())).collect();
+    writeln!(script, "set palette defined ({})", stops.join(", "))?;
+    writeln!(script, "set cbrange [{}:{}]", map.min, map.max)
+}
+
+/// A single-quoted gnuplot string, where quotes are escaped by doubling them. Nothing else can be escaped, and a line
+/// break would end the command, so control characters are replaced by spaces.
+fn string(text: &str) -> String {
+    let text: String = text.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ContourOptions, Layer, Missing};
+
+    fn script(terminal: GnuplotTerminal, plot: Plot) -> String {
+        Gnuplot::new(terminal).script(&[plot]).unwrap()
+    }
+
+    #[test]
+    fn terminals() {
+        let layer = Layer::new().x(vec![1.0, 2.0]).y(vec![3.0, 4.0]);
+        let png = script(GnuplotTerminal::PngCairo, Plot::scatter(&layer));
+        assert!(png.starts_with("set terminal pngcairo size 800,500\nset output 'render.png'\n"));
+        let svg = script(GnuplotTerminal::Svg, Plot::scatter(&layer));
+        assert!(svg.starts_with("set terminal svg size 800,500\nset output 'render.svg'\n"));
+        let dumb = script(GnuplotTerminal::Dumb, Plot::scatter(&layer));
+        assert!(dumb.starts_with("set terminal dumb size 80,25\n"));
+        assert!(!dumb.contains("set output"));
+    }
+
+    #[test]
+    fn categorical_tics() {
+        let layer = Layer::new().x(vec!["a", "b's"]).y(vec![1.0, 2.0]);
+        let script = script(GnuplotTerminal::Dumb, Plot::bar(&layer));
+        assert!(script.contains("set xrange [-0.5:1.5]\nset xtics ('a' 0, 'b''s' 1)\n"));
+        assert!(script.contains("$data0 << EOD\n0 1 0.8 2062260\n1 2 0.8 2062260\nEOD\n"));
+    }
+
+    #[test]
+    fn line_gaps_are_blank_rows() {
+        let layer = Layer::new()
+            .x(vec![Some(1.0), Some(2.0), None, Some(4.0), Some(5.0)])
+            .y(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        let skipped = script(GnuplotTerminal::Dumb, Plot::line(&layer));
+        assert!(skipped.contains("$data0 << EOD\n1 1\n2 2\n\n4 4\n5 5\nEOD\n"));
+        let joined = script(GnuplotTerminal::Dumb, Plot::line_with(&layer, Missing::Interpolate));
+        assert!(joined.contains("$data0 << EOD\n1 1\n2 2\n4 4\n5 5\nEOD\n"));
+    }
+
+    #[test]
+    fn missing_bars_are_left_out() {
+        let layer = Layer::new().x(vec!["a", "b"]).y(vec![1.0, f64::NAN]);
+        let vertical = script(GnuplotTerminal::Dumb, Plot::bar(&layer));
+        assert!(vertical.contains("$data0 << EOD\n0 1 0.8 2062260\nEOD\n"));
+        let horizontal = Layer::new().x(vec![1.0, f64::NAN]).y(vec!["a", "b"]);
+        let horizontal = script(GnuplotTerminal::Dumb, Plot::horizontal_bar(&horizontal));
+        assert!(horizontal.contains("$data0 << EOD\n0.5 0 0 1 -0.4 0.4 2062260\nEOD\n"));
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(string("it's"), "'it''s'");
+        assert_eq!(string("two\nlines\r\tand\u{1b}"), "'two lines  and '");
+        let layer = Layer::new().x(vec![1.0]).y(vec![1.0]).name("a\nb");
+        assert!(script(GnuplotTerminal::Dumb, Plot::scatter(&layer)).contains("title 'a b'\n"));
+    }
+
+    #[test]
+    fn contours_and_surfaces() {
+        let layer = Layer::new().z(vec![vec![0.0, 4.0], vec![0.0, 4.0]]);
+        let options = ContourOptions { levels: 1, ..Default::default() };
+        let contour = script(GnuplotTerminal::Dumb, Plot::contour_with(&layer, options));
+        assert!(contour.contains("$data0 << EOD\n0.5 0 2\n0.5 1 2\n\nEOD\n"));
+        assert!(contour.contains("plot $data0 using 1:2:3 with lines lw 1.5 lc palette notitle"));
+
+        let surface = script(GnuplotTerminal::Dumb, Plot::surface(&layer));
+        assert!(surface.contains("$data0 << EOD\n0 0 0\n1 0 4\n\n0 1 0\n1 1 4\n\nEOD\n"));
+        assert!(surface.contains("splot $data0 using 1:2:3 with pm3d title 'trace 0'"));
+    }
+}
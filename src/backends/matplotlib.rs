@@ -0,0 +1,747 @@
+use super::scale::{
+    box_shapes, cell_edges, contour_levels, dimension_scale, flat, grid_centers, heatmap_cells, layer_color,
+    layer_name, line_runs, pie_slices, point_colors, point_sizes, subset, violin_shapes, Axes, ColorMap, Scale,
+    PALETTE,
+};
+use super::time::format_timestamp;
+use super::treemap::{tiles, Rect};
+use super::Renderable;
+use crate::data::{MatrixData, Missing, VectorData};
+use crate::error::ConspireError;
+use crate::stats::{histogram, Histogram, ViolinInner};
+use crate::Plot;
+
+use std::fmt::Write as FmtWrite;
+use std::io::Write;
+
+type Result<T> = std::result::Result<T, ConspireError>;
+
+/// Writes a standalone Python script drawing the plots with matplotlib's pyplot interface
+pub struct Matplotlib {}
+
+impl Matplotlib {
+    pub(crate) fn script(&self, data: &[Plot]) -> Result<String> {
+        flat("matplotlib", data, |_| true)?;
+        let mut script = String::new();
+        let treemaps = data.iter().filter(|p| matches!(p, Plot::Treemap { .. })).count();
+        let surfaces = data.iter().any(|p| matches!(p, Plot::Surface { .. }));
+        writeln!(script, "import matplotlib.pyplot as plt")?;
+        if surfaces || data.iter().any(temporal) {
+            writeln!(script, "import numpy as np")?;
+        }
+        if treemaps > 0 {
+            writeln!(script, "from matplotlib.patches import Rectangle")?;
+        }
+        if data
+            .iter()
+            .any(|p| matches!(p, Plot::Heatmap { .. } | Plot::Contour { .. } | Plot::Surface { .. }))
+        {
+            writeln!(script, "from matplotlib.colors import LinearSegmentedColormap")?;
+        }
+        if surfaces {
+            writeln!(script, "\nfig, ax = plt.subplots(subplot_kw={{'projection': '3d'}})")?;
+        } else {
+            writeln!(script, "\nfig, ax = plt.subplots()")?;
+        }
+        // matplotlib places categories in the order it first sees them, so ordinal categories are registered up front
+        let (x_order, y_order) = ordinal_axes(data);
+        for (axis, order) in [("x", x_order), ("y", y_order)] {
+            if !order.is_empty() {
+                let order: Vec<String> = order.iter().map(|c| string(c)).collect();
+                writeln!(script, "ax.{}axis.update_units([{}])", axis, order.join(", "))?;
+            }
+        }
+
+        let mut legend = false;
+        let mut treemap = 0;
+
+        for (i, plot) in data.iter().enumerate() {
+            match plot {
+                Plot::Scatter { x, y, color, size, name } => {
+                    let sizes: Vec<f64> = point_sizes(size, x.len(), 6.0).iter().map(|s| s * s).collect();
+                    writeln!(
+                        script,
+                        "ax.scatter({}, {}, {}, s={}, label={})",
+                        python(x),
+                        python(y),
+                        point_color_args(i, x.len(), color),
+                        list(&sizes),
+                        string(&layer_name(i, name))
+                    )?;
+                    legend = true;
+                }
+                Plot::Line { x, y, missing, color, size, name } => {
+                    // matplotlib already breaks lines at NaN, so missing values are only left out to join the points
+                    // either side of the gaps
+                    let (x, y) = match missing {
+                        Missing::Skip => (python(&gaps(x)), python(&gaps(y))),
+                        Missing::Interpolate => {
+                            let points = line_runs(x, y, *missing).concat();
+                            (python(&subset(x, &points)), python(&subset(y, &points)))
+                        }
+                    };
+                    writeln!(
+                        script,
+                        "ax.plot({}, {}, color={}, linewidth={}, label={})",
+                        x,
+                        y,
+                        string(&layer_color(i, color).hex()),
+                        number(point_sizes(size, 1, 1.5)[0]),
+                        string(&layer_name(i, name))
+                    )?;
+                    legend = true;
+                }
+                Plot::Bar { x, y, color, name } => {
+                    writeln!(
+                        script,
+                        "ax.bar({}, {}, color={}, label={})",
+                        python(x),
+                        python(y),
+                        colors(i, x.len(), color),
+                        string(&layer_name(i, name))
+                    )?;
+                    legend = true;
+                }
+                Plot::HorizontalBar { x, y, color, name } => {
+                    writeln!(
+                        script,
+                        "ax.barh({}, {}, color={}, label={})",
+                        python(y),
+                        python(x),
+                        colors(i, y.len(), color),
+                        string(&layer_name(i, name))
+                    )?;
+                    legend = true;
+                }
+                Plot::Pie { x, color, .. } => {
+                    let slices = pie_slices(x);
+                    let labels: Vec<String> = slices.iter().map(|(label, _)| string(label)).collect();
+                    let values: Vec<f64> = slices.iter().map(|(_, value)| *value).collect();
+                    let slice_colors: Vec<String> = match color {
+                        Some(_) => point_colors(color, slices.len(), PALETTE[0]),
+                        None => (0..slices.len()).map(|i| PALETTE[i % PALETTE.len()]).collect(),
+                    }
+                    .iter()
+                    .map(|c| string(&c.hex()))
+                    .collect();
+                    writeln!(
+                        script,
+                        "ax.pie({}, labels=[{}], colors=[{}], startangle=90, counterclock=False)",
+                        list(&values),
+                        labels.join(", "),
+                        slice_colors.join(", ")
+                    )?;
+                }
+                Plot::Treemap {
+                    label,
+                    parent,
+                    value,
+                    color,
+                    ..
+                } => {
+                    // matplotlib has no treemaps, so the tiles are drawn as patches, side by side when there are
+                    // several treemaps
+                    let left = treemap as f64 * 1.7;
+                    let rect = Rect { x0: left, y0: 0.0, x1: left + 1.6, y1: 1.0 };
+                    for tile in tiles(label, parent, value, color, rect) {
+                        let Rect { x0, y0, x1, y1 } = tile.rect;
+                        writeln!(
+                            script,
+                            "ax.add_patch(Rectangle(({}, {}), {}, {}, facecolor={}, edgecolor='white'))",
+                            number(x0),
+                            number(y0),
+                            number(x1 - x0),
+                            number(y1 - y0),
+                            string(&tile.color.hex())
+                        )?;
+                        if tile.leaf {
+                            writeln!(
+                                script,
+                                "ax.text({}, {}, {}, ha='center', va='center', color={}, clip_on=True)",
+                                number((x0 + x1) / 2.0),
+                                number((y0 + y1) / 2.0),
+                                string(&tile.label),
+                                string(&tile.text_color().hex())
+                            )?;
+                        }
+                    }
+                    treemap += 1;
+                }
+                Plot::ParallelCoordinates { dimensions, color, .. } => {
+                    // Each dimension gets a vertical axis at its index, with the values scaled to the axis and its
+                    // ticks written next to it
+                    let scales: Vec<Scale> = dimensions.iter().map(|(_, data)| dimension_scale(data)).collect();
+                    let units: Vec<Vec<f64>> = dimensions.iter().zip(&scales).map(|((_, d), s)| s.units(d)).collect();
+                    let len = units.first().map_or(0, |u| u.len());
+                    let lines: Vec<String> = (0..len)
+                        .map(|p| list(&units.iter().map(|u| u[p]).collect::<Vec<f64>>()))
+                        .collect();
+                    let positions: Vec<f64> = (0..dimensions.len()).map(|d| d as f64).collect();
+                    writeln!(
+                        script,
+                        "for ys, c in zip([{}], {}):\n    ax.plot({}, ys, color=c, alpha=0.7)",
+                        lines.join(", "),
+                        colors(i, len, color),
+                        list(&positions)
+                    )?;
+                    for (d, scale) in scales.iter().enumerate() {
+                        writeln!(script, "ax.axvline({}, color='black', linewidth=1)", d)?;
+                        for tick in scale.ticks(5) {
+                            writeln!(
+                                script,
+                                "ax.text({}, {}, {}, ha='right', va='center')",
+                                number(d as f64 - 0.02),
+                                number(tick.position),
+                                string(&tick.label)
+                            )?;
+                        }
+                    }
+                    let names: Vec<String> = dimensions.iter().map(|(name, _)| string(name)).collect();
+                    writeln!(script, "ax.set_xticks({})", list(&positions))?;
+                    writeln!(script, "ax.set_xticklabels([{}])", names.join(", "))?;
+                    writeln!(script, "ax.set_yticks([])")?;
+                }
+                Plot::Box {
+                    x,
+                    y,
+                    options,
+                    color,
+                    name,
+                } => {
+                    let axes = match Axes::fit(data) {
+                        Some(axes) => axes,
+                        None => continue,
+                    };
+                    let mut titled: Vec<String> = Vec::new();
+                    for shape in box_shapes(i, x, y, *options, color, name, &axes) {
+                        let color = string(&shape.color.hex());
+                        let (xs, ys): (Vec<f64>, Vec<f64>) = shape.outline().into_iter().unzip();
+                        // Each color group gets a single legend entry, from its first box
+                        let label = if titled.contains(&shape.label) {
+                            String::from("None")
+                        } else {
+                            titled.push(shape.label.clone());
+                            string(&shape.label)
+                        };
+                        writeln!(
+                            script,
+                            "ax.fill({}, {}, facecolor={}, edgecolor={}, alpha=0.5, label={})",
+                            list(&xs),
+                            list(&ys),
+                            color,
+                            color,
+                            label
+                        )?;
+
+                        let mut lines = vec![(shape.median(), "-", 2.0)];
+                        lines.extend(shape.whiskers().into_iter().map(|w| (w, "-", 1.0)));
+                        lines.extend(shape.mean().map(|m| (m, "--", 1.0)));
+                        for ([(x0, y0), (x1, y1)], style, width) in lines {
+                            writeln!(
+                                script,
+                                "ax.plot([{}, {}], [{}, {}], color={}, linestyle='{}', linewidth={})",
+                                number(x0),
+                                number(x1),
+                                number(y0),
+                                number(y1),
+                                color,
+                                style,
+                                number(width)
+                            )?;
+                        }
+
+                        let (xs, ys): (Vec<f64>, Vec<f64>) = shape.outliers().into_iter().unzip();
+                        if !xs.is_empty() {
+                            writeln!(
+                                script,
+                                "ax.scatter({}, {}, facecolors='none', edgecolors={})",
+                                list(&xs),
+                                list(&ys),
+                                color
+                            )?;
+                        }
+                    }
+                    legend = true;
+                }
+                Plot::Violin {
+                    x,
+                    group,
+                    bandwidth,
+                    inner,
+                    color,
+                    name,
+                } => {
+                    let axes = match Axes::fit(data) {
+                        Some(axes) => axes,
+                        None => continue,
+                    };
+                    let color = string(&layer_color(i, color).hex());
+                    for (g, shape) in violin_shapes(x, group, &layer_name(i, name), *bandwidth, &axes.y)
+                        .iter()
+                        .enumerate()
+                    {
+                        let (xs, ys): (Vec<f64>, Vec<f64>) = shape.outline().into_iter().unzip();
+                        // Only the first violin of a layer gets a legend entry
+                        let label = if g == 0 { string(&layer_name(i, name)) } else { String::from("None") };
+                        writeln!(
+                            script,
+                            "ax.fill({}, {}, color={}, alpha=0.5, label={})",
+                            list(&xs),
+                            list(&ys),
+                            color,
+                            label
+                        )?;
+
+                        let stats = &shape.violin.stats;
+                        let (bottom, top) = (shape.center - shape.box_half(), shape.center + shape.box_half());
+                        match inner {
+                            ViolinInner::Box => {
+                                writeln!(
+                                    script,
+                                    "ax.hlines({}, {}, {}, color={}, linewidth=1)",
+                                    number(shape.center),
+                                    number(stats.lower_whisker),
+                                    number(stats.upper_whisker),
+                                    color
+                                )?;
+                                writeln!(
+                                    script,
+                                    "ax.fill_between([{}, {}], {}, {}, color={})",
+                                    number(stats.q1),
+                                    number(stats.q3),
+                                    number(bottom),
+                                    number(top),
+                                    color
+                                )?;
+                                writeln!(
+                                    script,
+                                    "ax.vlines({}, {}, {}, color='white', linewidth=2)",
+                                    number(stats.median),
+                                    number(bottom),
+                                    number(top)
+                                )?;
+                            }
+                            ViolinInner::Quartiles => {
+                                for (quartile, style) in &[(stats.q1, "--"), (stats.median, "-"), (stats.q3, "--")] {
+                                    let (bottom, top) = shape.span(*quartile);
+                                    writeln!(
+                                        script,
+                                        "ax.vlines({}, {}, {}, color={}, linestyles='{}', linewidth=1)",
+                                        number(*quartile),
+                                        number(bottom),
+                                        number(top),
+                                        color,
+                                        style
+                                    )?;
+                                }
+                            }
+                            ViolinInner::None => {}
+                        }
+                    }
+                    legend = true;
+                }
+                Plot::Histogram { x, binning, color, name } => {
+                    let style = format!(
+                        "color={}, edgecolor='white', label={}",
+                        string(&layer_color(i, color).hex()),
+                        string(&layer_name(i, name))
+                    );
+                    match histogram(x, *binning) {
+                        Histogram::Bins(bins) => {
+                            let starts: Vec<f64> = bins.iter().map(|b| b.start).collect();
+                            let counts: Vec<f64> = bins.iter().map(|b| b.count as f64).collect();
+                            let widths: Vec<f64> = bins.iter().map(|b| b.end - b.start).collect();
+                            // Bins of points in time start at a date, and are as wide as a duration
+                            let (starts, widths) = match x {
+                                VectorData::Temporal(_) => (python(&VectorData::Temporal(starts)), durations(&widths)),
+                                _ => (list(&starts), list(&widths)),
+                            };
+                            writeln!(
+                                script,
+                                "ax.bar({}, {}, width={}, align='edge', {})",
+                                starts,
+                                list(&counts),
+                                widths,
+                                style
+                            )?;
+                        }
+                        Histogram::Counts(counts) => {
+                            let (categories, counts): (Vec<String>, Vec<f64>) =
+                                counts.into_iter().map(|(c, n)| (c, n as f64)).unzip();
+                            writeln!(
+                                script,
+                                "ax.bar({}, {}, {})",
+                                python(&VectorData::Categorical(categories)),
+                                list(&counts),
+                                style
+                            )?;
+                        }
+                    }
+                    legend = true;
+                }
+                Plot::Heatmap { x, y, z, options, .. } => {
+                    let MatrixData::Quantitative(rows) = z;
+                    let columns = rows.first().map_or(0, |r| r.len());
+                    let map = ColorMap::heatmap(z, options);
+                    writeln!(script, "{}", colormap(&format!("heatmap{}", i), &map))?;
+                    writeln!(
+                        script,
+                        "im = ax.pcolormesh({}, {}, {}, cmap=cmap, vmin={}, vmax={})",
+                        coordinates(x, &cell_edges(x, columns)),
+                        coordinates(y, &cell_edges(y, rows.len())),
+                        matrix(z),
+                        number(map.min),
+                        number(map.max)
+                    )?;
+                    writeln!(script, "fig.colorbar(im, ax=ax)")?;
+                    for cell in heatmap_cells(x, y, z, options) {
+                        if let Some(text) = &cell.text {
+                            writeln!(
+                                script,
+                                "ax.text({}, {}, {}, ha='center', va='center', color={})",
+                                coordinate(x, (cell.left + cell.right) / 2.0),
+                                coordinate(y, (cell.bottom + cell.top) / 2.0),
+                                string(text),
+                                string(&map.color(cell.value).contrast().hex())
+                            )?;
+                        }
+                    }
+                }
+                Plot::Contour { x, y, z, options, name } => {
+                    let MatrixData::Quantitative(rows) = z;
+                    let columns = rows.first().map_or(0, |r| r.len());
+                    let map = ColorMap::grid(z, &options.colorscale);
+                    writeln!(script, "{}", colormap(&format!("contour{}", i), &map))?;
+                    writeln!(
+                        script,
+                        "cs = ax.contour({}, {}, {}, levels={}, cmap=cmap, vmin={}, vmax={})",
+                        coordinates(x, &grid_centers(x, columns)),
+                        coordinates(y, &grid_centers(y, rows.len())),
+                        matrix(z),
+                        list(&contour_levels(&map, options.levels)),
+                        number(map.min),
+                        number(map.max)
+                    )?;
+                    writeln!(script, "fig.colorbar(cs, ax=ax, label={})", string(&layer_name(i, name)))?;
+                }
+                Plot::Surface { x, y, z, colorscale, .. } => {
+                    let MatrixData::Quantitative(rows) = z;
+                    let columns = rows.first().map_or(0, |r| r.len());
+                    let map = ColorMap::grid(z, colorscale);
+                    writeln!(script, "{}", colormap(&format!("surface{}", i), &map))?;
+                    writeln!(
+                        script,
+                        "X, Y = np.meshgrid({}, {})",
+                        list(&grid_centers(x, columns)),
+                        list(&grid_centers(y, rows.len()))
+                    )?;
+                    writeln!(
+                        script,
+                        "im = ax.plot_surface(X, Y, np.array({}), cmap=cmap, vmin={}, vmax={})",
+                        matrix(z),
+                        number(map.min),
+                        number(map.max)
+                    )?;
+                    writeln!(script, "fig.colorbar(im, ax=ax)")?;
+                }
+            }
+        }
+
+        // Boxes, violins, heatmaps and contours are drawn at the index of their category, so those axes get the
+        // categories as ticks
+        if data.iter().any(|p| {
+            matches!(p, Plot::Box { .. } | Plot::Violin { .. } | Plot::Heatmap { .. } | Plot::Contour { .. })
+        }) {
+            if let Some(axes) = Axes::fit(data) {
+                for (axis, scale) in &[("x", &axes.x), ("y", &axes.y)] {
+                    if let Scale::Band(categories) = scale {
+                        let positions: Vec<f64> = (0..categories.len()).map(|c| c as f64).collect();
+                        let labels: Vec<String> = categories.iter().map(|c| string(c)).collect();
+                        writeln!(script, "ax.set_{}ticks({})", axis, list(&positions))?;
+                        writeln!(script, "ax.set_{}ticklabels([{}])", axis, labels.join(", "))?;
+                    }
+                }
+            }
+        }
+
+        if legend {
+            writeln!(script, "ax.legend()")?;
+        }
+        if treemaps > 0 && Axes::fit(data).is_none() {
+            writeln!(script, "ax.autoscale_view()")?;
+            writeln!(script, "ax.set_axis_off()")?;
+        }
+        writeln!(script, "\nplt.show()")?;
+        Ok(script)
+    }
+}
+
+impl Renderable for Matplotlib {
+    fn render(&self, data: &[Plot], display: bool) -> Result<()> {
+        use super::util::{run_program, write_to_file};
+        use std::path::Path;
+
+        let path = Path::new("render.py");
+        write_to_file(path, &self.script(data)?)?;
+
+        if display {
+            run_program("python3", &["render.py"])?;
+        }
+        Ok(())
+    }
+
+    fn write(&self, data: &[Plot], writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(self.script(data)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Color arguments for a scatter plot: quantitative colors go through a colormap, anything else is resolved to a
+/// color per point
+fn point_color_args(idx: usize, len: usize, color: &Option<VectorData>) -> String {
+    match color {
+        Some(VectorData::Quantitative(v)) if v.len() > 1 => format!("c={}, cmap='viridis'", list(v)),
+        _ => format!("color={}", colors(idx, len, color)),
+    }
+}
+
+fn colors(idx: usize, len: usize, color: &Option<VectorData>) -> String {
+    let colors: Vec<String> = point_colors(color, len, PALETTE[idx % PALETTE.len()])
+        .iter()
+        .map(|c| string(&c.hex()))
+        .collect();
+    format!("[{}]", colors.join(", "))
+}
+
+/// A Python literal holding some data
+fn python(data: &VectorData) -> String {
+    match data {
+        VectorData::Quantitative(v) => list(v),
+        VectorData::Categorical(v) | VectorData::Ordinal { values: v, .. } => {
+            let strings: Vec<String> = v.iter().map(|s| string(s)).collect();
+            format!("[{}]", strings.join(", "))
+        }
+        VectorData::Temporal(v) => {
+            let times: Vec<String> = v
+                .iter()
+                .map(|t| if t.is_finite() { string(&format_timestamp(*t).replace(' ', "T")) } else { string("NaT") })
+                .collect();
+            format!("np.array([{}], dtype='datetime64[ms]')", times.join(", "))
+        }
+    }
+}
+
+/// Durations in seconds as a numpy array, which matplotlib adds to dates
+fn durations(values: &[f64]) -> String {
+    let millis: Vec<String> = values.iter().map(|v| format!("{}", (v * 1000.0).round() as i64)).collect();
+    format!("np.array([{}], dtype='timedelta64[ms]')", millis.join(", "))
+}
+
+/// Positions along an axis placed by some data, as dates when the data holds points in time
+fn coordinates(data: &Option<VectorData>, values: &[f64]) -> String {
+    match data {
+        Some(VectorData::Temporal(_)) => python(&VectorData::Temporal(values.to_vec())),
+        _ => list(values),
+    }
+}
+
+/// A position along an axis placed by some data, as a date when the data holds points in time
+fn coordinate(data: &Option<VectorData>, value: f64) -> String {
+    match data {
+        Some(VectorData::Temporal(_)) => format!("{}[0]", coordinates(data, &[value])),
+        _ => number(value),
+    }
+}
+
+/// The categories of the ordinal data along the x and y axes, of the plots that matplotlib places by category itself
+fn ordinal_axes(data: &[Plot]) -> (Vec<String>, Vec<String>) {
+    let mut x_order: Vec<String> = Vec::new();
+    let mut y_order: Vec<String> = Vec::new();
+    let include = |order: &mut Vec<String>, data: &VectorData| {
+        if let VectorData::Ordinal { order: categories, .. } = data {
+            for category in categories {
+                if !order.contains(category) {
+                    order.push(category.to_string());
+                }
+            }
+        }
+    };
+    for plot in data {
+        match plot {
+            Plot::Scatter { x, y, .. }
+            | Plot::Line { x, y, .. }
+            | Plot::Bar { x, y, .. }
+            | Plot::HorizontalBar { x, y, .. } => {
+                include(&mut x_order, x);
+                include(&mut y_order, y);
+            }
+            Plot::Histogram { x, .. } => include(&mut x_order, x),
+            _ => {}
+        }
+    }
+    (x_order, y_order)
+}
+
+/// Whether a plot holds points in time, which are written as numpy dates
+fn temporal(plot: &Plot) -> bool {
+    let temporal = |data: &VectorData| matches!(data, VectorData::Temporal(_));
+    match plot {
+        Plot::Scatter { x, y, .. }
+        | Plot::Line { x, y, .. }
+        | Plot::Bar { x, y, .. }
+        | Plot::HorizontalBar { x, y, .. } => temporal(x) || temporal(y),
+        Plot::Histogram { x, .. } => temporal(x),
+        Plot::Heatmap { x, y, .. } | Plot::Contour { x, y, .. } => x.iter().chain(y.iter()).any(temporal),
+        _ => false,
+    }
+}
+
+/// Data with every missing value as NaN, including infinities, which matplotlib would otherwise try to draw
+fn gaps(data: &VectorData) -> VectorData {
+    match data {
+        VectorData::Quantitative(v) => {
+            VectorData::Quantitative(v.iter().map(|v| if v.is_finite() { *v } else { f64::NAN }).collect())
+        }
+        VectorData::Categorical(v) => VectorData::Categorical(v.clone()),
+        VectorData::Temporal(v) => VectorData::Temporal(v.clone()),
+        VectorData::Ordinal { values, order } => VectorData::Ordinal {
+            values: values.clone(),
+            order: order.clone(),
+        },
+    }
+}
+
+/// A colormap named `cmap`, going through the stops of a color map
+fn colormap(name: &str, map: &ColorMap) -> String {
+    let stops: Vec<String> = map
+        .stops
+        .iter()
+        .map(|(t, c)| format!("({}, {})", number(*t), string(&c.hex())))
+        .collect();
+    format!("cmap = LinearSegmentedColormap.from_list({}, [{}])", string(name), stops.join(", "))
+}
+
+fn matrix(data: &MatrixData) -> String {
+    match data {
+        MatrixData::Quantitative(rows) => {
+            let rows: Vec<String> = rows.iter().map(|r| list(r)).collect();
+            format!("[{}]", rows.join(", "))
+        }
+    }
+}
+
+fn list(values: &[f64]) -> String {
+    let numbers: Vec<String> = values.iter().map(|v| number(*v)).collect();
+    format!("[{}]", numbers.join(", "))
+}
+
+/// A Python float literal, spelling out the values Python has no literal for
+fn number(value: f64) -> String {
+    if value.is_nan() {
+        String::from("float('nan')")
+    } else if value.is_infinite() {
+        format!("float('{}inf')", if value < 0.0 { "-" } else { "" })
+    } else {
+        format!("{:?}", value)
+    }
+}
+
+/// A single-quoted Python string literal. Control characters other than the common ones are written as `\xNN`, so
+/// the script stays readable and no character can end the line.
+fn string(text: &str) -> String {
+    let mut literal = String::from("'");
+    for c in text.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '\'' => literal.push_str("\\'"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c.is_control() => literal.push_str(&format!("\\x{:02x}", u32::from(c))),
+            c => literal.push(c),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{string, Matplotlib};
+    use crate::{Binning, ContourOptions, Layer, Ordinal, Plot};
+
+    fn script(plot: Plot) -> String {
+        Matplotlib {}.script(&[plot]).unwrap()
+    }
+
+    #[test]
+    fn scatter() {
+        let layer = Layer::new().x(vec![1.0, 2.0]).y(vec![3.0, 4.0]).name("points");
+        let script = script(Plot::scatter(&layer));
+        assert!(script.starts_with("import matplotlib.pyplot as plt\n"));
+        assert!(script.contains(
+            "ax.scatter([1.0, 2.0], [3.0, 4.0], color=['#1f77b4', '#1f77b4'], s=[36.0, 36.0], label='points')"
+        ));
+        assert!(script.contains("ax.legend()"));
+        assert!(script.ends_with("plt.show()\n"));
+    }
+
+    #[test]
+    fn categorical_bars() {
+        let layer = Layer::new().x(vec!["a", "b"]).y(vec![1, 2]);
+        assert!(script(Plot::bar(&layer))
+            .contains("ax.bar(['a', 'b'], [1.0, 2.0], color=['#1f77b4', '#1f77b4'], label='trace 0')"));
+    }
+
+    #[test]
+    fn histogram_bins() {
+        let layer = Layer::new().x(vec![1.0, 2.0, 2.0, 3.0]);
+        assert!(script(Plot::histogram_with(&layer, Binning::Count(2))).contains(
+            "ax.bar([1.0, 2.0], [1.0, 3.0], width=[1.0, 1.0], align='edge', color='#1f77b4', edgecolor='white'"
+        ));
+    }
+
+    #[test]
+    fn ordinal_axis_order() {
+        let layer = Layer::new()
+            .x(Ordinal::new(vec!["high", "low"], vec!["low", "high"]))
+            .y(vec![2, 1]);
+        let script = script(Plot::bar(&layer));
+        let order = script.find("ax.xaxis.update_units(['low', 'high'])").unwrap();
+        assert!(order < script.find("ax.bar(").unwrap());
+    }
+
+    #[test]
+    fn imports() {
+        let heatmap = Layer::new().z(vec![vec![1.0, 2.0]]);
+        assert!(script(Plot::heatmap(&heatmap)).contains("from matplotlib.colors import LinearSegmentedColormap"));
+        let treemap = Layer::new().label(vec!["a", "b"]).parent(vec!["", "a"]).value(vec![1.0, 2.0]);
+        assert!(script(Plot::treemap(&treemap)).contains("from matplotlib.patches import Rectangle"));
+        let scatter = Layer::new().x(vec![1.0]).y(vec![1.0]);
+        assert!(!script(Plot::scatter(&scatter)).contains("numpy"));
+    }
+
+    #[test]
+    fn contours_and_surfaces() {
+        let layer = Layer::new().z(vec![vec![0.0, 4.0], vec![0.0, 4.0]]).y(vec![10.0, 20.0]);
+        let contour = script(Plot::contour_with(&layer, ContourOptions { levels: 3, ..Default::default() }));
+        assert!(contour.contains("fig, ax = plt.subplots()\n"));
+        assert!(contour.contains(
+            "cs = ax.contour([0.0, 1.0], [10.0, 20.0], [[0.0, 4.0], [0.0, 4.0]], levels=[1.0, 2.0, 3.0], cmap=cmap, \
+             vmin=0.0, vmax=4.0)"
+        ));
+
+        let surface = script(Plot::surface(&layer));
+        assert!(surface.contains("import numpy as np\n"));
+        assert!(surface.contains("fig, ax = plt.subplots(subplot_kw={'projection': '3d'})\n"));
+        assert!(surface.contains("X, Y = np.meshgrid([0.0, 1.0], [10.0, 20.0])\n"));
+        assert!(surface.contains("im = ax.plot_surface(X, Y, np.array([[0.0, 4.0], [0.0, 4.0]]), cmap=cmap"));
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(string("it's a\\b\n\t"), r"'it\'s a\\b\n\t'");
+        assert_eq!(string("a\u{0}b\u{1b}c\u{7f}\u{85}"), r"'a\x00b\x1bc\x7f\x85'");
+        assert_eq!(string("é ✓"), "'é ✓'");
+    }
+}
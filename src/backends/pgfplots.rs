@@ -0,0 +1,598 @@
+use super::scale::{
+    bar_offset, box_legend, box_shapes, contour_lines, dimension_position, dimension_scale, heatmap_cells,
+    histogram_bars, layer_color, layer_name, line_runs, point_colors, point_sizes, supported, violin_shapes, Axes,
+    ColorMap, Rgb, Scale, PALETTE,
+};
+use super::treemap::{tiles, Rect, Tile};
+use super::Renderable;
+use crate::data::VectorData;
+use crate::error::ConspireError;
+use crate::stats::ViolinInner;
+use crate::Plot;
+
+use std::fmt::{self, Write as FmtWrite};
+use std::io::Write;
+
+type Result<T> = std::result::Result<T, ConspireError>;
+
+/// Writes a LaTeX fragment drawing the plots with PGFPlots, to be `\input` into a document
+pub struct Pgfplots {}
+
+impl Pgfplots {
+    pub(crate) fn tex(&self, data: &[Plot]) -> Result<String> {
+        supported("pgfplots", data, |p| !matches!(p, Plot::Pie { .. } | Plot::Surface { .. }))?;
+        let mut tex = String::new();
+        writeln!(tex, "% Requires \\usepackage{{pgfplots}} in the preamble")?;
+        writeln!(tex, "\\begin{{tikzpicture}}")?;
+
+        let axes = match Axes::fit(data) {
+            Some(axes) => axes,
+            None => {
+                // Without axes, treemaps and parallel coordinates are drawn side by side with plain TikZ, in
+                // centimeters
+                let panels = data
+                    .iter()
+                    .filter(|p| matches!(p, Plot::Treemap { .. } | Plot::ParallelCoordinates { .. }))
+                    .count();
+                let width = 12.0 / panels.max(1) as f64;
+                let mut panel = 0;
+                for (i, plot) in data.iter().enumerate() {
+                    let left = panel as f64 * width;
+                    match plot {
+                        Plot::Treemap {
+                            label,
+                            parent,
+                            value,
+                            color,
+                            ..
+                        } => {
+                            let rect = Rect { x0: left + 0.1, y0: 0.0, x1: left + width - 0.1, y1: 8.0 };
+                            treemap_tiles(&mut tex, tiles(label, parent, value, color, rect))?;
+                            panel += 1;
+                        }
+                        Plot::ParallelCoordinates { dimensions, color, .. } => {
+                            let rect = Rect { x0: left + 1.0, y0: 0.0, x1: left + width - 0.5, y1: 8.0 };
+                            parallel_coordinates(&mut tex, rect, i, dimensions, color)?;
+                            panel += 1;
+                        }
+                        _ => unreachable!("only treemaps and parallel coordinates are drawn without axes"),
+                    }
+                }
+                writeln!(tex, "\\end{{tikzpicture}}")?;
+                return Ok(tex);
+            }
+        };
+
+        let mut options = vec![
+            String::from("width=12cm"),
+            String::from("height=8cm"),
+            String::from("legend pos=outer north east"),
+            String::from("legend cell align=left"),
+            String::from("colormap/viridis"),
+        ];
+        axis_options(&mut options, "x", &axes.x);
+        axis_options(&mut options, "y", &axes.y);
+        if let Some(map) = data.iter().find_map(|p| match p {
+            Plot::Heatmap { z, options, .. } => Some(ColorMap::heatmap(z, options)),
+            Plot::Contour { z, options, .. } => Some(ColorMap::grid(z, &options.colorscale)),
+            _ => None,
+        }) {
+            // An axis has a single colormap, which is used for the colorbar of the heatmap or contour plot. Colormap
+            // positions have to be whole multiples of its step, so they're given in thousandths.
+            let stops: Vec<String> = map
+                .stops
+                .iter()
+                .map(|(t, c)| format!("rgb255({}pt)=({},{},{})", (t * 1000.0).round(), c.0, c.1, c.2))
+                .collect();
+            options.retain(|o| o != "colormap/viridis");
+            options.push(format!("colormap={{heatmap}}{{[1pt] {}}}", stops.join("; ")));
+            options.push(format!("point meta min={}", map.min));
+            options.push(format!("point meta max={}", map.max));
+            options.push(String::from("colorbar"));
+        }
+        writeln!(tex, "\\begin{{axis}}[\n    {}\n]", options.join(",\n    "))?;
+
+        let bars = data.iter().filter(|p| matches!(p, Plot::Bar { .. })).count();
+        let horizontal_bars = data
+            .iter()
+            .filter(|p| matches!(p, Plot::HorizontalBar { .. }))
+            .count();
+        let (mut bar, mut horizontal_bar) = (0, 0);
+
+        for (i, plot) in data.iter().enumerate() {
+            match plot {
+                Plot::Scatter { x, y, color, size, name } => {
+                    let xs = axes.x.positions(x);
+                    let ys = axes.y.positions(y);
+                    let mark_size = point_sizes(size, 1, 6.0)[0] * 0.375;
+                    match color {
+                        Some(VectorData::Quantitative(c)) if c.len() > 1 => {
+                            let points: Vec<String> = xs
+                                .iter()
+                                .zip(ys.iter())
+                                .zip(c.iter())
+                                .filter(|((x, y), c)| x.is_finite() && y.is_finite() && c.is_finite())
+                                .map(|((x, y), c)| format!("({}, {}) [{}]", x, y, c))
+                                .collect();
+                            writeln!(
+                                tex,
+                                "\\addplot[scatter, only marks, mark=*, mark size={}pt, scatter src=explicit] coordinates {{{}}};",
+                                mark_size,
+                                points.join(" ")
+                            )?;
+                            legend_entry(&mut tex, i, name)?;
+                        }
+                        _ => {
+                            // Each color gets its own plot, of which only the first one shows up in the legend
+                            let colors = point_colors(color, xs.len(), layer_color(i, color));
+                            let mut groups: Vec<(Rgb, Vec<String>)> = Vec::new();
+                            let points = xs.iter().zip(ys.iter()).zip(colors);
+                            for ((x, y), c) in points.filter(|((x, y), _)| x.is_finite() && y.is_finite()) {
+                                let point = format!("({}, {})", x, y);
+                                match groups.iter_mut().find(|(g, _)| *g == c) {
+                                    Some((_, points)) => points.push(point),
+                                    None => groups.push((c, vec![point])),
+                                }
+                            }
+                            for (g, (c, points)) in groups.iter().enumerate() {
+                                writeln!(
+                                    tex,
+                                    "\\addplot[only marks, mark=*, mark size={}pt, color={}{}] coordinates {{{}}};",
+                                    mark_size,
+                                    rgb(*c),
+                                    if g == 0 { "" } else { ", forget plot" },
+                                    points.join(" ")
+                                )?;
+                                if g == 0 {
+                                    legend_entry(&mut tex, i, name)?;
+                                }
+                            }
+                        }
+                    }
+                }
+                Plot::Line { x, y, missing, color, size, name } => {
+                    // Each run between gaps gets its own plot, of which only the first one shows up in the legend
+                    let (xs, ys) = (axes.x.positions(x), axes.y.positions(y));
+                    for (r, run) in line_runs(x, y, *missing).iter().enumerate() {
+                        let points: Vec<String> = run.iter().map(|i| format!("({}, {})", xs[*i], ys[*i])).collect();
+                        writeln!(
+                            tex,
+                            "\\addplot[no marks, color={}, line width={}pt{}] coordinates {{{}}};",
+                            rgb(layer_color(i, color)),
+                            point_sizes(size, 1, 1.0)[0],
+                            if r == 0 { "" } else { ", forget plot" },
+                            points.join(" ")
+                        )?;
+                        if r == 0 {
+                            legend_entry(&mut tex, i, name)?;
+                        }
+                    }
+                }
+                Plot::Bar { x, y, color, name } => {
+                    let width = axes.x.bar_width(x);
+                    let offset = axes.x.data_width(bar_offset(bar, bars, width));
+                    let half = axes.x.data_width(width / bars as f64) / 2.0;
+                    let xs = axes.x.positions(x);
+                    let colors = point_colors(color, xs.len(), PALETTE[i % PALETTE.len()]);
+                    for ((x, y), c) in xs.iter().zip(axes.y.positions(y)).zip(colors) {
+                        let x = x + offset;
+                        writeln!(
+                            tex,
+                            "\\fill[fill={}] (axis cs:{}, 0) rectangle (axis cs:{}, {});",
+                            rgb(c),
+                            x - half,
+                            x + half,
+                            y
+                        )?;
+                    }
+                    area_legend_entry(&mut tex, i, color, name)?;
+                    bar += 1;
+                }
+                Plot::HorizontalBar { x, y, color, name } => {
+                    let height = axes.y.bar_width(y);
+                    let offset = axes.y.data_width(bar_offset(horizontal_bar, horizontal_bars, height));
+                    let half = axes.y.data_width(height / horizontal_bars as f64) / 2.0;
+                    let xs = axes.x.positions(x);
+                    let colors = point_colors(color, xs.len(), PALETTE[i % PALETTE.len()]);
+                    for ((x, y), c) in xs.iter().zip(axes.y.positions(y)).zip(colors) {
+                        let y = y + offset;
+                        writeln!(
+                            tex,
+                            "\\fill[fill={}] (axis cs:0, {}) rectangle (axis cs:{}, {});",
+                            rgb(c),
+                            y - half,
+                            x,
+                            y + half
+                        )?;
+                    }
+                    area_legend_entry(&mut tex, i, color, name)?;
+                    horizontal_bar += 1;
+                }
+                Plot::Box {
+                    x,
+                    y,
+                    options,
+                    color,
+                    name,
+                } => {
+                    let point = |(x, y): (f64, f64)| format!("(axis cs:{}, {})", x, y);
+                    for shape in box_shapes(i, x, y, *options, color, name, &axes) {
+                        let c = rgb(shape.color);
+                        let outline: Vec<String> = shape.outline().into_iter().map(point).collect();
+                        writeln!(
+                            tex,
+                            "\\draw[color={c}, fill={c}, fill opacity=0.5] {} -- cycle;",
+                            outline.join(" -- "),
+                            c = c
+                        )?;
+                        let [from, to] = shape.median();
+                        writeln!(tex, "\\draw[color={}, thick] {} -- {};", c, point(from), point(to))?;
+                        for [from, to] in shape.whiskers() {
+                            writeln!(tex, "\\draw[color={}] {} -- {};", c, point(from), point(to))?;
+                        }
+                        if let Some([from, to]) = shape.mean() {
+                            writeln!(tex, "\\draw[color={}, dashed] {} -- {};", c, point(from), point(to))?;
+                        }
+                        let outliers: Vec<String> = shape
+                            .outliers()
+                            .into_iter()
+                            .map(|(x, y)| format!("({}, {})", x, y))
+                            .collect();
+                        if !outliers.is_empty() {
+                            writeln!(
+                                tex,
+                                "\\addplot[only marks, mark=o, color={}, forget plot] coordinates {{{}}};",
+                                c,
+                                outliers.join(" ")
+                            )?;
+                        }
+                    }
+                    for (label, color) in box_legend(i, color, name) {
+                        writeln!(tex, "\\addlegendimage{{area legend, fill={}, draw=none}}", rgb(color))?;
+                        writeln!(tex, "\\addlegendentry{{{}}}", escape(&label))?;
+                    }
+                }
+                Plot::Violin {
+                    x,
+                    group,
+                    bandwidth,
+                    inner,
+                    color,
+                    name,
+                } => {
+                    let c = rgb(layer_color(i, color));
+                    for shape in violin_shapes(x, group, &layer_name(i, name), *bandwidth, &axes.y) {
+                        let points: Vec<String> = shape
+                            .outline()
+                            .into_iter()
+                            .map(|(x, y)| format!("(axis cs:{}, {})", x, y))
+                            .collect();
+                        writeln!(
+                            tex,
+                            "\\draw[color={c}, fill={c}, fill opacity=0.5] {} -- cycle;",
+                            points.join(" -- "),
+                            c = c
+                        )?;
+
+                        let stats = &shape.violin.stats;
+                        let (y, half) = (shape.center, shape.box_half());
+                        match inner {
+                            ViolinInner::Box => {
+                                writeln!(
+                                    tex,
+                                    "\\draw[color={}] (axis cs:{}, {y}) -- (axis cs:{}, {y});",
+                                    c,
+                                    stats.lower_whisker,
+                                    stats.upper_whisker,
+                                    y = y
+                                )?;
+                                writeln!(
+                                    tex,
+                                    "\\fill[fill={}] (axis cs:{}, {}) rectangle (axis cs:{}, {});",
+                                    c,
+                                    stats.q1,
+                                    y - half,
+                                    stats.q3,
+                                    y + half
+                                )?;
+                                writeln!(
+                                    tex,
+                                    "\\draw[color=white, thick] (axis cs:{m}, {}) -- (axis cs:{m}, {});",
+                                    y - half,
+                                    y + half,
+                                    m = stats.median
+                                )?;
+                            }
+                            ViolinInner::Quartiles => {
+                                let dashed = ", dashed";
+                                for (quartile, style) in &[(stats.q1, dashed), (stats.median, ""), (stats.q3, dashed)] {
+                                    let (bottom, top) = shape.span(*quartile);
+                                    writeln!(
+                                        tex,
+                                        "\\draw[color={}{}] (axis cs:{q}, {}) -- (axis cs:{q}, {});",
+                                        c,
+                                        style,
+                                        bottom,
+                                        top,
+                                        q = quartile
+                                    )?;
+                                }
+                            }
+                            ViolinInner::None => {}
+                        }
+                    }
+                    area_legend_entry(&mut tex, i, color, name)?;
+                }
+                Plot::Histogram { x, binning, color, name } => {
+                    let c = rgb(layer_color(i, color));
+                    for (start, end, count) in histogram_bars(x, *binning, &axes.x) {
+                        writeln!(
+                            tex,
+                            "\\fill[fill={}, draw=white] (axis cs:{}, 0) rectangle (axis cs:{}, {});",
+                            c, start, end, count
+                        )?;
+                    }
+                    area_legend_entry(&mut tex, i, color, name)?;
+                }
+                Plot::Heatmap { x, y, z, options, .. } => {
+                    // Cells are filled one by one rather than as a matrix plot, so uneven grids keep their spacing
+                    let map = ColorMap::heatmap(z, options);
+                    for cell in heatmap_cells(x, y, z, options) {
+                        let color = map.color(cell.value);
+                        writeln!(
+                            tex,
+                            "\\fill[fill={}] (axis cs:{}, {}) rectangle (axis cs:{}, {});",
+                            rgb(color),
+                            cell.left,
+                            cell.bottom,
+                            cell.right,
+                            cell.top
+                        )?;
+                        if let Some(text) = &cell.text {
+                            writeln!(
+                                tex,
+                                "\\node[text={}, font=\\small] at (axis cs:{}, {}) {{{}}};",
+                                rgb(color.contrast()),
+                                (cell.left + cell.right) / 2.0,
+                                (cell.bottom + cell.top) / 2.0,
+                                escape(text)
+                            )?;
+                        }
+                    }
+                }
+                Plot::Contour { x, y, z, options, .. } => {
+                    for line in contour_lines(x, y, z, options) {
+                        let segments: Vec<String> = line
+                            .segments
+                            .iter()
+                            .map(|[(x0, y0), (x1, y1)]| format!("(axis cs:{}, {}) -- (axis cs:{}, {})", x0, y0, x1, y1))
+                            .collect();
+                        writeln!(tex, "\\draw[color={}, thick] {};", rgb(line.color), segments.join(" "))?;
+                    }
+                }
+                Plot::Pie { .. } | Plot::Surface { .. } | Plot::Treemap { .. } | Plot::ParallelCoordinates { .. } => {
+                    unreachable!(
+                        "pies, surfaces, and treemaps and parallel coordinates on axes, are rejected before drawing"
+                    )
+                }
+            }
+        }
+
+        writeln!(tex, "\\end{{axis}}")?;
+        writeln!(tex, "\\end{{tikzpicture}}")?;
+        Ok(tex)
+    }
+}
+
+impl Renderable for Pgfplots {
+    /// Write the fragment to `render.tex`. The fragment has to be included in a document and compiled before it can
+    /// be viewed, so `display` is ignored.
+    fn render(&self, data: &[Plot], _display: bool) -> Result<()> {
+        use super::util::write_to_file;
+        use std::path::Path;
+
+        write_to_file(Path::new("render.tex"), &self.tex(data)?)?;
+        Ok(())
+    }
+
+    fn write(&self, data: &[Plot], writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(self.tex(data)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Fix the range of an axis to the one shared by all backends, labelling each category on a band scale, and round
+/// dates and times on a time scale
+fn axis_options(options: &mut Vec<String>, name: &str, scale: &Scale) {
+    options.push(format!("{}min={}", name, scale.value(0.0)));
+    options.push(format!("{}max={}", name, scale.value(1.0)));
+    match scale {
+        Scale::Band(categories) => {
+            let ticks: Vec<String> = (0..categories.len()).map(|i| i.to_string()).collect();
+            let labels: Vec<String> = categories.iter().map(|c| format!("{{{}}}", escape(c))).collect();
+            options.push(format!("{}tick={{{}}}", name, ticks.join(",")));
+            options.push(format!("{}ticklabels={{{}}}", name, labels.join(",")));
+        }
+        Scale::Time { .. } => {
+            let ticks = scale.ticks(5);
+            let positions: Vec<String> = ticks.iter().map(|t| t.value.to_string()).collect();
+            let labels: Vec<String> = ticks.iter().map(|t| format!("{{{}}}", escape(&t.label))).collect();
+            options.push(format!("{}tick={{{}}}", name, positions.join(",")));
+            options.push(format!("{}ticklabels={{{}}}", name, labels.join(",")));
+            // Timestamps are large numbers, which would otherwise get a common factor written next to the axis
+            options.push(format!("scaled {} ticks=false", name));
+        }
+        Scale::Linear { .. } => {}
+    }
+}
+
+/// Draw the tiles of a treemap, labelling the leaves
+fn treemap_tiles(tex: &mut String, tiles: Vec<Tile>) -> fmt::Result {
+    for tile in tiles {
+        let Rect { x0, y0, x1, y1 } = tile.rect;
+        writeln!(tex, "\\fill[fill={}, draw=white] ({}, {}) rectangle ({}, {});", rgb(tile.color), x0, y0, x1, y1)?;
+        if tile.leaf {
+            writeln!(
+                tex,
+                "\\node[text={}, font=\\small] at ({}, {}) {{{}}};",
+                rgb(tile.text_color()),
+                (x0 + x1) / 2.0,
+                (y0 + y1) / 2.0,
+                escape(&tile.label)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Draw a parallel coordinates plot, with a vertical axis per dimension spread across a rectangle
+fn parallel_coordinates(
+    tex: &mut String,
+    rect: Rect,
+    idx: usize,
+    dimensions: &[(String, VectorData)],
+    color: &Option<VectorData>,
+) -> fmt::Result {
+    let scales: Vec<Scale> = dimensions.iter().map(|(_, data)| dimension_scale(data)).collect();
+    let units: Vec<Vec<f64>> = dimensions.iter().zip(&scales).map(|((_, d), s)| s.units(d)).collect();
+    let len = units.first().map_or(0, |u| u.len());
+    let x = |d: usize| rect.x0 + rect.width() * dimension_position(d, dimensions.len());
+    let y = |unit: f64| rect.y0 + rect.height() * unit;
+
+    for (p, c) in point_colors(color, len, PALETTE[idx % PALETTE.len()]).into_iter().enumerate() {
+        let points: Vec<String> = units
+            .iter()
+            .enumerate()
+            .filter(|(_, u)| u[p].is_finite())
+            .map(|(d, u)| format!("({}, {})", x(d), y(u[p])))
+            .collect();
+        writeln!(tex, "\\draw[color={}, opacity=0.7] {};", rgb(c), points.join(" -- "))?;
+    }
+    for (d, ((name, _), scale)) in dimensions.iter().zip(&scales).enumerate() {
+        writeln!(tex, "\\draw ({x}, {}) -- ({x}, {});", rect.y0, rect.y1, x = x(d))?;
+        writeln!(tex, "\\node[below] at ({}, {}) {{{}}};", x(d), rect.y0, escape(name))?;
+        for tick in scale.ticks(5) {
+            writeln!(
+                tex,
+                "\\node[left, font=\\small] at ({}, {}) {{{}}};",
+                x(d),
+                y(tick.position),
+                escape(&tick.label)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn legend_entry(tex: &mut String, idx: usize, name: &Option<String>) -> fmt::Result {
+    writeln!(tex, "\\addlegendentry{{{}}}", escape(&layer_name(idx, name)))
+}
+
+/// A legend entry for layers drawn with plain TikZ commands, which pgfplots doesn't add to the legend by itself
+fn area_legend_entry(tex: &mut String, idx: usize, color: &Option<VectorData>, name: &Option<String>) -> fmt::Result {
+    writeln!(
+        tex,
+        "\\addlegendimage{{area legend, fill={}, draw=none}}",
+        rgb(layer_color(idx, color))
+    )?;
+    legend_entry(tex, idx, name)
+}
+
+/// An xcolor color expression
+fn rgb(color: Rgb) -> String {
+    format!("{{rgb,255:red,{};green,{};blue,{}}}", color.0, color.1, color.2)
+}
+
+/// Escape the characters with a special meaning in LaTeX
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{axis_options, escape, Pgfplots};
+    use crate::backends::scale::Scale;
+    use crate::error::ConspireError;
+    use crate::{ContourOptions, Layer, Plot};
+
+    #[test]
+    fn special_characters_are_escaped() {
+        assert_eq!(escape("50% of $x_1 & {y}"), r"50\% of \$x\_1 \& \{y\}");
+        assert_eq!(escape(r"a\b~c^#"), r"a\textbackslash{}b\textasciitilde{}c\textasciicircum{}\#");
+    }
+
+    #[test]
+    fn band_axis() {
+        let mut options = Vec::new();
+        axis_options(&mut options, "x", &Scale::Band(vec![String::from("a_1"), String::from("b")]));
+        assert_eq!(options, vec!["xmin=-0.5", "xmax=1.5", "xtick={0,1}", r"xticklabels={{a\_1},{b}}"]);
+    }
+
+    #[test]
+    fn time_axis() {
+        let mut options = Vec::new();
+        axis_options(&mut options, "y", &Scale::Time { min: 0.0, max: 4.0 * 86400.0 });
+        assert_eq!(
+            options,
+            vec![
+                "ymin=0",
+                "ymax=345600",
+                "ytick={0,86400,172800,259200,345600}",
+                "yticklabels={{Jan 1},{Jan 2},{Jan 3},{Jan 4},{Jan 5}}",
+                "scaled y ticks=false",
+            ]
+        );
+    }
+
+    #[test]
+    fn panels_are_drawn_without_axes() {
+        let treemap = Layer::new().label(vec!["root", "leaf"]).parent(vec!["", "root"]).value(vec![0.0, 1.0]);
+        let parallel = Layer::new().dimension("a", vec![1.0, 2.0]).dimension("b", vec![3.0, 4.0]);
+        let tex = Pgfplots {}
+            .tex(&[Plot::treemap(&treemap), Plot::parallel_coordinates(&parallel)])
+            .unwrap();
+        assert!(tex.starts_with("% Requires"));
+        assert!(!tex.contains(r"\begin{axis}"));
+        assert!(tex.contains("leaf"));
+        assert!(tex.trim_end().ends_with(r"\end{tikzpicture}"));
+    }
+
+    #[test]
+    fn unsupported_plots() {
+        let layer = Layer::new().x(vec!["a", "b"]).y(vec![1.0, 2.0]);
+        let treemap = Layer::new().label(vec!["root"]).parent(vec![""]);
+        let grid = Layer::new().z(vec![vec![0.0, 4.0], vec![0.0, 4.0]]);
+        for data in [
+            vec![Plot::pie(&layer)],
+            vec![Plot::bar(&layer), Plot::treemap(&treemap)],
+            vec![Plot::surface(&grid)],
+        ] {
+            assert!(matches!(
+                Pgfplots {}.tex(&data),
+                Err(ConspireError::UnsupportedPlot { backend: "pgfplots", .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn contours() {
+        let grid = Layer::new().z(vec![vec![0.0, 4.0], vec![0.0, 4.0]]);
+        let options = ContourOptions { levels: 1, ..Default::default() };
+        let tex = Pgfplots {}.tex(&[Plot::contour_with(&grid, options)]).unwrap();
+        assert!(tex.contains("thick] (axis cs:0.5, 0) -- (axis cs:0.5, 1);"));
+        assert!(tex.contains("colorbar"));
+    }
+}
@@ -1,44 +1,124 @@
 use super::common::{stringify_data_vec, AttributePair};
+use super::scale::{
+    box_legend, categories, contour_levels, dimension_scale, flat, grid_centers, levels, point_colors, subset, ColorMap,
+    PALETTE,
+};
+use super::time::format_timestamp;
 use super::Renderable;
-use crate::data::{MatrixData, VectorData};
+use crate::data::{MatrixData, Missing, VectorData};
+use crate::error::ConspireError;
+use crate::heatmap::{Annotations, ContourOptions, HeatmapOptions};
+use crate::stats::{histogram, violins, Bandwidth, BoxOptions, Histogram, ViolinInner, Whiskers};
 use crate::Plot;
 
-use std::error;
 use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
+type Result<T> = std::result::Result<T, ConspireError>;
 
-pub struct Plotly {}
+/// The plotly.js release loaded from the CDN. Pinned, so that plots don't change or break when a new version is out.
+const CDN: &str = "https://cdn.plot.ly/plotly-2.35.2.min.js";
+
+/// Where the Plotly backend gets plotly.js from
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum PlotlyJs {
+    /// Load plotly.js 2.35.2 from the Plotly CDN, which needs a network connection when viewing the plot
+    #[default]
+    Cdn,
+    /// Read the bundle at the given path and embed it in the page, making the output a single self-contained file
+    /// which works offline. Conspire doesn't ship plotly.js, so the bundle is whatever file is passed in, and should be
+    /// the release the CDN is pinned to, plotly.js 2.35.2 (<https://cdn.plot.ly/plotly-2.35.2.min.js>), which the
+    /// pages are written for.
+    Inline(PathBuf),
+    /// Load the bundle from the given URL when viewing the plot, such as a copy of plotly.js 2.35.2 on an internal
+    /// server. Relative URLs, such as a file name, are relative to the page.
+    Local(String),
+}
+
+pub struct Plotly {
+    js: PlotlyJs,
+}
 
 impl Plotly {
-    fn build_javascript(&self, data: &[Plot]) -> Result<String> {
-        let traces: Vec<String> = data
-            .iter()
-            .enumerate()
-            .map(|(i, d)| {
-                let t = PlotlyPlot { plot: d };
-                Plotly::trace(i, t)
-            })
-            .collect();
-
-        let names: Vec<String> = data
-            .iter()
-            .enumerate()
-            .map(|(i, _d)| Plotly::name(i))
-            .collect();
+    pub fn new(js: PlotlyJs) -> Self {
+        Self { js }
+    }
+
+    /// The script element loading plotly.js
+    fn script_tag(&self) -> Result<String> {
+        Ok(match &self.js {
+            PlotlyJs::Cdn => format!("<script src=\"{}\"></script>", CDN),
+            PlotlyJs::Inline(path) => {
+                let bundle = fs::read_to_string(path).map_err(|e| {
+                    io::Error::new(e.kind(), format!("Couldn't read plotly.js from {}: {}", path.display(), e))
+                })?;
+                // The bundle can't be allowed to close the script element it's embedded in
+                format!("<script>{}</script>", bundle.replace("</script", "<\\/script"))
+            }
+            PlotlyJs::Local(url) => format!(
+                "<script src=\"{}\"></script>",
+                url.replace('&', "&amp;").replace('"', "&quot;")
+            ),
+        })
+    }
+
+    fn build_javascript(&self, data: &[Plot], div: &str) -> Result<String> {
+        flat("plotly", data, |_| true)?;
+        let mut traces = Vec::new();
+        let mut names = Vec::new();
+        let mut grouped = false;
+        for (i, d) in data.iter().enumerate() {
+            match d {
+                Plot::Box {
+                    x,
+                    y,
+                    options,
+                    color: color @ Some(VectorData::Categorical(groups) | VectorData::Ordinal { values: groups, .. }),
+                    ..
+                } if groups.len() > 1 => {
+                    // Plotly only places boxes side by side when they're separate traces, so each color gets a trace
+                    for (g, body) in grouped_boxes(x, y, *options, color, groups).into_iter().enumerate() {
+                        let name = format!("{}_{}", i, g);
+                        traces.push(format!("let trace{} = {{ {} }};\n", name, body));
+                        names.push(Plotly::name(&name));
+                    }
+                    grouped = true;
+                }
+                _ => {
+                    traces.push(Plotly::trace(i, PlotlyPlot { plot: d }));
+                    names.push(Plotly::name(&i.to_string()));
+                }
+            }
+        }
 
+        let mut layout = Vec::new();
+        if grouped {
+            layout.push(String::from("boxmode: 'group'"));
+        }
+        let (x, y) = axis_data(data);
+        for (axis, data) in [("xaxis", x), ("yaxis", y)] {
+            if let Some(attributes) = axis_layout(&data) {
+                layout.push(format!("{}: {{ {} }}", axis, attributes));
+            }
+        }
+        let layout = if layout.is_empty() { String::from("{}") } else { format!("{{ {} }}", layout.join(", ")) };
         Ok(format!(
-            "{}\nlet data = [{}]; Plotly.newPlot('myDiv', data);",
+            "{}\nlet data = [{}]; Plotly.newPlot('{}', data, {});",
             traces.concat(),
-            names.concat()
+            names.concat(),
+            div,
+            layout
         ))
     }
 
-    fn html(plot_script: String) -> String {
+    fn html(plot_script: String, script_tag: &str) -> String {
         format!(
             r#"<head>
     <!-- Plotly.js -->
-    <script src="https://cdn.plot.ly/plotly-latest.min.js"></script>
+    {}
     </head>
     <body>
     <div id="myDiv"></div>
@@ -47,7 +127,23 @@ impl Plotly {
     </script>
     </body>
     "#,
-            plot_script
+            script_tag, plot_script
+        )
+    }
+
+    /// An HTML fragment holding the plot, which can be embedded in a page alongside other plots
+    fn fragment(plot_script: String, div: &str, script_tag: &str) -> String {
+        format!(
+            r#"<div id="{div}"></div>
+{script_tag}
+<script>
+{{
+{script}
+}}
+</script>"#,
+            div = div,
+            script_tag = script_tag,
+            script = plot_script
         )
     }
 
@@ -55,7 +151,7 @@ impl Plotly {
         format!("let trace{} = {{ {} }};\n", idx, t)
     }
 
-    fn name(idx: usize) -> String {
+    fn name(idx: &str) -> String {
         format!("trace{},", idx)
     }
 }
@@ -66,29 +162,30 @@ struct PlotlyPlot<'a> {
 
 impl Renderable for Plotly {
     fn render(&self, data: &[Plot], display: bool) -> Result<()> {
-        use super::util::write_to_file;
+        use super::util::{open_in_viewer, write_to_file};
         use std::path::Path;
 
-        let html = Plotly::html(self.build_javascript(data)?);
-        write_to_file(&Path::new("render.html"), &html);
+        let path = Path::new("render.html");
+        let html = Plotly::html(self.build_javascript(data, "myDiv")?, &self.script_tag()?);
+        write_to_file(path, &html)?;
 
         if display {
-            use std::process::Command;
-            if cfg!(target_os = "windows") {
-                Command::new("cmd")
-                    .args(&["/C", "start render.html"])
-                    .output()
-                    .expect("failed to execute process");
-            } else {
-                Command::new("sh")
-                    .arg("-c")
-                    .arg("open render.html")
-                    .output()
-                    .expect("failed to execute process");
-            }
+            open_in_viewer(path)?;
         }
         Ok(())
     }
+
+    fn write(&self, data: &[Plot], writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(Plotly::html(self.build_javascript(data, "myDiv")?, &self.script_tag()?).as_bytes())?;
+        Ok(())
+    }
+
+    fn html_fragment(&self, data: &[Plot]) -> Result<String> {
+        // Every fragment needs its own div, as a notebook may show many plots on the same page
+        static FRAGMENTS: AtomicUsize = AtomicUsize::new(0);
+        let div = format!("conspire-plot-{}", FRAGMENTS.fetch_add(1, Ordering::Relaxed));
+        Ok(Plotly::fragment(self.build_javascript(data, &div)?, &div, &self.script_tag()?))
+    }
 }
 
 impl<'a> fmt::Display for PlotlyPlot<'a> {
@@ -103,58 +200,389 @@ impl<'a> fmt::Display for PlotlyPlot<'a> {
             } => {
                 let type_str = "mode: 'markers', type: 'scatter'";
                 let base = type_to_string_2d(type_str, x, y);
-                let markers = markers_to_string(color, size);
+                let markers = markers_to_string("marker", ("color", color), ("size", size));
                 let name = name_to_string(name);
                 write!(f, "{} {} {}", base, markers, name)
             }
             Plot::Line {
                 x,
                 y,
+                missing,
                 color,
                 size,
                 name,
             } => {
-                let type_str = "mode: 'lines', type: 'scatter'";
+                // Missing values are written as null, which Plotly either leaves a gap at or connects across
+                let type_str = match missing {
+                    Missing::Skip => "mode: 'lines', type: 'scatter', connectgaps: false",
+                    Missing::Interpolate => "mode: 'lines', type: 'scatter', connectgaps: true",
+                };
                 let base = type_to_string_2d(type_str, x, y);
-                let markers = markers_to_string(color, size);
+                let markers = markers_to_string("line", ("color", color), ("width", size));
                 let name = name_to_string(name);
                 write!(f, "{} {} {}", base, markers, name)
             }
             Plot::Bar { x, y, color, name } => {
                 let type_str = "type: 'bar'";
                 let base = type_to_string_2d(type_str, x, y);
-                let markers = markers_to_string(color, &None);
+                let markers = markers_to_string("marker", ("color", color), ("size", &None));
                 let name = name_to_string(name);
                 write!(f, "{} {} {}", base, markers, name)
             }
             Plot::Pie { x, color, name } => {
                 let type_str = "type: 'pie'";
                 let base = type_to_string_1d(type_str, x, Some("values"));
-                let markers = markers_to_string(color, &None);
+                let markers = markers_to_string("marker", ("colors", color), ("size", &None));
                 let name = name_to_string(name);
                 write!(f, "{} {} {}", base, markers, name)
             }
             Plot::HorizontalBar { x, y, color, name } => {
                 let type_str = "type: 'bar', orientation: 'h'";
                 let base = type_to_string_2d(type_str, x, y);
-                let markers = markers_to_string(color, &None);
+                let markers = markers_to_string("marker", ("color", color), ("size", &None));
                 let name = name_to_string(name);
                 write!(f, "{} {} {}", base, markers, name)
             }
-            Plot::SimpleHeatmap { z, color, name } => {
-                let type_str = "type: 'heatmap'";
-                let base = type_to_string_1d(type_str, z, Some("z"));
-                let markers = markers_to_string(color, &None);
+            Plot::Histogram {
+                x,
+                binning,
+                color,
+                name,
+            } => {
+                // The bins are computed by Conspire rather than Plotly, so every backend shows the same bins
+                let type_str = "type: 'bar'";
+                let base = match histogram(x, *binning) {
+                    Histogram::Bins(bins) => {
+                        let centers = bins.iter().map(|b| (b.start + b.end) / 2.0).collect();
+                        let counts = VectorData::Quantitative(bins.iter().map(|b| b.count as f64).collect());
+                        // Bars on a date axis are as wide as a number of milliseconds
+                        let (centers, scale) = match x {
+                            VectorData::Temporal(_) => (VectorData::Temporal(centers), 1000.0),
+                            _ => (VectorData::Quantitative(centers), 1.0),
+                        };
+                        let widths = VectorData::Quantitative(bins.iter().map(|b| (b.end - b.start) * scale).collect());
+                        let widths = AttributePair::new("width", &widths);
+                        format!("{} {}", widths, type_to_string_2d(type_str, &centers, &counts))
+                    }
+                    Histogram::Counts(counts) => {
+                        let categories = VectorData::Categorical(counts.iter().map(|(c, _)| c.to_string()).collect());
+                        let counts = VectorData::Quantitative(counts.iter().map(|(_, n)| *n as f64).collect());
+                        type_to_string_2d(type_str, &categories, &counts)
+                    }
+                };
+                let markers = markers_to_string("marker", ("color", color), ("size", &None));
                 let name = name_to_string(name);
                 write!(f, "{} {} {}", base, markers, name)
             }
-            Plot::Box { x, color, name } => {
-                let type_str = "type: 'box', boxpoints: 'Outliers'";
-                let base = type_to_string_1d(type_str, x, None);
-                let markers = markers_to_string(color, &None);
+            Plot::Violin {
+                x,
+                group,
+                bandwidth,
+                inner,
+                color,
+                name,
+            } => {
+                let type_str = "type: 'violin', orientation: 'h', points: false";
+                let base = match group {
+                    Some(group) => type_to_string_2d(type_str, *x, group),
+                    None => type_to_string_1d(type_str, x, None),
+                };
+                // Plotly estimates the density itself. A fixed bandwidth, or the one Conspire picked for a single
+                // violin, is passed on so the shape matches the other backends, while grouped violins fall back to
+                // Plotly's own rule of thumb for each group.
+                let violins = violins(x, group, "", *bandwidth);
+                let bandwidth = match (bandwidth, violins.as_slice()) {
+                    (Bandwidth::Fixed(h), _) => format!(", bandwidth: {}", number(*h)),
+                    (_, [violin]) => format!(", bandwidth: {}", number(violin.bandwidth)),
+                    _ => String::new(),
+                };
+                // Plotly has no quartile lines, so they're shown as the inner box too
+                let inner = match inner {
+                    ViolinInner::Box | ViolinInner::Quartiles => ", box: { visible: true }",
+                    ViolinInner::None => ", box: { visible: false }",
+                };
+                let markers = markers_to_string("line", ("color", color), ("width", &None));
                 let name = name_to_string(name);
-                write!(f, "{} {} {}", base, markers, name)
+                write!(f, "{}{}{} {} {}", base, bandwidth, inner, markers, name)
+            }
+            Plot::Treemap {
+                label,
+                parent,
+                value,
+                color,
+                name,
+            } => {
+                let type_str = "type: 'treemap'";
+                let labels = AttributePair::new("labels", label);
+                let parents = AttributePair::new("parents", parent);
+                let values = match value {
+                    Some(v) => format!("{}", AttributePair::new("values", v)),
+                    None => String::new(),
+                };
+                let markers = markers_to_string("marker", ("colors", color), ("size", &None));
+                let name = name_to_string(name);
+                write!(f, "{} {} {} {} {} {}", labels, parents, values, type_str, markers, name)
+            }
+            Plot::ParallelCoordinates { dimensions, color, name } => {
+                let type_str = "type: 'parcoords'";
+                let len = dimensions.first().map_or(0, |(_, data)| data.len());
+                let dimensions: Vec<String> = dimensions
+                    .iter()
+                    .map(|(label, data)| dimension_to_string(label, data))
+                    .collect();
+                let line = parcoords_line(color, len);
+                let name = name_to_string(name);
+                write!(f, "dimensions: [{}], {} {} {}", dimensions.join(", "), type_str, line, name)
+            }
+            Plot::Box {
+                x,
+                y,
+                options,
+                color,
+                name,
+            } => write!(f, "{}", box_to_string(x, y, *options, color, name)),
+            Plot::Heatmap {
+                x,
+                y,
+                z,
+                options,
+                name,
+            } => write!(f, "{}", heatmap_to_string(x, y, z, options, name)),
+            Plot::Contour {
+                x,
+                y,
+                z,
+                options,
+                name,
+            } => write!(f, "{}", contour_to_string(x, y, z, options, name)),
+            Plot::Surface {
+                x,
+                y,
+                z,
+                colorscale,
+                name,
+            } => {
+                let map = ColorMap::grid(z, colorscale);
+                let trace = grid_to_string("type: 'surface'", x, y, z);
+                write!(f, "{}, {}, {}", trace, colorscale_to_string(&map, "c"), name_to_string(name))
+            }
+        }
+    }
+}
+
+/// A box plot trace. Plotly's whiskers reach the smallest and largest values when the points are hidden, which is
+/// how whiskers spanning all the data are drawn.
+fn box_to_string(
+    x: &VectorData,
+    y: &Option<VectorData>,
+    options: BoxOptions,
+    color: &Option<VectorData>,
+    name: &Option<String>,
+) -> String {
+    let type_str = "type: 'box'";
+    let base = match y {
+        Some(y) => type_to_string_2d(type_str, x, y),
+        None => type_to_string_1d(type_str, x, None),
+    };
+    let points = match options.whiskers {
+        Whiskers::Tukey => "'outliers'",
+        Whiskers::MinMax => "false",
+    };
+    let markers = markers_to_string("marker", ("color", color), ("size", &None));
+    format!(
+        "{}, boxpoints: {}, notched: {}, boxmean: {} {} {}",
+        base,
+        points,
+        options.notched,
+        options.mean,
+        markers,
+        name_to_string(name)
+    )
+}
+
+/// A heatmap trace. The colorscale and its range are resolved by Conspire, so they match the static backends, and
+/// annotations are written by Plotly's text template.
+fn heatmap_to_string(
+    x: &Option<VectorData>,
+    y: &Option<VectorData>,
+    z: &MatrixData,
+    options: &HeatmapOptions,
+    name: &Option<String>,
+) -> String {
+    let mut trace = format!("{} type: 'heatmap'", AttributePair::new("z", z));
+    for (key, ticks) in &[("x", x), ("y", y)] {
+        if let Some(ticks) = ticks {
+            trace = format!("{} {}", AttributePair::new(key, ticks), trace);
+        }
+    }
+
+    let map = ColorMap::heatmap(z, options);
+    trace = format!("{}, {}", trace, colorscale_to_string(&map, "z"));
+
+    let text = match &options.annotations {
+        Annotations::None => None,
+        Annotations::Values(decimals) => Some(format!("texttemplate: '%{{z:.{}f}}'", decimals)),
+        Annotations::Text(text) => {
+            let rows: Vec<String> = text.iter().map(|row| strings(row)).collect();
+            Some(format!("text: [{}], texttemplate: '%{{text}}'", rows.join(", ")))
+        }
+    };
+    if let Some(text) = text {
+        trace = format!("{}, {}", trace, text);
+    }
+    format!("{}, {}", trace, name_to_string(name))
+}
+
+/// A contour trace. The levels are picked by Conspire, so the lines match the static backends, and are drawn in the
+/// color of their level.
+fn contour_to_string(
+    x: &Option<VectorData>,
+    y: &Option<VectorData>,
+    z: &MatrixData,
+    options: &ContourOptions,
+    name: &Option<String>,
+) -> String {
+    let map = ColorMap::grid(z, &options.colorscale);
+    let levels = contour_levels(&map, options.levels);
+    let (start, end) = (levels[0], levels[levels.len() - 1]);
+    let size = (map.max - map.min) / (options.levels + 1) as f64;
+    format!(
+        "{}, autocontour: false, contours: {{ coloring: 'lines', start: {}, end: {}, size: {} }}, {}, {}",
+        grid_to_string("type: 'contour'", x, y, z),
+        number(start),
+        number(end),
+        number(size),
+        colorscale_to_string(&map, "z"),
+        name_to_string(name)
+    )
+}
+
+/// The data of a trace placed on the points of a grid. Plotly draws contours and surfaces through the points, so
+/// numeric coordinates given as the edges of cells are moved to the points between them.
+fn grid_to_string(type_str: &str, x: &Option<VectorData>, y: &Option<VectorData>, z: &MatrixData) -> String {
+    let MatrixData::Quantitative(rows) = z;
+    let columns = rows.first().map_or(0, |r| r.len());
+    let mut trace = format!("{} {}", AttributePair::new("z", z), type_str);
+    for (key, coordinates, count) in [("y", y, rows.len()), ("x", x, columns)] {
+        let centers;
+        let points = match coordinates {
+            Some(VectorData::Quantitative(_)) => {
+                centers = VectorData::Quantitative(grid_centers(coordinates, count));
+                Some(&centers)
             }
+            Some(VectorData::Temporal(_)) => {
+                centers = VectorData::Temporal(grid_centers(coordinates, count));
+                Some(&centers)
+            }
+            other => other.as_ref(),
+        };
+        if let Some(points) = points {
+            trace = format!("{} {}", AttributePair::new(key, points), trace);
+        }
+    }
+    trace
+}
+
+/// The colorscale of a trace, with the range of its values given by `axis`, such as `zmin` and `zmax`
+fn colorscale_to_string(map: &ColorMap, axis: &str) -> String {
+    let stops: Vec<String> = map.stops.iter().map(|(t, c)| format!("[{:?}, {}]", t, string(&c.hex()))).collect();
+    format!(
+        "colorscale: [{}], {axis}min: {}, {axis}max: {}",
+        stops.join(", "),
+        number(map.min),
+        number(map.max),
+        axis = axis
+    )
+}
+
+/// The traces of a box plot split by the categories of its color channel, named after the category and colored by
+/// it, like the static backends
+fn grouped_boxes(
+    x: &VectorData,
+    y: &Option<VectorData>,
+    options: BoxOptions,
+    color: &Option<VectorData>,
+    groups: &[String],
+) -> Vec<String> {
+    box_legend(0, color, &None)
+        .into_iter()
+        .map(|(group, rgb)| {
+            let members: Vec<usize> = (0..groups.len()).filter(|i| groups[*i] == group).collect();
+            let pick = |data: &VectorData| subset(data, &members);
+            let color = Some(VectorData::Categorical(vec![rgb.hex()]));
+            box_to_string(&pick(x), &y.as_ref().map(pick), options, &color, &Some(group))
+        })
+        .collect()
+}
+
+/// A dimension of a parallel coordinates plot. Categorical and ordinal dimensions are placed at the index of their
+/// category, with a tick labelled with the category at each index, and points in time are placed at their timestamp,
+/// with ticks on round dates and times, since Plotly has no date axes for parallel coordinates.
+fn dimension_to_string(label: &str, data: &VectorData) -> String {
+    match data {
+        VectorData::Quantitative(_) => {
+            format!("{{ label: {}, {} }}", string(label), AttributePair::new("values", data))
+        }
+        VectorData::Temporal(v) => {
+            let ticks = dimension_scale(data).ticks(5);
+            let values: Vec<f64> = ticks.iter().map(|t| t.value).collect();
+            let labels: Vec<&str> = ticks.iter().map(|t| t.label.as_str()).collect();
+            format!(
+                "{{ label: {}, values: {}, tickvals: {}, ticktext: {} }}",
+                string(label),
+                numbers(v),
+                numbers(&values),
+                strings(&labels)
+            )
+        }
+        VectorData::Categorical(v) | VectorData::Ordinal { values: v, .. } => {
+            let categories = levels(data);
+            let values: Vec<usize> = v
+                .iter()
+                .map(|c| categories.iter().position(|x| x == c).unwrap_or(0))
+                .collect();
+            let ticks: Vec<usize> = (0..categories.len()).collect();
+            format!(
+                "{{ label: {}, values: {}, tickvals: {}, ticktext: {} }}",
+                string(label),
+                stringify_data_vec(&values),
+                stringify_data_vec(&ticks),
+                strings(&categories)
+            )
+        }
+    }
+}
+
+/// The `line` object of a parallel coordinates plot. Plotly only colors the lines by numbers, so quantitative colors
+/// go through a colorscale, while any other colors are resolved and numbered, with a colorscale holding each of them.
+fn parcoords_line(color: &Option<VectorData>, len: usize) -> String {
+    match color {
+        None => String::from(","),
+        Some(VectorData::Quantitative(v)) if v.len() > 1 => format!(
+            ", line: {{ color: {}, colorscale: 'Viridis', showscale: true }},",
+            numbers(v)
+        ),
+        Some(_) => {
+            let colors: Vec<String> = point_colors(color, len, PALETTE[0]).iter().map(|c| c.hex()).collect();
+            let unique = categories(&colors);
+            if unique.len() == 1 {
+                return format!(", line: {{ color: {} }},", string(&unique[0]));
+            }
+            let indices: Vec<usize> = colors
+                .iter()
+                .map(|c| unique.iter().position(|u| u == c).unwrap_or(0))
+                .collect();
+            let scale: Vec<String> = unique
+                .iter()
+                .enumerate()
+                .map(|(i, c)| format!("[{:?}, {}]", i as f64 / (unique.len() - 1) as f64, string(c)))
+                .collect();
+            format!(
+                ", line: {{ color: {}, colorscale: [{}], cmin: 0, cmax: {} }},",
+                stringify_data_vec(&indices),
+                scale.join(", "),
+                unique.len() - 1
+            )
         }
     }
 }
@@ -179,35 +607,169 @@ fn type_to_string_1d<T: fmt::Display>(
     }
 }
 
-fn markers_to_string<T: fmt::Display>(color: &Option<T>, size: &Option<T>) -> String {
-    let mut marker = String::new();
+/// The object styling the marks of a trace (e.g. `marker` or `line`), holding the color and size channels under the
+/// given keys
+fn markers_to_string(
+    attribute: &str,
+    (color_key, color): (&str, &Option<VectorData>),
+    (size_key, size): (&str, &Option<VectorData>),
+) -> String {
+    let mut style = String::new();
 
     if let Some(c) = color {
-        let color = AttributePair::new("color", c);
-        marker = format!("{}", color);
+        let color = match c {
+            // Plotly would take ordinal categories for color names, so they're resolved onto the color scale here
+            VectorData::Ordinal { .. } => {
+                let colors: Vec<String> = point_colors(color, c.len(), PALETTE[0]).iter().map(|c| c.hex()).collect();
+                AttributePair::new(color_key, &strings(&colors))
+            }
+            _ => AttributePair::new(color_key, &constant_or_array(c)),
+        };
+        style = format!("{}", color);
     }
 
     if let Some(s) = size {
-        let size = AttributePair::new("size", s);
-        marker = format!("{} {}", marker, size);
+        let size = AttributePair::new(size_key, &constant_or_array(s));
+        style = format!("{} {}", style, size);
     }
 
-    format!("{},", marker)
+    if style.is_empty() {
+        String::from(",")
+    } else {
+        format!(", {}: {{ {} }},", attribute, style)
+    }
+}
+
+/// A channel's values, with a single value written as a constant. Plotly would only apply an array holding a
+/// single value to the first data point.
+fn constant_or_array(data: &VectorData) -> String {
+    match data {
+        VectorData::Quantitative(v) if v.len() == 1 => number(v[0]),
+        VectorData::Categorical(v) | VectorData::Ordinal { values: v, .. } if v.len() == 1 => string(&v[0]),
+        VectorData::Temporal(v) if v.len() == 1 => timestamp(v[0]),
+        data => data.to_string(),
+    }
 }
 
 fn name_to_string(name: &Option<String>) -> String {
     if let Some(n) = name {
-        format!("name: \'{}\'", n)
+        format!("name: {}", string(n))
     } else {
         String::from("")
     }
 }
 
+/// A JavaScript number, with missing values (NaN or infinite) as null, which Plotly leaves out
+fn number(value: f64) -> String {
+    if value.is_finite() {
+        format!("{:?}", value)
+    } else {
+        String::from("null")
+    }
+}
+
+/// A JavaScript string literal, written as JSON, with `</` escaped so that it can't close the script element the
+/// plot is embedded in
+fn string(text: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            '/' if literal.ends_with('<') => literal.push_str("\\/"),
+            // JSON allows these two line separators in strings, but older JavaScript engines don't
+            '\u{2028}' | '\u{2029}' => literal.push_str(&format!("\\u{:04x}", c as u32)),
+            c if (c as u32) < 0x20 => literal.push_str(&format!("\\u{:04x}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// A JavaScript array of strings
+fn strings<T: AsRef<str>>(values: &[T]) -> String {
+    let strings: Vec<String> = values.iter().map(|v| string(v.as_ref())).collect();
+    format!("[{}]", strings.join(", "))
+}
+
+/// A point in time as a date string, which Plotly reads in UTC, or null when it's missing
+fn timestamp(value: f64) -> String {
+    if value.is_finite() {
+        string(&format_timestamp(value))
+    } else {
+        String::from("null")
+    }
+}
+
+/// The data placed along the x and y axes by every plot
+fn axis_data<'a>(data: &[Plot<'a>]) -> (Vec<&'a VectorData>, Vec<&'a VectorData>) {
+    let mut along_x = Vec::new();
+    let mut along_y = Vec::new();
+    for plot in data {
+        match plot {
+            Plot::Scatter { x, y, .. }
+            | Plot::Line { x, y, .. }
+            | Plot::Bar { x, y, .. }
+            | Plot::HorizontalBar { x, y, .. } => {
+                along_x.push(*x);
+                along_y.push(*y);
+            }
+            Plot::Histogram { x, .. } => along_x.push(*x),
+            Plot::Box { x, y: Some(_), .. } => along_x.push(*x),
+            Plot::Violin { group, .. } => along_y.extend(group.iter()),
+            Plot::Heatmap { x, y, .. } | Plot::Contour { x, y, .. } => {
+                along_x.extend(x.iter());
+                along_y.extend(y.iter());
+            }
+            _ => {}
+        }
+    }
+    (along_x, along_y)
+}
+
+/// The layout attributes of an axis: a date axis for points in time, and categories in their order for ordinal data
+fn axis_layout(data: &[&VectorData]) -> Option<String> {
+    let mut attributes = Vec::new();
+    if data.iter().any(|d| matches!(d, VectorData::Temporal(_))) {
+        attributes.push(String::from("type: 'date'"));
+    }
+    let mut categories: Vec<&String> = Vec::new();
+    for d in data {
+        if let VectorData::Ordinal { order, .. } = d {
+            categories.extend(order.iter().filter(|c| !categories.contains(c)).collect::<Vec<&String>>());
+        }
+    }
+    if !categories.is_empty() {
+        attributes.push(format!("categoryorder: 'array', categoryarray: {}", strings(&categories)));
+    }
+    if attributes.is_empty() {
+        None
+    } else {
+        Some(attributes.join(", "))
+    }
+}
+
+/// A JavaScript array of numbers, with missing values as null
+fn numbers(values: &[f64]) -> String {
+    let numbers: Vec<String> = values.iter().map(|v| number(*v)).collect();
+    format!("[{}]", numbers.join(", "))
+}
+
 impl fmt::Display for VectorData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            VectorData::Quantitative(v) => write!(f, "{}", stringify_data_vec(v)),
-            VectorData::Categorical(v) => write!(f, "{}", stringify_data_vec(v)),
+            VectorData::Quantitative(v) => write!(f, "{}", numbers(v)),
+            VectorData::Categorical(v) | VectorData::Ordinal { values: v, .. } => {
+                write!(f, "{}", strings(v))
+            }
+            VectorData::Temporal(v) => {
+                let times: Vec<String> = v.iter().map(|t| timestamp(*t)).collect();
+                write!(f, "[{}]", times.join(", "))
+            }
         }
     }
 }
@@ -215,7 +777,10 @@ impl fmt::Display for VectorData {
 impl fmt::Display for MatrixData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            MatrixData::Quantitative(v) => write!(f, "{}", stringify_data_vec(v)),
+            MatrixData::Quantitative(rows) => {
+                let rows: Vec<String> = rows.iter().map(|row| numbers(row)).collect();
+                write!(f, "[{}]", rows.join(", "))
+            }
         }
     }
 }
@@ -225,3 +790,100 @@ impl fmt::Display for AttributePair {
         write!(f, "{}: {},", self.key, self.value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Plotly, PlotlyJs};
+    use crate::error::ConspireError;
+    use crate::{ContourOptions, Layer, Missing, Ordinal, Plot};
+
+    fn javascript(data: &[Plot]) -> String {
+        Plotly::new(PlotlyJs::Cdn).build_javascript(data, "div").unwrap()
+    }
+
+    #[test]
+    fn box_group_names_are_string_literals() {
+        let layer = Layer::new()
+            .x(vec!["a", "a", "b"])
+            .y(vec![1.0, 2.0, 3.0])
+            .color(vec!["Bob's", "Bob's", "Ann \"A\""]);
+        let js = Plotly::new(PlotlyJs::Cdn).build_javascript(&[Plot::boxplot(&layer)], "div").unwrap();
+        assert!(js.contains(r#"name: "Bob's""#));
+        assert!(js.contains(r#"name: "Ann \"A\"""#));
+    }
+
+    #[test]
+    fn local_bundle_url() {
+        let tag = Plotly::new(PlotlyJs::Local(String::from("js/plotly.min.js?v=2.35.2&x=\"y\""))).script_tag().unwrap();
+        assert_eq!(tag, r#"<script src="js/plotly.min.js?v=2.35.2&amp;x=&quot;y&quot;"></script>"#);
+    }
+
+    #[test]
+    fn missing_values_are_null() {
+        let layer = Layer::new().x(vec![1.0, 2.0, f64::NAN]).y(vec![Some(1.0), None, Some(3.0)]);
+        let js = javascript(&[Plot::scatter(&layer)]);
+        assert!(js.contains("x: [1.0, 2.0, null]"));
+        assert!(js.contains("y: [1.0, null, 3.0]"));
+    }
+
+    #[test]
+    fn lines_break_or_join_at_gaps() {
+        let layer = Layer::new().x(vec![1.0, 2.0, 3.0]).y(vec![Some(1.0), None, Some(3.0)]);
+        let skipped = javascript(&[Plot::line_with(&layer, Missing::Skip)]);
+        assert!(skipped.contains("y: [1.0, null, 3.0]"));
+        assert!(skipped.contains("connectgaps: false"));
+        let joined = javascript(&[Plot::line_with(&layer, Missing::Interpolate)]);
+        assert!(joined.contains("y: [1.0, null, 3.0]"));
+        assert!(joined.contains("connectgaps: true"));
+    }
+
+    #[test]
+    fn strings_are_json() {
+        let layer = Layer::new()
+            .x(vec!["</script>", "a\u{1}b", "\u{2028}\\"])
+            .y(vec![1.0, 2.0, 3.0])
+            .color(vec!["red"])
+            .name("<b>\"x\"</b>");
+        let js = javascript(&[Plot::bar(&layer)]);
+        assert!(!js.contains("</"));
+        let x: Vec<String> = serde_json::from_str(r#"["<\/script>", "a\u0001b", "\u2028\\"]"#).unwrap();
+        assert_eq!(x, vec!["</script>", "a\u{1}b", "\u{2028}\\"]);
+        assert!(js.contains(r#"x: ["<\/script>", "a\u0001b", "\u2028\\"]"#));
+        assert!(js.contains(r#"color: "red""#));
+        assert!(js.contains(r#"name: "<b>\"x\"<\/b>""#));
+    }
+
+    #[test]
+    fn contours_and_surfaces() {
+        let layer = Layer::new().z(vec![vec![0.0, 4.0], vec![0.0, 4.0]]).x(vec![0.0, 2.0, 4.0]).name("grid");
+        let js = javascript(&[Plot::contour_with(&layer, ContourOptions { levels: 3, ..Default::default() })]);
+        // The edges of the cells are turned into the points of the grid
+        assert!(js.contains("x: [1.0, 3.0]"));
+        assert!(js.contains("type: 'contour'"));
+        assert!(js.contains("autocontour: false, contours: { coloring: 'lines', start: 1.0, end: 3.0, size: 1.0 }"));
+        assert!(js.contains("zmin: 0.0, zmax: 4.0"));
+
+        let js = javascript(&[Plot::surface(&layer)]);
+        assert!(js.contains("type: 'surface'"));
+        assert!(js.contains("cmin: 0.0, cmax: 4.0"));
+        assert!(js.contains(r#"name: "grid""#));
+
+        let points = Layer::new().x(vec![1.0]).y(vec![1.0]);
+        let mixed = [Plot::surface(&layer), Plot::scatter(&points)];
+        match Plotly::new(PlotlyJs::Cdn).build_javascript(&mixed, "div") {
+            Err(ConspireError::UnsupportedPlot { backend: "plotly", plot: "surface alongside other" }) => {}
+            other => panic!("expected surfaces to be kept apart from flat plots, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ordinal_categories_keep_the_given_order() {
+        let risk = Ordinal::new(vec!["high", "extreme", "low"], vec!["low", "medium", "high"]);
+        let layer = Layer::new().x(risk).y(vec![3, 4, 1]);
+        let js = javascript(&[Plot::bar(&layer)]);
+        assert!(js.contains(
+            r#"xaxis: { categoryorder: 'array', categoryarray: ["low", "medium", "high", "extreme"] }"#
+        ));
+        assert!(!js.contains("yaxis"));
+    }
+}
@@ -0,0 +1,605 @@
+use super::scale::{
+    bar_offset, box_legend, box_shapes, contour_lines, dimension_position, dimension_scale, heatmap_cells,
+    histogram_bars, layer_color, layer_name, line_runs, point_colors, point_sizes, supported, violin_shapes, Axes,
+    ColorMap, Rgb, Scale, PALETTE,
+};
+use super::treemap::{tiles, Rect};
+use super::Renderable;
+use crate::data::VectorData;
+use crate::error::ConspireError;
+use crate::stats::ViolinInner;
+use crate::Plot;
+
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::element::DashedPathElement;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::prelude::*;
+
+use std::error;
+use std::io::Write;
+
+type Result<T> = std::result::Result<T, ConspireError>;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 500;
+
+/// The file format to render to with plotters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlottersFormat {
+    /// A PNG image, written to `render.png`
+    Png,
+    /// An SVG image, written to `render.svg`
+    Svg,
+}
+
+/// Draws plots with the pure-Rust plotters crate, to bitmap or SVG images
+pub struct Plotters {
+    format: PlottersFormat,
+}
+
+impl Plotters {
+    pub fn new(format: PlottersFormat) -> Self {
+        Self { format }
+    }
+}
+
+impl Renderable for Plotters {
+    fn render(&self, data: &[Plot], display: bool) -> Result<()> {
+        use super::util::open_in_viewer;
+        use std::fs;
+        use std::path::Path;
+
+        let path = Path::new(match self.format {
+            PlottersFormat::Png => "render.png",
+            PlottersFormat::Svg => "render.svg",
+        });
+        // Draw the whole image before creating the file, so that a plot that can't be drawn leaves no file behind
+        let mut image = Vec::new();
+        self.write(data, &mut image)?;
+        fs::write(path, image)?;
+
+        if display {
+            open_in_viewer(path)?;
+        }
+        Ok(())
+    }
+
+    fn write(&self, data: &[Plot], writer: &mut dyn Write) -> Result<()> {
+        supported("plotters", data, |p| !matches!(p, Plot::Pie { .. } | Plot::Surface { .. }))?;
+
+        match self.format {
+            PlottersFormat::Png => {
+                let mut buffer = vec![0; (WIDTH * HEIGHT * 3) as usize];
+                {
+                    let root = BitMapBackend::with_buffer(&mut buffer, (WIDTH, HEIGHT)).into_drawing_area();
+                    draw(&root, data).map_err(render_error)?;
+                    root.present().map_err(render_error)?;
+                }
+                PngEncoder::new(writer)
+                    .write_image(&buffer, WIDTH, HEIGHT, ColorType::Rgb8)
+                    .map_err(render_error)?;
+            }
+            PlottersFormat::Svg => {
+                let mut svg = String::new();
+                {
+                    let root = SVGBackend::with_string(&mut svg, (WIDTH, HEIGHT)).into_drawing_area();
+                    draw(&root, data).map_err(render_error)?;
+                    root.present().map_err(render_error)?;
+                }
+                writer.write_all(svg.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn render_error<E: ToString>(e: E) -> ConspireError {
+    ConspireError::Render(e.to_string())
+}
+
+fn color(rgb: Rgb) -> RGBColor {
+    RGBColor(rgb.0, rgb.1, rgb.2)
+}
+
+/// Label ticks on a band scale with the category they fall on, and leave ticks in between categories blank
+fn tick_label(scale: &Scale, value: f64) -> String {
+    match scale {
+        Scale::Linear { .. } | Scale::Time { .. } => format!("{}", value),
+        Scale::Band(categories) => {
+            let idx = value.round();
+            if (value - idx).abs() < 1e-6 && idx >= 0.0 {
+                categories.get(idx as usize).cloned().unwrap_or_default()
+            } else {
+                String::new()
+            }
+        }
+    }
+}
+
+fn draw<DB>(root: &DrawingArea<DB, Shift>, data: &[Plot]) -> std::result::Result<(), Box<dyn error::Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+
+    let axes = match Axes::fit(data) {
+        Some(axes) => axes,
+        None => return panels(root, data),
+    };
+
+    let mut chart = ChartBuilder::on(root)
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(
+            axes.x.value(0.0)..axes.x.value(1.0),
+            axes.y.value(0.0)..axes.y.value(1.0),
+        )?;
+
+    let x_label = |v: &f64| tick_label(&axes.x, *v);
+    let y_label = |v: &f64| tick_label(&axes.y, *v);
+    let mut mesh = chart.configure_mesh();
+    mesh.x_label_formatter(&x_label).y_label_formatter(&y_label);
+    if let Scale::Band(categories) = &axes.x {
+        mesh.x_labels(categories.len() * 2 + 1);
+    }
+    if let Scale::Band(categories) = &axes.y {
+        mesh.y_labels(categories.len() * 2 + 1);
+    }
+    // Plotters only places ticks on round numbers, so time axes get their ticks drawn by `time_ticks` instead
+    if let Scale::Time { .. } = &axes.x {
+        mesh.x_labels(0).disable_x_mesh();
+    }
+    if let Scale::Time { .. } = &axes.y {
+        mesh.y_labels(0).disable_y_mesh();
+    }
+    mesh.draw()?;
+    time_ticks(root, &chart, &axes)?;
+
+    let bars = data.iter().filter(|p| matches!(p, Plot::Bar { .. })).count();
+    let horizontal_bars = data
+        .iter()
+        .filter(|p| matches!(p, Plot::HorizontalBar { .. }))
+        .count();
+    let (mut bar, mut horizontal_bar) = (0, 0);
+    let mut legend = false;
+
+    for (i, plot) in data.iter().enumerate() {
+        match plot {
+            Plot::Scatter { x, y, color: c, size, name } => {
+                let xs = axes.x.positions(x);
+                let colors = point_colors(c, xs.len(), PALETTE[i % PALETTE.len()]);
+                let sizes = point_sizes(size, xs.len(), 6.0);
+                let points: Vec<((f64, f64), RGBColor, f64)> = xs
+                    .into_iter()
+                    .zip(axes.y.positions(y))
+                    .zip(colors.into_iter().zip(sizes))
+                    .filter(|((x, y), _)| x.is_finite() && y.is_finite())
+                    .map(|(point, (c, s))| (point, color(c), s))
+                    .collect();
+                let legend_color = color(layer_color(i, c));
+                chart
+                    .draw_series(
+                        points
+                            .into_iter()
+                            .map(|(point, c, s)| Circle::new(point, s / 2.0, c.filled())),
+                    )?
+                    .label(layer_name(i, name))
+                    .legend(move |(x, y)| Circle::new((x + 10, y), 4, legend_color.filled()));
+                legend = true;
+            }
+            Plot::Line { x, y, missing, color: c, size, name } => {
+                let line_color = color(layer_color(i, c));
+                let width = point_sizes(size, 1, 2.0)[0] as u32;
+                let (xs, ys) = (axes.x.positions(x), axes.y.positions(y));
+                // Each run between gaps is a series of its own, of which only the first one shows up in the legend
+                for (r, run) in line_runs(x, y, *missing).into_iter().enumerate() {
+                    let points: Vec<(f64, f64)> = run.into_iter().map(|i| (xs[i], ys[i])).collect();
+                    let series = chart.draw_series(LineSeries::new(points, line_color.stroke_width(width)))?;
+                    if r == 0 {
+                        let stroke = line_color.stroke_width(2);
+                        series
+                            .label(layer_name(i, name))
+                            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], stroke));
+                        legend = true;
+                    }
+                }
+            }
+            Plot::Bar { x, y, color: c, name } => {
+                let width = axes.x.bar_width(x);
+                let offset = axes.x.data_width(bar_offset(bar, bars, width));
+                let half = axes.x.data_width(width / bars as f64) / 2.0;
+                let xs = axes.x.positions(x);
+                let colors = point_colors(c, xs.len(), PALETTE[i % PALETTE.len()]);
+                let rects: Vec<Rectangle<(f64, f64)>> = xs
+                    .into_iter()
+                    .zip(axes.y.positions(y))
+                    .zip(colors)
+                    .map(|((x, y), c)| Rectangle::new([(x + offset - half, 0.0), (x + offset + half, y)], color(c).filled()))
+                    .collect();
+                let legend_color = color(layer_color(i, c));
+                chart
+                    .draw_series(rects)?
+                    .label(layer_name(i, name))
+                    .legend(move |(x, y)| Rectangle::new([(x + 5, y - 5), (x + 15, y + 5)], legend_color.filled()));
+                bar += 1;
+                legend = true;
+            }
+            Plot::HorizontalBar { x, y, color: c, name } => {
+                let height = axes.y.bar_width(y);
+                let offset = axes.y.data_width(bar_offset(horizontal_bar, horizontal_bars, height));
+                let half = axes.y.data_width(height / horizontal_bars as f64) / 2.0;
+                let xs = axes.x.positions(x);
+                let colors = point_colors(c, xs.len(), PALETTE[i % PALETTE.len()]);
+                let rects: Vec<Rectangle<(f64, f64)>> = xs
+                    .into_iter()
+                    .zip(axes.y.positions(y))
+                    .zip(colors)
+                    .map(|((x, y), c)| Rectangle::new([(0.0, y + offset - half), (x, y + offset + half)], color(c).filled()))
+                    .collect();
+                let legend_color = color(layer_color(i, c));
+                chart
+                    .draw_series(rects)?
+                    .label(layer_name(i, name))
+                    .legend(move |(x, y)| Rectangle::new([(x + 5, y - 5), (x + 15, y + 5)], legend_color.filled()));
+                horizontal_bar += 1;
+                legend = true;
+            }
+            Plot::Box {
+                x,
+                y,
+                options,
+                color: c,
+                name,
+            } => {
+                for shape in box_shapes(i, x, y, *options, c, name, &axes) {
+                    let box_color = color(shape.color);
+                    let mut outline = shape.outline();
+                    chart.draw_series(std::iter::once(Polygon::new(outline.clone(), box_color.mix(0.5).filled())))?;
+                    outline.extend(outline.first().cloned());
+                    chart.draw_series(std::iter::once(PathElement::new(outline, box_color.stroke_width(1))))?;
+
+                    chart.draw_series(std::iter::once(PathElement::new(
+                        shape.median().to_vec(),
+                        box_color.stroke_width(2),
+                    )))?;
+                    chart.draw_series(
+                        shape
+                            .whiskers()
+                            .into_iter()
+                            .map(|whisker| PathElement::new(whisker.to_vec(), box_color.stroke_width(1))),
+                    )?;
+                    chart.draw_series(
+                        shape
+                            .mean()
+                            .into_iter()
+                            .map(|mean| DashedPathElement::new(mean.to_vec(), 4, 3, box_color.stroke_width(1))),
+                    )?;
+                    chart.draw_series(
+                        shape
+                            .outliers()
+                            .into_iter()
+                            .map(|o| Circle::new(o, 3.0, box_color.stroke_width(1))),
+                    )?;
+                }
+                // Empty series, just to give each color group a legend entry
+                for (label, legend_color) in box_legend(i, c, name) {
+                    let legend_color = color(legend_color);
+                    chart
+                        .draw_series(std::iter::empty::<Rectangle<(f64, f64)>>())?
+                        .label(label)
+                        .legend(move |(x, y)| {
+                            Rectangle::new([(x + 5, y - 5), (x + 15, y + 5)], legend_color.mix(0.5).filled())
+                        });
+                }
+                legend = true;
+            }
+            Plot::Violin {
+                x,
+                group,
+                bandwidth,
+                inner,
+                color: c,
+                name,
+            } => {
+                let violin_color = color(layer_color(i, c));
+                let shapes = violin_shapes(x, group, &layer_name(i, name), *bandwidth, &axes.y);
+                for shape in &shapes {
+                    let mut outline = shape.outline();
+                    chart.draw_series(std::iter::once(Polygon::new(outline.clone(), violin_color.mix(0.5).filled())))?;
+                    outline.extend(outline.first().cloned());
+                    chart.draw_series(std::iter::once(PathElement::new(outline, violin_color.stroke_width(1))))?;
+
+                    let stats = &shape.violin.stats;
+                    let (y, half) = (shape.center, shape.box_half());
+                    match inner {
+                        ViolinInner::Box => {
+                            chart.draw_series(vec![
+                                Rectangle::new([(stats.q1, y - half), (stats.q3, y + half)], violin_color.filled()),
+                            ])?;
+                            chart.draw_series(vec![
+                                PathElement::new(
+                                    vec![(stats.lower_whisker, y), (stats.upper_whisker, y)],
+                                    violin_color.stroke_width(1),
+                                ),
+                                PathElement::new(
+                                    vec![(stats.median, y - half), (stats.median, y + half)],
+                                    WHITE.stroke_width(2),
+                                ),
+                            ])?;
+                        }
+                        ViolinInner::Quartiles => {
+                            let line = |quartile: f64| {
+                                let (bottom, top) = shape.span(quartile);
+                                vec![(quartile, bottom), (quartile, top)]
+                            };
+                            chart.draw_series(vec![stats.q1, stats.q3].into_iter().map(|quartile| {
+                                DashedPathElement::new(line(quartile), 4, 3, violin_color.stroke_width(1))
+                            }))?;
+                            chart.draw_series(std::iter::once(PathElement::new(
+                                line(stats.median),
+                                violin_color.stroke_width(1),
+                            )))?;
+                        }
+                        ViolinInner::None => {}
+                    }
+                }
+                if !shapes.is_empty() {
+                    // An empty series, just to give the layer a legend entry
+                    chart
+                        .draw_series(std::iter::empty::<Rectangle<(f64, f64)>>())?
+                        .label(layer_name(i, name))
+                        .legend(move |(x, y)| {
+                            Rectangle::new([(x + 5, y - 5), (x + 15, y + 5)], violin_color.mix(0.5).filled())
+                        });
+                    legend = true;
+                }
+            }
+            Plot::Histogram { x, binning, color: c, name } => {
+                let fill = color(layer_color(i, c));
+                let rects: Vec<Rectangle<(f64, f64)>> = histogram_bars(x, *binning, &axes.x)
+                    .into_iter()
+                    .map(|(start, end, count)| Rectangle::new([(start, 0.0), (end, count)], fill.filled()))
+                    .collect();
+                chart
+                    .draw_series(rects)?
+                    .label(layer_name(i, name))
+                    .legend(move |(x, y)| Rectangle::new([(x + 5, y - 5), (x + 15, y + 5)], fill.filled()));
+                legend = true;
+            }
+            Plot::Heatmap { x, y, z, options, .. } => {
+                let map = ColorMap::heatmap(z, options);
+                let cells = heatmap_cells(x, y, z, options);
+                chart.draw_series(cells.iter().map(|cell| {
+                    Rectangle::new(
+                        [(cell.left, cell.bottom), (cell.right, cell.top)],
+                        color(map.color(cell.value)).filled(),
+                    )
+                }))?;
+                chart.draw_series(cells.iter().filter_map(|cell| {
+                    let text = cell.text.clone()?;
+                    let font = ("sans-serif", 12)
+                        .into_font()
+                        .color(&color(map.color(cell.value).contrast()))
+                        .pos(Pos::new(HPos::Center, VPos::Center));
+                    let center = ((cell.left + cell.right) / 2.0, (cell.bottom + cell.top) / 2.0);
+                    Some(Text::new(text, center, font))
+                }))?;
+            }
+            Plot::Contour { x, y, z, options, .. } => {
+                for line in contour_lines(x, y, z, options) {
+                    let style = color(line.color).stroke_width(2);
+                    let segments = line.segments.iter().map(|[from, to]| PathElement::new(vec![*from, *to], style));
+                    chart.draw_series(segments)?;
+                }
+            }
+            Plot::Pie { .. } | Plot::Surface { .. } | Plot::Treemap { .. } | Plot::ParallelCoordinates { .. } => {
+                unreachable!(
+                    "pies, surfaces, and treemaps and parallel coordinates on axes, are rejected before drawing"
+                )
+            }
+        }
+    }
+
+    if legend {
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+    }
+    panels(root, data)
+}
+
+/// Draw the ticks and grid lines of time axes on round dates and times, labelled like on the other backends
+fn time_ticks<DB>(
+    root: &DrawingArea<DB, Shift>,
+    chart: &ChartContext<DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    axes: &Axes,
+) -> std::result::Result<(), Box<dyn error::Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let font = ("sans-serif", 12).into_font().color(&BLACK);
+    let (left, right) = (axes.x.value(0.0), axes.x.value(1.0));
+    let (bottom, top) = (axes.y.value(0.0), axes.y.value(1.0));
+    if let Scale::Time { .. } = &axes.x {
+        for tick in axes.x.ticks(8) {
+            let x = tick.value;
+            let (start, end) = (chart.backend_coord(&(x, bottom)), chart.backend_coord(&(x, top)));
+            root.draw(&PathElement::new(vec![start, end], BLACK.mix(0.1)))?;
+            root.draw(&PathElement::new(vec![start, (start.0, start.1 + 5)], BLACK))?;
+            let below = font.clone().pos(Pos::new(HPos::Center, VPos::Top));
+            root.draw(&Text::new(tick.label, (start.0, start.1 + 8), below))?;
+        }
+    }
+    if let Scale::Time { .. } = &axes.y {
+        for tick in axes.y.ticks(6) {
+            let y = tick.value;
+            let (start, end) = (chart.backend_coord(&(left, y)), chart.backend_coord(&(right, y)));
+            root.draw(&PathElement::new(vec![start, end], BLACK.mix(0.1)))?;
+            root.draw(&PathElement::new(vec![start, (start.0 - 5, start.1)], BLACK))?;
+            let beside = font.clone().pos(Pos::new(HPos::Right, VPos::Center));
+            root.draw(&Text::new(tick.label, (start.0 - 8, start.1), beside))?;
+        }
+    }
+    Ok(())
+}
+
+/// Draw any treemaps and parallel coordinates side by side across the image, as they don't share the cartesian axes
+fn panels<DB>(root: &DrawingArea<DB, Shift>, data: &[Plot]) -> std::result::Result<(), Box<dyn error::Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let panels: Vec<(usize, &Plot)> = data
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| matches!(p, Plot::Treemap { .. } | Plot::ParallelCoordinates { .. }))
+        .collect();
+    let slot_width = (f64::from(WIDTH) - 40.0) / panels.len().max(1) as f64;
+
+    for (i, (idx, plot)) in panels.iter().enumerate() {
+        let left = 20.0 + slot_width * i as f64;
+        if let Plot::ParallelCoordinates { dimensions, color: c, .. } = plot {
+            let rect = Rect {
+                x0: left + 40.0,
+                y0: 20.0,
+                x1: left + slot_width - 20.0,
+                y1: f64::from(HEIGHT) - 40.0,
+            };
+            parallel_coordinates(root, rect, *idx, dimensions, c)?;
+        }
+        if let Plot::Treemap {
+            label,
+            parent,
+            value,
+            color: c,
+            ..
+        } = plot
+        {
+            let rect = Rect {
+                x0: left + 5.0,
+                y0: 20.0,
+                x1: left + slot_width - 5.0,
+                y1: f64::from(HEIGHT) - 20.0,
+            };
+            for tile in tiles(label, parent, value, c, rect) {
+                let Rect { x0, y0, x1, y1 } = tile.rect;
+                let corners = [(x0.round() as i32, y0.round() as i32), (x1.round() as i32, y1.round() as i32)];
+                root.draw(&Rectangle::new(corners, color(tile.color).filled()))?;
+                root.draw(&Rectangle::new(corners, WHITE.stroke_width(1)))?;
+                // Labels are only drawn where they fit, guessing at the width of the text
+                if tile.leaf && tile.label.chars().count() as f64 * 7.0 + 8.0 < x1 - x0 && y1 - y0 > 18.0 {
+                    let font = ("sans-serif", 12)
+                        .into_font()
+                        .color(&color(tile.text_color()))
+                        .pos(Pos::new(HPos::Center, VPos::Center));
+                    let center = (((x0 + x1) / 2.0).round() as i32, ((y0 + y1) / 2.0).round() as i32);
+                    root.draw(&Text::new(tile.label.clone(), center, font))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Draw a parallel coordinates plot, with a vertical axis per dimension spread across a rectangle in pixels
+fn parallel_coordinates<DB>(
+    root: &DrawingArea<DB, Shift>,
+    rect: Rect,
+    idx: usize,
+    dimensions: &[(String, VectorData)],
+    c: &Option<VectorData>,
+) -> std::result::Result<(), Box<dyn error::Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let scales: Vec<Scale> = dimensions.iter().map(|(_, data)| dimension_scale(data)).collect();
+    let units: Vec<Vec<f64>> = dimensions.iter().zip(&scales).map(|((_, d), s)| s.units(d)).collect();
+    let len = units.first().map_or(0, |u| u.len());
+    let x = |d: usize| (rect.x0 + rect.width() * dimension_position(d, dimensions.len())).round() as i32;
+    let y = |unit: f64| (rect.y1 - rect.height() * unit).round() as i32;
+
+    for (p, line_color) in point_colors(c, len, PALETTE[idx % PALETTE.len()]).into_iter().enumerate() {
+        let points: Vec<(i32, i32)> = units
+            .iter()
+            .enumerate()
+            .filter(|(_, u)| u[p].is_finite())
+            .map(|(d, u)| (x(d), y(u[p])))
+            .collect();
+        root.draw(&PathElement::new(points, color(line_color).mix(0.7).stroke_width(1)))?;
+    }
+
+    let font = ("sans-serif", 12).into_font().color(&BLACK);
+    for (d, ((name, _), scale)) in dimensions.iter().zip(&scales).enumerate() {
+        root.draw(&PathElement::new(vec![(x(d), y(0.0)), (x(d), y(1.0))], BLACK.stroke_width(1)))?;
+        let below = font.clone().pos(Pos::new(HPos::Center, VPos::Top));
+        root.draw(&Text::new(name.clone(), (x(d), y(0.0) + 6), below))?;
+        for tick in scale.ticks(5) {
+            let beside = font.clone().pos(Pos::new(HPos::Right, VPos::Center));
+            root.draw(&Text::new(tick.label, (x(d) - 4, y(tick.position)), beside))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Plotters, PlottersFormat, HEIGHT, WIDTH};
+    use crate::backends::Renderable;
+    use crate::data::VectorData;
+    use crate::error::ConspireError;
+    use crate::{Layer, Plot};
+
+    fn layer() -> Layer<VectorData> {
+        Layer::new().x(vec!["a", "b", "c"]).y(vec![1.0, 3.0, 2.0])
+    }
+
+    #[test]
+    fn png() {
+        let layer = layer();
+        let mut png = Vec::new();
+        Plotters::new(PlottersFormat::Png).write(&[Plot::bar(&layer), Plot::line(&layer)], &mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let image = image::load_from_memory(&png).unwrap();
+        assert_eq!((image.width(), image.height()), (WIDTH, HEIGHT));
+    }
+
+    #[test]
+    fn svg() {
+        let layer = layer();
+        let mut svg = Vec::new();
+        Plotters::new(PlottersFormat::Svg).write(&[Plot::scatter(&layer)], &mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.trim_start().starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<svg").count(), 1);
+        assert!(svg.contains("<circle"));
+    }
+
+    #[test]
+    fn unsupported_plots() {
+        let layer = layer();
+        let treemap = Layer::new().label(vec!["root"]).parent(vec![""]);
+        let grid = Layer::new().z(vec![vec![0.0, 4.0], vec![0.0, 4.0]]);
+        for data in [
+            vec![Plot::pie(&layer)],
+            vec![Plot::scatter(&layer), Plot::treemap(&treemap)],
+            vec![Plot::surface(&grid)],
+        ] {
+            let mut out = Vec::new();
+            assert!(matches!(
+                Plotters::new(PlottersFormat::Svg).write(&data, &mut out),
+                Err(ConspireError::UnsupportedPlot { backend: "plotters", .. })
+            ));
+            assert!(out.is_empty());
+        }
+    }
+}
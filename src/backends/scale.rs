@@ -0,0 +1,1306 @@
+//! Layout helpers for the backends that position every mark themselves, rather than handing the data over to a
+//! plotting library. Positions are expressed in "units", where 0 and 1 are the two ends of an axis, so each backend
+//! only has to map units onto its own canvas.
+
+use super::time::format_timestamp;
+use crate::data::{MatrixData, Missing, VectorData};
+use crate::error::ConspireError;
+use crate::heatmap::{Annotations, Colorscale, ContourOptions, HeatmapOptions};
+use crate::stats::{
+    box_stats, histogram, violins, Bandwidth, Binning, BoxOptions, BoxStats, Histogram, Violin, Whiskers,
+};
+use crate::Plot;
+
+/// A color, as red, green and blue components
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+/// Colors given to successive layers, matching the Plotly defaults
+pub const PALETTE: [Rgb; 10] = [
+    Rgb(0x1f, 0x77, 0xb4),
+    Rgb(0xff, 0x7f, 0x0e),
+    Rgb(0x2c, 0xa0, 0x2c),
+    Rgb(0xd6, 0x27, 0x28),
+    Rgb(0x94, 0x67, 0xbd),
+    Rgb(0x8c, 0x56, 0x4b),
+    Rgb(0xe3, 0x77, 0xc2),
+    Rgb(0x7f, 0x7f, 0x7f),
+    Rgb(0xbc, 0xbd, 0x22),
+    Rgb(0x17, 0xbe, 0xcf),
+];
+
+/// Stops of the sequential color scale used for quantitative colors (viridis)
+const SEQUENTIAL: [Rgb; 5] = [
+    Rgb(0x44, 0x01, 0x54),
+    Rgb(0x3b, 0x52, 0x8b),
+    Rgb(0x21, 0x91, 0x8c),
+    Rgb(0x5e, 0xc9, 0x62),
+    Rgb(0xfd, 0xe7, 0x25),
+];
+
+const NAMED_COLORS: [(&str, Rgb); 15] = [
+    ("black", Rgb(0, 0, 0)),
+    ("white", Rgb(255, 255, 255)),
+    ("red", Rgb(255, 0, 0)),
+    ("green", Rgb(0, 128, 0)),
+    ("blue", Rgb(0, 0, 255)),
+    ("yellow", Rgb(255, 255, 0)),
+    ("orange", Rgb(255, 165, 0)),
+    ("purple", Rgb(128, 0, 128)),
+    ("pink", Rgb(255, 192, 203)),
+    ("brown", Rgb(165, 42, 42)),
+    ("gray", Rgb(128, 128, 128)),
+    ("grey", Rgb(128, 128, 128)),
+    ("cyan", Rgb(0, 255, 255)),
+    ("magenta", Rgb(255, 0, 255)),
+    ("teal", Rgb(0, 128, 128)),
+];
+
+impl Rgb {
+    /// Parse a CSS-style color, either as a hex code (`#rgb` or `#rrggbb`) or as a common color name
+    pub fn parse(color: &str) -> Option<Rgb> {
+        let color = color.trim().to_lowercase();
+        if let Some(hex) = color.strip_prefix('#') {
+            let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+            return match hex.len() {
+                3 => Some(Rgb(
+                    channel(&hex[0..1].repeat(2))?,
+                    channel(&hex[1..2].repeat(2))?,
+                    channel(&hex[2..3].repeat(2))?,
+                )),
+                6 => Some(Rgb(channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?)),
+                _ => None,
+            };
+        }
+
+        NAMED_COLORS
+            .iter()
+            .find(|(name, _)| *name == color)
+            .map(|(_, rgb)| *rgb)
+    }
+
+    pub fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    /// Black or white, whichever stands out more on this color, e.g. for text written on it
+    pub fn contrast(self) -> Rgb {
+        let luminance = 0.299 * f64::from(self.0) + 0.587 * f64::from(self.1) + 0.114 * f64::from(self.2);
+        if luminance > 150.0 {
+            Rgb(0, 0, 0)
+        } else {
+            Rgb(255, 255, 255)
+        }
+    }
+
+    fn lerp(self, other: Rgb, t: f64) -> Rgb {
+        let channel = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * t).round() as u8;
+        Rgb(
+            channel(self.0, other.0),
+            channel(self.1, other.1),
+            channel(self.2, other.2),
+        )
+    }
+}
+
+/// Map a value in `[0, 1]` onto the sequential color scale
+pub fn sequential(t: f64) -> Rgb {
+    let t = if t.is_finite() { t.clamp(0.0, 1.0) } else { 0.0 };
+    let position = t * (SEQUENTIAL.len() - 1) as f64;
+    let idx = (position.floor() as usize).min(SEQUENTIAL.len() - 2);
+    SEQUENTIAL[idx].lerp(SEQUENTIAL[idx + 1], position - idx as f64)
+}
+
+/// The colors of a colorscale, at positions from 0 to 1
+fn colorscale_stops(colorscale: &Colorscale) -> Vec<(f64, Rgb)> {
+    let even = |colors: &[Rgb]| -> Vec<(f64, Rgb)> {
+        let last = (colors.len() - 1) as f64;
+        colors.iter().enumerate().map(|(i, c)| (i as f64 / last, *c)).collect()
+    };
+    match colorscale {
+        Colorscale::Viridis => even(&SEQUENTIAL),
+        Colorscale::Greys => even(&[Rgb(0xff, 0xff, 0xff), Rgb(0x96, 0x96, 0x96), Rgb(0, 0, 0)]),
+        Colorscale::Blues => even(&[Rgb(0xf7, 0xfb, 0xff), Rgb(0x6b, 0xae, 0xd6), Rgb(0x08, 0x30, 0x6b)]),
+        Colorscale::Reds => even(&[Rgb(0xff, 0xf5, 0xf0), Rgb(0xfb, 0x6a, 0x4a), Rgb(0x67, 0x00, 0x0d)]),
+        Colorscale::BlueRed => even(&[
+            Rgb(0x05, 0x30, 0x61),
+            Rgb(0x43, 0x93, 0xc3),
+            Rgb(0xf7, 0xf7, 0xf7),
+            Rgb(0xd6, 0x60, 0x4d),
+            Rgb(0x67, 0x00, 0x1f),
+        ]),
+        Colorscale::Custom(stops) => stops
+            .iter()
+            .map(|(t, c)| (*t, Rgb::parse(c).unwrap_or(Rgb(0, 0, 0))))
+            .collect(),
+    }
+}
+
+/// A range of values widened around its value if it's empty, so a single value still has a range to place it and
+/// a colorbar's ticks in
+fn widen(min: f64, max: f64) -> (f64, f64) {
+    if min == max {
+        (min - 1.0, max + 1.0)
+    } else {
+        (min, max)
+    }
+}
+
+/// Maps values onto a colorscale, spread over a range of values
+pub struct ColorMap {
+    pub stops: Vec<(f64, Rgb)>,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ColorMap {
+    /// The sequential color scale used for quantitative colors, over a range of values
+    pub fn sequential(min: f64, max: f64) -> ColorMap {
+        let (min, max) = widen(min, max);
+        ColorMap {
+            stops: colorscale_stops(&Colorscale::Viridis),
+            min,
+            max,
+        }
+    }
+
+    /// The colors of a heatmap, spanning its values unless the options limit or center them
+    pub fn heatmap(z: &MatrixData, options: &HeatmapOptions) -> ColorMap {
+        let (mut min, mut max) = matrix_range(z).unwrap_or((0.0, 1.0));
+        if let Some(mid) = options.midpoint {
+            let half = (max - mid).abs().max((mid - min).abs());
+            min = mid - half;
+            max = mid + half;
+        }
+        let (min, max) = widen(options.zmin.unwrap_or(min), options.zmax.unwrap_or(max));
+        ColorMap {
+            stops: colorscale_stops(&options.colorscale),
+            min,
+            max,
+        }
+    }
+
+    /// The colors of a contour plot or surface, spanning the values of its matrix
+    pub fn grid(z: &MatrixData, colorscale: &Colorscale) -> ColorMap {
+        let (min, max) = matrix_range(z).unwrap_or((0.0, 1.0));
+        let (min, max) = widen(min, max);
+        ColorMap {
+            stops: colorscale_stops(colorscale),
+            min,
+            max,
+        }
+    }
+
+    /// The position of a value along the colorscale, clamped to its ends
+    pub fn unit(&self, value: f64) -> f64 {
+        if self.max > self.min {
+            ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.5
+        }
+    }
+
+    pub fn color(&self, value: f64) -> Rgb {
+        let t = self.unit(value);
+        let upper = self.stops.iter().position(|(s, _)| *s >= t).unwrap_or(self.stops.len() - 1);
+        if upper == 0 {
+            return self.stops[0].1;
+        }
+        let ((start, from), (end, to)) = (self.stops[upper - 1], self.stops[upper]);
+        from.lerp(to, if end > start { (t - start) / (end - start) } else { 1.0 })
+    }
+}
+
+/// Resolve a color channel into one color per data point. Quantitative colors are mapped onto the sequential color
+/// scale, as are ordinal colors from the first category to the last, while categorical colors are used as-is if they
+/// name a color, and are otherwise given a color per category.
+pub fn point_colors(color: &Option<VectorData>, len: usize, default: Rgb) -> Vec<Rgb> {
+    let colors = match color {
+        Some(VectorData::Quantitative(v)) | Some(VectorData::Temporal(v)) => {
+            let (min, max) = range(v).unwrap_or((0.0, 1.0));
+            v.iter()
+                .map(|c| sequential(if max > min { (c - min) / (max - min) } else { 0.5 }))
+                .collect()
+        }
+        Some(VectorData::Categorical(v)) => {
+            let categories = categories(v);
+            v.iter()
+                .map(|c| {
+                    Rgb::parse(c).unwrap_or_else(|| {
+                        let idx = categories.iter().position(|x| x == c).unwrap_or(0);
+                        PALETTE[idx % PALETTE.len()]
+                    })
+                })
+                .collect()
+        }
+        Some(VectorData::Ordinal { values, order }) => {
+            let last = order.len().saturating_sub(1).max(1) as f64;
+            values
+                .iter()
+                .map(|v| sequential(order.iter().position(|o| o == v).unwrap_or(0) as f64 / last))
+                .collect()
+        }
+        None => vec![default],
+    };
+
+    broadcast(colors, len, default)
+}
+
+/// The color representing a whole layer, such as the color of a line or of a legend entry
+pub fn layer_color(idx: usize, color: &Option<VectorData>) -> Rgb {
+    let default = PALETTE[idx % PALETTE.len()];
+    match color {
+        Some(VectorData::Categorical(v)) if v.len() == 1 => Rgb::parse(&v[0]).unwrap_or(default),
+        _ => default,
+    }
+}
+
+/// Resolve a size channel into one size per data point
+pub fn point_sizes(size: &Option<VectorData>, len: usize, default: f64) -> Vec<f64> {
+    let sizes = match size {
+        Some(VectorData::Quantitative(v)) => v.to_vec(),
+        _ => vec![default],
+    };
+
+    broadcast(sizes, len, default)
+}
+
+/// Repeat single values across every data point, filling in missing values with a default
+fn broadcast<T: Copy>(values: Vec<T>, len: usize, default: T) -> Vec<T> {
+    if values.len() == 1 {
+        vec![values[0]; len]
+    } else {
+        (0..len).map(|i| *values.get(i).unwrap_or(&default)).collect()
+    }
+}
+
+/// Check that a backend can draw every plot, before it starts writing anything. `drawable` tells which kinds of plot
+/// the backend can draw at all, while treemaps and parallel coordinates, which bring their own axes, can only be drawn
+/// without any plots on shared axes.
+pub fn supported(backend: &'static str, data: &[Plot], drawable: impl Fn(&Plot) -> bool) -> Result<(), ConspireError> {
+    flat(backend, data, drawable)?;
+    let panel = |p: &&Plot| matches!(p, Plot::Treemap { .. } | Plot::ParallelCoordinates { .. });
+    if data.iter().any(|p| panel(&p)) && !data.iter().all(|p| panel(&p)) {
+        return Err(ConspireError::UnsupportedPlot {
+            backend,
+            plot: "treemap or parallel coordinates alongside other",
+        });
+    }
+    Ok(())
+}
+
+/// Check that a backend can draw every plot, like `supported`, for backends placing treemaps and parallel
+/// coordinates beside plots on axes. Surfaces are 3D, so they can't share a plot with any flat plots.
+pub fn flat(backend: &'static str, data: &[Plot], drawable: impl Fn(&Plot) -> bool) -> Result<(), ConspireError> {
+    if let Some(plot) = data.iter().find(|p| !drawable(p)) {
+        return Err(ConspireError::UnsupportedPlot { backend, plot: plot.kind() });
+    }
+    let surface = |p: &&Plot| matches!(p, Plot::Surface { .. });
+    if data.iter().any(|p| surface(&p)) && !data.iter().all(|p| surface(&p)) {
+        return Err(ConspireError::UnsupportedPlot { backend, plot: "surface alongside other" });
+    }
+    Ok(())
+}
+
+/// The name of a layer, falling back to Plotly-style trace numbering
+pub fn layer_name(idx: usize, name: &Option<String>) -> String {
+    match name {
+        Some(n) => n.to_string(),
+        None => format!("trace {}", idx),
+    }
+}
+
+/// The finite minimum and maximum of some data
+pub fn range(values: &[f64]) -> Option<(f64, f64)> {
+    values
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold(None, |acc, v| match acc {
+            None => Some((*v, *v)),
+            Some((min, max)) => Some((min.min(*v), max.max(*v))),
+        })
+}
+
+/// The finite minimum and maximum of a matrix
+pub fn matrix_range(data: &MatrixData) -> Option<(f64, f64)> {
+    match data {
+        MatrixData::Quantitative(rows) => range(&rows.concat()),
+    }
+}
+
+/// Unique categories, in the order they first appear
+pub fn categories(values: &[String]) -> Vec<String> {
+    let mut categories: Vec<String> = Vec::new();
+    for v in values {
+        if !categories.contains(v) {
+            categories.push(v.to_string());
+        }
+    }
+    categories
+}
+
+/// The categories of categorical or ordinal data, in the order of ordinal data, or else in the order they first
+/// appear. Numbers and points in time have no categories.
+pub fn levels(data: &VectorData) -> Vec<String> {
+    match data {
+        VectorData::Categorical(v) => categories(v),
+        VectorData::Ordinal { order, .. } => order.to_vec(),
+        VectorData::Quantitative(_) | VectorData::Temporal(_) => Vec::new(),
+    }
+}
+
+/// The smallest distance between two distinct values, which is used to size bars on a quantitative axis
+pub fn spacing(values: &[f64]) -> f64 {
+    let mut sorted: Vec<f64> = values.iter().cloned().filter(|v| v.is_finite()).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    sorted
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|d| *d > 0.0)
+        .fold(None, |acc: Option<f64>, d| Some(acc.map_or(d, |a| a.min(d))))
+        .unwrap_or(1.0)
+}
+
+/// Split the points of a line into the runs drawn as connected paths, as indices into its data. Points missing a
+/// coordinate (NaN or infinite) are left out, and the line is broken at them unless it interpolates across the gap.
+pub fn line_runs(x: &VectorData, y: &VectorData, missing: Missing) -> Vec<Vec<usize>> {
+    let present = |data: &VectorData, i: usize| match data {
+        VectorData::Quantitative(v) | VectorData::Temporal(v) => v[i].is_finite(),
+        VectorData::Categorical(_) | VectorData::Ordinal { .. } => true,
+    };
+    let mut runs: Vec<Vec<usize>> = vec![Vec::new()];
+    for i in 0..x.len().min(y.len()) {
+        if present(x, i) && present(y, i) {
+            runs.last_mut().unwrap().push(i);
+        } else if missing == Missing::Skip && !runs.last().unwrap().is_empty() {
+            runs.push(Vec::new());
+        }
+    }
+    runs.retain(|run| !run.is_empty());
+    runs
+}
+
+/// The values of some data at the given indices. Data holding a single value, which applies to every point, is kept
+/// as it is.
+pub fn subset(data: &VectorData, indices: &[usize]) -> VectorData {
+    match data {
+        VectorData::Quantitative(v) if v.len() == 1 => VectorData::Quantitative(v.clone()),
+        VectorData::Categorical(v) if v.len() == 1 => VectorData::Categorical(v.clone()),
+        VectorData::Temporal(v) if v.len() == 1 => VectorData::Temporal(v.clone()),
+        VectorData::Quantitative(v) => VectorData::Quantitative(indices.iter().map(|i| v[*i]).collect()),
+        VectorData::Categorical(v) => VectorData::Categorical(indices.iter().map(|i| v[*i].clone()).collect()),
+        VectorData::Temporal(v) => VectorData::Temporal(indices.iter().map(|i| v[*i]).collect()),
+        VectorData::Ordinal { values, order } => VectorData::Ordinal {
+            values: indices.iter().map(|i| values[*i].clone()).collect(),
+            order: order.to_vec(),
+        },
+    }
+}
+
+/// The labelled slices of a pie chart. Quantitative data gives the size of each slice directly, while categorical
+/// data, ordinal data and points in time are counted, with ordinal slices in the order of their categories.
+pub fn pie_slices(data: &VectorData) -> Vec<(String, f64)> {
+    match data {
+        VectorData::Temporal(v) => {
+            let times: Vec<String> = v.iter().filter(|t| t.is_finite()).map(|t| format_timestamp(*t)).collect();
+            pie_slices(&VectorData::Categorical(times))
+        }
+        VectorData::Quantitative(v) => v
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.is_finite() && **v > 0.0)
+            .map(|(i, v)| (i.to_string(), *v))
+            .collect(),
+        VectorData::Categorical(v) | VectorData::Ordinal { values: v, .. } => levels(data)
+            .into_iter()
+            .map(|c| {
+                let count = v.iter().filter(|x| **x == c).count();
+                (c, count as f64)
+            })
+            .filter(|(_, count)| *count > 0.0)
+            .collect(),
+    }
+}
+
+/// The edges of the cells along one axis of a heatmap, in axis coordinates. Numeric coordinates are either the edges
+/// themselves, or the centers of the cells, which then reach halfway to their neighbours, so uneven grids keep their
+/// spacing. Other cells are placed at their index.
+pub fn cell_edges(coordinates: &Option<VectorData>, count: usize) -> Vec<f64> {
+    match coordinates {
+        Some(VectorData::Quantitative(v)) | Some(VectorData::Temporal(v)) if v.len() == count + 1 => v.to_vec(),
+        Some(VectorData::Quantitative(v)) | Some(VectorData::Temporal(v)) if count > 1 => {
+            let mut edges = vec![v[0] - (v[1] - v[0]) / 2.0];
+            edges.extend(v.windows(2).map(|w| (w[0] + w[1]) / 2.0));
+            edges.push(v[count - 1] + (v[count - 1] - v[count - 2]) / 2.0);
+            edges
+        }
+        Some(VectorData::Quantitative(v)) | Some(VectorData::Temporal(v)) if count == 1 => vec![v[0] - 0.5, v[0] + 0.5],
+        _ => (0..=count).map(|i| i as f64 - 0.5).collect(),
+    }
+}
+
+/// A cell of a heatmap, spanning from its left to its right and its bottom to its top edge in axis coordinates
+pub struct HeatmapCell {
+    pub row: usize,
+    pub column: usize,
+    pub left: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub top: f64,
+    pub value: f64,
+    /// The annotation written on the cell
+    pub text: Option<String>,
+}
+
+/// The cells of a heatmap with a finite value, row by row
+pub fn heatmap_cells(
+    x: &Option<VectorData>,
+    y: &Option<VectorData>,
+    z: &MatrixData,
+    options: &HeatmapOptions,
+) -> Vec<HeatmapCell> {
+    let MatrixData::Quantitative(rows) = z;
+    let columns = rows.first().map_or(0, |r| r.len());
+    let (xs, ys) = (cell_edges(x, columns), cell_edges(y, rows.len()));
+
+    let mut cells = Vec::new();
+    for (r, row) in rows.iter().enumerate() {
+        for (c, value) in row.iter().enumerate().filter(|(_, v)| v.is_finite()) {
+            let text = match &options.annotations {
+                Annotations::None => None,
+                Annotations::Values(decimals) => Some(format!("{:.*}", decimals, value)),
+                Annotations::Text(text) => Some(text[r][c].to_string()),
+            };
+            cells.push(HeatmapCell {
+                row: r,
+                column: c,
+                left: xs[c],
+                right: xs[c + 1],
+                bottom: ys[r],
+                top: ys[r + 1],
+                value: *value,
+                text,
+            });
+        }
+    }
+    cells
+}
+
+/// The points of a grid along one axis, in axis coordinates. Numeric coordinates are either the points themselves,
+/// or the edges of the cells around them, with the points halfway between. Other points are placed at their index.
+pub fn grid_centers(coordinates: &Option<VectorData>, count: usize) -> Vec<f64> {
+    match coordinates {
+        Some(VectorData::Quantitative(v)) | Some(VectorData::Temporal(v)) if v.len() == count + 1 => {
+            v.windows(2).map(|w| (w[0] + w[1]) / 2.0).collect()
+        }
+        Some(VectorData::Quantitative(v)) | Some(VectorData::Temporal(v)) if v.len() == count => v.to_vec(),
+        _ => (0..count).map(|i| i as f64).collect(),
+    }
+}
+
+/// The values the lines of a contour plot are drawn at, evenly spaced between the ends of its color map
+pub fn contour_levels(map: &ColorMap, levels: usize) -> Vec<f64> {
+    let step = (map.max - map.min) / (levels + 1) as f64;
+    (1..=levels).map(|l| map.min + l as f64 * step).collect()
+}
+
+/// The line of a contour plot at a single value, as separate segments in axis coordinates
+pub struct ContourLine {
+    pub level: f64,
+    pub color: Rgb,
+    pub segments: Vec<[(f64, f64); 2]>,
+}
+
+/// The lines of a contour plot, traced through the cells between the points of the grid with marching squares.
+/// Cells with a missing corner are left out, and levels no cell crosses have no line.
+pub fn contour_lines(
+    x: &Option<VectorData>,
+    y: &Option<VectorData>,
+    z: &MatrixData,
+    options: &ContourOptions,
+) -> Vec<ContourLine> {
+    let MatrixData::Quantitative(rows) = z;
+    let columns = rows.first().map_or(0, |r| r.len());
+    let (xs, ys) = (grid_centers(x, columns), grid_centers(y, rows.len()));
+    let map = ColorMap::grid(z, &options.colorscale);
+
+    let mut lines = Vec::new();
+    for level in contour_levels(&map, options.levels) {
+        let mut segments = Vec::new();
+        for r in 0..rows.len().saturating_sub(1) {
+            for c in 0..columns.saturating_sub(1) {
+                // The corners go around the cell, and each edge runs from a corner to the next
+                let corners = [
+                    (xs[c], ys[r], rows[r][c]),
+                    (xs[c + 1], ys[r], rows[r][c + 1]),
+                    (xs[c + 1], ys[r + 1], rows[r + 1][c + 1]),
+                    (xs[c], ys[r + 1], rows[r + 1][c]),
+                ];
+                if corners.iter().any(|(_, _, v)| !v.is_finite()) {
+                    continue;
+                }
+                let crossings: Vec<(f64, f64)> = (0..4)
+                    .filter_map(|e| {
+                        let ((x0, y0, a), (x1, y1, b)) = (corners[e], corners[(e + 1) % 4]);
+                        if (a < level) == (b < level) {
+                            return None;
+                        }
+                        let t = (level - a) / (b - a);
+                        Some((x0 + t * (x1 - x0), y0 + t * (y1 - y0)))
+                    })
+                    .collect();
+                match crossings.len() {
+                    2 => segments.push([crossings[0], crossings[1]]),
+                    // A saddle, crossed on every edge. The value at the center tells whether the first corner is
+                    // joined to the opposite one, which separates the other two corners, or cut off itself.
+                    4 => {
+                        let center = corners.iter().map(|(_, _, v)| v).sum::<f64>() / 4.0;
+                        if (center < level) == (corners[0].2 < level) {
+                            segments.push([crossings[0], crossings[1]]);
+                            segments.push([crossings[2], crossings[3]]);
+                        } else {
+                            segments.push([crossings[3], crossings[0]]);
+                            segments.push([crossings[1], crossings[2]]);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if !segments.is_empty() {
+            lines.push(ContourLine {
+                level,
+                color: map.color(level),
+                segments,
+            });
+        }
+    }
+    lines
+}
+
+/// A labelled position along an axis
+pub struct Tick {
+    pub position: f64,
+    /// The value at the tick, in axis coordinates
+    pub value: f64,
+    pub label: String,
+}
+
+/// Maps data onto a position along an axis
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scale {
+    Linear { min: f64, max: f64 },
+    Band(Vec<String>),
+    /// A linear scale over points in time, in seconds since the Unix epoch, with ticks on round dates and times
+    Time { min: f64, max: f64 },
+}
+
+impl Scale {
+    /// The position of a quantitative value. On a band scale, the value is taken as the index of a category.
+    pub fn unit(&self, value: f64) -> f64 {
+        match self {
+            Scale::Linear { min, max } | Scale::Time { min, max } => (value - min) / (max - min),
+            Scale::Band(categories) => (value + 0.5) / categories.len() as f64,
+        }
+    }
+
+    /// The quantitative value at a position, the inverse of `unit`
+    pub fn value(&self, unit: f64) -> f64 {
+        match self {
+            Scale::Linear { min, max } | Scale::Time { min, max } => min + unit * (max - min),
+            Scale::Band(categories) => unit * categories.len() as f64 - 0.5,
+        }
+    }
+
+    /// The coordinates of some data along the axis, where categories are placed at their index
+    pub fn positions(&self, data: &VectorData) -> Vec<f64> {
+        match data {
+            VectorData::Quantitative(v) | VectorData::Temporal(v) => v.to_vec(),
+            VectorData::Categorical(_) | VectorData::Ordinal { .. } => {
+                self.units(data).into_iter().map(|u| self.value(u)).collect()
+            }
+        }
+    }
+
+    /// Convert a width in units to a width in axis coordinates
+    pub fn data_width(&self, width: f64) -> f64 {
+        self.value(width) - self.value(0.0)
+    }
+
+    /// The position of the center of a category's band
+    pub fn unit_category(&self, category: &str) -> f64 {
+        match self {
+            Scale::Band(categories) => match categories.iter().position(|c| c == category) {
+                Some(idx) => self.unit(idx as f64),
+                None => f64::NAN,
+            },
+            Scale::Linear { .. } | Scale::Time { .. } => f64::NAN,
+        }
+    }
+
+    /// The positions of every value in some data
+    pub fn units(&self, data: &VectorData) -> Vec<f64> {
+        match data {
+            VectorData::Quantitative(v) | VectorData::Temporal(v) => v.iter().map(|x| self.unit(*x)).collect(),
+            VectorData::Categorical(v) | VectorData::Ordinal { values: v, .. } => {
+                v.iter().map(|x| self.unit_category(x)).collect()
+            }
+        }
+    }
+
+    /// The width of the band given to each category, or zero on a linear scale
+    pub fn band(&self) -> f64 {
+        match self {
+            Scale::Band(categories) => 1.0 / categories.len() as f64,
+            Scale::Linear { .. } | Scale::Time { .. } => 0.0,
+        }
+    }
+
+    /// The width taken up by a single bar (or a group of bars) placed at the values of some data
+    pub fn bar_width(&self, data: &VectorData) -> f64 {
+        match (self, data) {
+            (Scale::Band(_), _) => 0.8 * self.band(),
+            (
+                Scale::Linear { min, max } | Scale::Time { min, max },
+                VectorData::Quantitative(v) | VectorData::Temporal(v),
+            ) => 0.8 * spacing(v) / (max - min),
+            (Scale::Linear { .. } | Scale::Time { .. }, VectorData::Categorical(_) | VectorData::Ordinal { .. }) => 0.0,
+        }
+    }
+
+    /// The position of the zero line, clamped to the axis
+    pub fn baseline(&self) -> f64 {
+        match self {
+            Scale::Linear { .. } => self.unit(0.0).clamp(0.0, 1.0),
+            Scale::Band(_) | Scale::Time { .. } => 0.0,
+        }
+    }
+
+    /// Ticks along the axis, aiming for roughly `count` ticks on a linear or time scale. A linear scale without any
+    /// width has a single tick in its middle.
+    pub fn ticks(&self, count: usize) -> Vec<Tick> {
+        match self {
+            Scale::Linear { min, max } => {
+                let step = nice_step(max - min, count);
+                if !(step > 0.0 && step.is_finite()) {
+                    return vec![Tick {
+                        position: 0.5,
+                        value: *min,
+                        label: min.to_string(),
+                    }];
+                }
+                let decimals = (-step.log10().floor()).max(0.0) as usize;
+                let first = (min / step).ceil() as i64;
+                let last = (max / step).floor() as i64;
+                (first..=last)
+                    .map(|i| {
+                        let value = i as f64 * step;
+                        Tick {
+                            position: self.unit(value),
+                            value,
+                            label: format!("{:.*}", decimals, value),
+                        }
+                    })
+                    .collect()
+            }
+            Scale::Time { min, max } => super::time::ticks(*min, *max, count, nice_step)
+                .into_iter()
+                .map(|(value, label)| Tick {
+                    position: self.unit(value),
+                    value,
+                    label,
+                })
+                .collect(),
+            Scale::Band(categories) => categories
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Tick {
+                    position: self.unit(i as f64),
+                    value: i as f64,
+                    label: c.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A step size of 1, 2 or 5 times a power of ten, giving roughly `count` steps over a range
+fn nice_step(range: f64, count: usize) -> f64 {
+    let raw = range / count.max(1) as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let residual = raw / magnitude;
+    let nice = if residual > 5.0 {
+        10.0
+    } else if residual > 2.0 {
+        5.0
+    } else if residual > 1.0 {
+        2.0
+    } else {
+        1.0
+    };
+    nice * magnitude
+}
+
+/// The extent of the data assigned to an axis
+#[derive(Default)]
+struct Domain {
+    range: Option<(f64, f64)>,
+    categories: Vec<String>,
+    pad: bool,
+    temporal: bool,
+}
+
+impl Domain {
+    fn include(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.range = match self.range {
+            None => Some((value, value)),
+            Some((min, max)) => Some((min.min(value), max.max(value))),
+        };
+    }
+
+    fn include_vector(&mut self, data: &VectorData) {
+        match data {
+            VectorData::Quantitative(v) => v.iter().for_each(|x| self.include(*x)),
+            VectorData::Categorical(v) => v.iter().for_each(|x| self.include_category(x)),
+            // Every category of ordinal data is included, in order, even when there are no values of it
+            VectorData::Ordinal { order, .. } => order.iter().for_each(|x| self.include_category(x)),
+            VectorData::Temporal(v) => {
+                self.temporal = true;
+                v.iter().for_each(|x| self.include(*x));
+            }
+        }
+    }
+
+    /// Include the zero line that bars start from, which points in time don't have
+    fn include_baseline(&mut self) {
+        if !self.temporal {
+            self.include(0.0);
+            self.pad = true;
+        }
+    }
+
+    fn include_category(&mut self, category: &str) {
+        if !self.categories.iter().any(|c| c == category) {
+            self.categories.push(category.to_string());
+        }
+    }
+
+    /// Include the values of some data, plus room for a bar around each of them
+    fn include_bars(&mut self, data: &VectorData) {
+        self.include_vector(data);
+        if let VectorData::Quantitative(v) | VectorData::Temporal(v) = data {
+            let half = spacing(v) / 2.0;
+            if let Some((min, max)) = range(v) {
+                self.include(min - half);
+                self.include(max + half);
+            }
+        }
+    }
+
+    fn into_scale(self) -> Scale {
+        if !self.categories.is_empty() {
+            return Scale::Band(self.categories);
+        }
+
+        let (mut min, mut max) = self.range.unwrap_or((0.0, 1.0));
+        if min == max {
+            min -= 1.0;
+            max += 1.0;
+        } else if self.pad {
+            // Bars start at zero, so leave the zero line at the edge of the axis
+            let padding = (max - min) * 0.05;
+            if min != 0.0 {
+                min -= padding;
+            }
+            if max != 0.0 {
+                max += padding;
+            }
+        }
+        if self.temporal {
+            Scale::Time { min, max }
+        } else {
+            Scale::Linear { min, max }
+        }
+    }
+}
+
+/// The horizontal and vertical scales shared by every plot drawn on cartesian axes
+pub struct Axes {
+    pub x: Scale,
+    pub y: Scale,
+}
+
+impl Axes {
+    /// Fit axes around all cartesian plots, or return `None` if there are none (e.g. only pie charts and treemaps).
+    /// Parallel coordinates plots have an axis per dimension instead, see `dimension_scale`.
+    pub fn fit(data: &[Plot]) -> Option<Axes> {
+        let mut x = Domain::default();
+        let mut y = Domain::default();
+        let mut cartesian = false;
+
+        for (i, plot) in data.iter().enumerate() {
+            match plot {
+                Plot::Scatter { x: xs, y: ys, .. } | Plot::Line { x: xs, y: ys, .. } => {
+                    x.include_vector(xs);
+                    y.include_vector(ys);
+                    x.pad = true;
+                    y.pad = true;
+                }
+                Plot::Bar { x: xs, y: ys, .. } => {
+                    x.include_bars(xs);
+                    y.include_vector(ys);
+                    y.include_baseline();
+                }
+                Plot::HorizontalBar { x: xs, y: ys, .. } => {
+                    x.include_vector(xs);
+                    x.include_baseline();
+                    y.include_bars(ys);
+                }
+                Plot::Box {
+                    x: xs,
+                    y: ys,
+                    options,
+                    color,
+                    name,
+                } => {
+                    // Upright boxes have their categories along x, and lying boxes are placed on the band of the layer
+                    let (categories, values) = if ys.is_some() { (&mut x, &mut y) } else { (&mut y, &mut x) };
+                    if let (VectorData::Ordinal { .. }, Some(_)) = (xs, ys) {
+                        categories.include_vector(xs);
+                    }
+                    for (category, _, stats) in boxes(i, xs, ys, options.whiskers, color, name) {
+                        categories.include_category(&category);
+                        values.include(stats.lower_whisker);
+                        values.include(stats.upper_whisker);
+                        stats.outliers.iter().for_each(|o| values.include(*o));
+                        if options.notched {
+                            values.include(stats.notch.0);
+                            values.include(stats.notch.1);
+                        }
+                    }
+                    values.pad = true;
+                }
+                Plot::Violin {
+                    x: xs,
+                    group,
+                    bandwidth,
+                    name,
+                    ..
+                } => {
+                    if let Some(groups @ VectorData::Ordinal { .. }) = group {
+                        y.include_vector(groups);
+                    }
+                    for violin in violins(xs, group, &layer_name(i, name), *bandwidth) {
+                        if let (Some((first, _)), Some((last, _))) = (violin.density.first(), violin.density.last()) {
+                            x.include(*first);
+                            x.include(*last);
+                        }
+                        y.include_category(&violin.group);
+                    }
+                    x.pad = true;
+                }
+                Plot::Histogram { x: xs, binning, .. } => {
+                    x.temporal |= matches!(xs, VectorData::Temporal(_));
+                    match histogram(xs, *binning) {
+                        Histogram::Bins(bins) => {
+                            for bin in bins {
+                                x.include(bin.start);
+                                x.include(bin.end);
+                                y.include(bin.count as f64);
+                            }
+                        }
+                        Histogram::Counts(counts) => {
+                            for (category, count) in counts {
+                                x.include_category(&category);
+                                y.include(count as f64);
+                            }
+                        }
+                    }
+                    y.include(0.0);
+                    y.pad = true;
+                }
+                Plot::Heatmap { x: xs, y: ys, z, .. } => {
+                    let MatrixData::Quantitative(rows) = z;
+                    let columns = rows.first().map_or(0, |r| r.len());
+                    for (domain, coordinates, count) in [(&mut x, xs, columns), (&mut y, ys, rows.len())] {
+                        match coordinates {
+                            Some(categories @ (VectorData::Categorical(_) | VectorData::Ordinal { .. })) => {
+                                domain.include_vector(categories)
+                            }
+                            _ => cell_edges(coordinates, count).into_iter().for_each(|e| domain.include(e)),
+                        }
+                    }
+                }
+                Plot::Contour { x: xs, y: ys, z, .. } => {
+                    let MatrixData::Quantitative(rows) = z;
+                    let columns = rows.first().map_or(0, |r| r.len());
+                    for (domain, coordinates, count) in [(&mut x, xs, columns), (&mut y, ys, rows.len())] {
+                        match coordinates {
+                            Some(categories @ (VectorData::Categorical(_) | VectorData::Ordinal { .. })) => {
+                                domain.include_vector(categories)
+                            }
+                            _ => grid_centers(coordinates, count).into_iter().for_each(|c| domain.include(c)),
+                        }
+                    }
+                }
+                Plot::Pie { .. } | Plot::Treemap { .. } | Plot::ParallelCoordinates { .. } | Plot::Surface { .. } => {
+                    continue
+                }
+            }
+            cartesian = true;
+        }
+
+        if cartesian {
+            Some(Axes {
+                x: x.into_scale(),
+                y: y.into_scale(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// The scale of the axis of a dimension of a parallel coordinates plot, spanning the values of the dimension. The
+/// categories of categorical dimensions get a band each, in the order they first appear.
+pub fn dimension_scale(data: &VectorData) -> Scale {
+    let mut domain = Domain::default();
+    domain.include_vector(data);
+    domain.into_scale()
+}
+
+/// The horizontal position of the axis of a dimension of a parallel coordinates plot, in units
+pub fn dimension_position(idx: usize, count: usize) -> f64 {
+    if count > 1 {
+        idx as f64 / (count - 1) as f64
+    } else {
+        0.5
+    }
+}
+
+/// The offset of a bar from the center of its group, given its slot among `slots` bars sharing `width`
+pub fn bar_offset(slot: usize, slots: usize, width: f64) -> f64 {
+    let slot_width = width / slots.max(1) as f64;
+    -width / 2.0 + slot_width * (slot as f64 + 0.5)
+}
+
+/// The bars of a histogram, as their left and right edges in axis coordinates along with their height. The bars of
+/// binned data span their bins, while counted categories get a bar centered on their band, like a bar chart.
+pub fn histogram_bars(data: &VectorData, binning: Binning, scale: &Scale) -> Vec<(f64, f64, f64)> {
+    match histogram(data, binning) {
+        Histogram::Bins(bins) => bins
+            .into_iter()
+            .map(|bin| (bin.start, bin.end, bin.count as f64))
+            .collect(),
+        Histogram::Counts(counts) => {
+            let half = scale.data_width(0.8 * scale.band()) / 2.0;
+            counts
+                .into_iter()
+                .map(|(category, count)| {
+                    let x = scale.value(scale.unit_category(&category));
+                    (x - half, x + half, count as f64)
+                })
+                .collect()
+        }
+    }
+}
+
+/// A violin placed on the band of its group along the y axis, in axis coordinates
+pub struct ViolinShape {
+    pub violin: Violin,
+    /// The center of the band
+    pub center: f64,
+    /// Half the height of the violin where the density peaks
+    half: f64,
+    peak: f64,
+}
+
+impl ViolinShape {
+    /// The outline of the violin, along its upper edge from left to right, then back along its lower edge
+    pub fn outline(&self) -> Vec<(f64, f64)> {
+        let upper = self.violin.density.iter().map(|(v, _)| (*v, self.span(*v).1));
+        let lower = self.violin.density.iter().rev().map(|(v, _)| (*v, self.span(*v).0));
+        upper.chain(lower).collect()
+    }
+
+    /// The bottom and top of the violin at a value
+    pub fn span(&self, value: f64) -> (f64, f64) {
+        let half = self.half * self.density_at(value) / self.peak;
+        (self.center - half, self.center + half)
+    }
+
+    /// Half the height of the box plot drawn inside the violin
+    pub fn box_half(&self) -> f64 {
+        self.half / 10.0
+    }
+
+    /// The density at a value, interpolated between the values it was estimated at
+    fn density_at(&self, value: f64) -> f64 {
+        let density = &self.violin.density;
+        match density.iter().position(|(v, _)| *v >= value) {
+            Some(0) | None => 0.0,
+            Some(i) => {
+                let ((v0, d0), (v1, d1)) = (density[i - 1], density[i]);
+                d0 + (d1 - d0) * (value - v0) / (v1 - v0)
+            }
+        }
+    }
+}
+
+/// Estimate the densities of a violin plot and place each violin on the band of its group, at most 80% of the band
+/// high. Every violin is scaled to the same height, like Plotly's default.
+pub fn violin_shapes(
+    x: &VectorData,
+    group: &Option<VectorData>,
+    name: &str,
+    bandwidth: Bandwidth,
+    scale: &Scale,
+) -> Vec<ViolinShape> {
+    let half = scale.data_width(0.8 * scale.band()) / 2.0;
+    violins(x, group, name, bandwidth)
+        .into_iter()
+        .map(|violin| {
+            let center = scale.value(scale.unit_category(&violin.group));
+            let peak = violin.density.iter().fold(0.0_f64, |peak, (_, d)| peak.max(*d));
+            ViolinShape {
+                violin,
+                center,
+                half,
+                peak: if peak > 0.0 { peak } else { 1.0 },
+            }
+        })
+        .collect()
+}
+
+/// The legend entries of a box plot: one per color group, or one for the whole layer without groups
+pub fn box_legend(idx: usize, color: &Option<VectorData>, name: &Option<String>) -> Vec<(String, Rgb)> {
+    match color {
+        Some(data @ (VectorData::Categorical(c) | VectorData::Ordinal { values: c, .. })) if c.len() > 1 => {
+            let colors = point_colors(color, c.len(), PALETTE[idx % PALETTE.len()]);
+            levels(data)
+                .into_iter()
+                .filter(|group| c.contains(group))
+                .map(|group| {
+                    let first = c.iter().position(|g| *g == group).unwrap_or(0);
+                    (group, colors[first])
+                })
+                .collect()
+        }
+        _ => vec![(layer_name(idx, name), layer_color(idx, color))],
+    }
+}
+
+/// The boxes of a box plot, as the category each box is placed on, its color group and its statistics. A lone x
+/// channel makes up a single box named after the layer, while a y channel is split by the categories in x.
+fn boxes(
+    idx: usize,
+    x: &VectorData,
+    y: &Option<VectorData>,
+    whiskers: Whiskers,
+    color: &Option<VectorData>,
+    name: &Option<String>,
+) -> Vec<(String, Option<String>, BoxStats)> {
+    let (values, categories) = match (x, y) {
+        (VectorData::Quantitative(v), None) => (v, None),
+        (VectorData::Categorical(c) | VectorData::Ordinal { values: c, .. }, Some(VectorData::Quantitative(v))) => {
+            (v, Some(c))
+        }
+        _ => return Vec::new(),
+    };
+    let groups = match color {
+        Some(VectorData::Categorical(g) | VectorData::Ordinal { values: g, .. }) if g.len() > 1 => Some(g),
+        _ => None,
+    };
+    let layer = layer_name(idx, name);
+
+    let mut grouped: Vec<(String, Option<String>, Vec<f64>)> = Vec::new();
+    for (i, v) in values.iter().enumerate() {
+        let category = categories.map_or(&layer, |c| &c[i]);
+        let group = groups.map(|g| &g[i]);
+        match grouped.iter_mut().find(|(c, g, _)| c == category && g.as_ref() == group) {
+            Some((_, _, values)) => values.push(*v),
+            None => grouped.push((category.to_string(), group.cloned(), vec![*v])),
+        }
+    }
+
+    grouped
+        .into_iter()
+        .filter_map(|(category, group, values)| Some((category, group, box_stats(&values, whiskers)?)))
+        .collect()
+}
+
+/// A box of a box plot, placed in its slot of the band of its category, in axis coordinates
+pub struct BoxShape {
+    pub stats: BoxStats,
+    /// The legend entry of the box, which is its color group or else the layer
+    pub label: String,
+    pub color: Rgb,
+    /// Whether the box stands upright, with its values along the y axis
+    pub vertical: bool,
+    center: f64,
+    half: f64,
+    options: BoxOptions,
+}
+
+impl BoxShape {
+    /// The point at a value, offset from the center of the box across it
+    pub fn point(&self, value: f64, offset: f64) -> (f64, f64) {
+        if self.vertical {
+            (self.center + offset, value)
+        } else {
+            (value, self.center + offset)
+        }
+    }
+
+    /// The outline of the box, narrowing to half its width at the median when notched
+    pub fn outline(&self) -> Vec<(f64, f64)> {
+        let BoxStats { q1, median, q3, .. } = self.stats;
+        let h = self.half;
+        if self.options.notched {
+            let (low, high) = (self.stats.notch.0.max(q1), self.stats.notch.1.min(q3));
+            vec![
+                self.point(q1, -h),
+                self.point(low, -h),
+                self.point(median, -h / 2.0),
+                self.point(high, -h),
+                self.point(q3, -h),
+                self.point(q3, h),
+                self.point(high, h),
+                self.point(median, h / 2.0),
+                self.point(low, h),
+                self.point(q1, h),
+            ]
+        } else {
+            vec![self.point(q1, -h), self.point(q3, -h), self.point(q3, h), self.point(q1, h)]
+        }
+    }
+
+    /// The line across the box at the median
+    pub fn median(&self) -> [(f64, f64); 2] {
+        let h = if self.options.notched { self.half / 2.0 } else { self.half };
+        [self.point(self.stats.median, -h), self.point(self.stats.median, h)]
+    }
+
+    /// The lines from the box out to the whiskers, followed by the caps at the ends of the whiskers
+    pub fn whiskers(&self) -> Vec<[(f64, f64); 2]> {
+        let BoxStats { lower_whisker, q1, q3, upper_whisker, .. } = self.stats;
+        let cap = self.half / 2.0;
+        vec![
+            [self.point(lower_whisker, 0.0), self.point(q1, 0.0)],
+            [self.point(q3, 0.0), self.point(upper_whisker, 0.0)],
+            [self.point(lower_whisker, -cap), self.point(lower_whisker, cap)],
+            [self.point(upper_whisker, -cap), self.point(upper_whisker, cap)],
+        ]
+    }
+
+    /// The dashed line across the box at the mean, if it's shown
+    pub fn mean(&self) -> Option<[(f64, f64); 2]> {
+        if self.options.mean {
+            Some([self.point(self.stats.mean, -self.half), self.point(self.stats.mean, self.half)])
+        } else {
+            None
+        }
+    }
+
+    pub fn outliers(&self) -> Vec<(f64, f64)> {
+        self.stats.outliers.iter().map(|o| self.point(*o, 0.0)).collect()
+    }
+}
+
+/// Place the boxes of a box plot on the axes. Color groups share the band of each category side by side, like the
+/// bars of a grouped bar chart.
+pub fn box_shapes(
+    idx: usize,
+    x: &VectorData,
+    y: &Option<VectorData>,
+    options: BoxOptions,
+    color: &Option<VectorData>,
+    name: &Option<String>,
+    axes: &Axes,
+) -> Vec<BoxShape> {
+    let scale = if y.is_some() { &axes.x } else { &axes.y };
+    let legend = box_legend(idx, color, name);
+    let width = 0.8 * scale.band();
+    let slot_width = scale.data_width(width / legend.len() as f64);
+
+    boxes(idx, x, y, options.whiskers, color, name)
+        .into_iter()
+        .map(|(category, group, stats)| {
+            let slot = group
+                .as_ref()
+                .and_then(|g| legend.iter().position(|(l, _)| l == g))
+                .unwrap_or(0);
+            let center = scale.value(scale.unit_category(&category));
+            BoxShape {
+                stats,
+                label: legend[slot].0.clone(),
+                color: legend[slot].1,
+                vertical: y.is_some(),
+                center: center + scale.data_width(bar_offset(slot, legend.len(), width)),
+                half: slot_width * 0.4,
+                options,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contour_lines, ColorMap};
+    use crate::data::{MatrixData, VectorData};
+    use crate::heatmap::{ContourOptions, HeatmapOptions};
+
+    fn limits(options: HeatmapOptions) -> (f64, f64) {
+        let map = ColorMap::heatmap(&MatrixData::Quantitative(vec![vec![0.0, 1.0], vec![2.0, 4.0]]), &options);
+        (map.min, map.max)
+    }
+
+    #[test]
+    fn heatmap_midpoint() {
+        assert_eq!(limits(HeatmapOptions::default()), (0.0, 4.0));
+        assert_eq!(limits(HeatmapOptions { midpoint: Some(1.0), ..Default::default() }), (-2.0, 4.0));
+        assert_eq!(limits(HeatmapOptions { midpoint: Some(5.0), ..Default::default() }), (0.0, 10.0));
+        // Limits that are set are kept as they are
+        let options = HeatmapOptions { midpoint: Some(1.0), zmax: Some(3.0), ..Default::default() };
+        assert_eq!(limits(options), (-2.0, 3.0));
+    }
+
+    #[test]
+    fn contour_lines_cross_the_edges_between_points() {
+        // A single level at 2, halfway between the ends of the values
+        let options = ContourOptions { levels: 1, ..Default::default() };
+        let z = MatrixData::Quantitative(vec![vec![0.0, 4.0], vec![0.0, 4.0]]);
+        let x = Some(VectorData::Quantitative(vec![10.0, 20.0]));
+        let lines = contour_lines(&x, &None, &z, &options);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].level, 2.0);
+        assert_eq!(lines[0].segments, vec![[(15.0, 0.0), (15.0, 1.0)]]);
+
+        // Edges of cells are turned into the points between them
+        let edges = Some(VectorData::Quantitative(vec![0.0, 2.0, 4.0]));
+        assert_eq!(contour_lines(&edges, &None, &z, &options)[0].segments, vec![[(2.0, 0.0), (2.0, 1.0)]]);
+
+        // A cell with a missing corner has no lines through it
+        let z = MatrixData::Quantitative(vec![vec![0.0, 4.0], vec![f64::NAN, 4.0]]);
+        assert!(contour_lines(&x, &None, &z, &options).is_empty());
+    }
+
+    #[test]
+    fn contour_saddles() {
+        // The corners at 8 are across from each other, and the level at 4 crosses every edge. The value at the center
+        // tells whether they're joined, cutting off the other two corners, or cut off themselves.
+        let options = ContourOptions { levels: 1, ..Default::default() };
+        let segments = |rows: Vec<Vec<f64>>| contour_lines(&None, &None, &MatrixData::Quantitative(rows), &options);
+
+        let joined = segments(vec![vec![8.0, 0.0], vec![2.0, 8.0]]);
+        let expected = [[(0.5, 0.0), (1.0, 0.5)], [(1.0 - 4.0 / 6.0, 1.0), (0.0, 1.0 - 2.0 / 6.0)]];
+        assert_eq!(joined[0].segments, expected);
+
+        let separate = segments(vec![vec![8.0, 0.0], vec![0.0, 6.0]]);
+        let expected = [[(0.0, 0.5), (0.5, 0.0)], [(1.0, 4.0 / 6.0), (1.0 - 2.0 / 6.0, 1.0)]];
+        assert_eq!(separate[0].segments, expected);
+    }
+}
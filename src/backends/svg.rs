@@ -0,0 +1,923 @@
+use super::scale::{
+    bar_offset, box_legend, box_shapes, contour_lines, dimension_position, dimension_scale, flat, heatmap_cells,
+    histogram_bars, layer_color, layer_name, line_runs, pie_slices, point_colors, point_sizes, range, violin_shapes,
+    Axes, BoxShape, ColorMap, ContourLine, HeatmapCell, Rgb, Scale, ViolinShape, PALETTE,
+};
+use super::treemap::{tiles, Rect};
+use super::Renderable;
+use crate::data::{Missing, VectorData};
+use crate::error::ConspireError;
+use crate::stats::{Binning, ViolinInner};
+use crate::Plot;
+
+use std::f64::consts::PI;
+use std::fmt::{self, Write};
+use std::io;
+
+type Result<T> = std::result::Result<T, ConspireError>;
+
+const MARGIN_LEFT: f64 = 70.0;
+const MARGIN_RIGHT: f64 = 160.0;
+const MARGIN_TOP: f64 = 20.0;
+const MARGIN_BOTTOM: f64 = 50.0;
+const FONT: &str = r#"font-family="sans-serif" font-size="12""#;
+
+/// Renders plots into a self-contained SVG document, without relying on any external libraries
+pub struct Svg {
+    width: f64,
+    height: f64,
+}
+
+impl Default for Svg {
+    fn default() -> Self {
+        Self {
+            width: 800.0,
+            height: 500.0,
+        }
+    }
+}
+
+/// The part of the canvas plots are drawn in, in pixels
+#[derive(Clone, Copy)]
+struct Area {
+    left: f64,
+    top: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Area {
+    fn x(&self, unit: f64) -> f64 {
+        self.left + unit * self.width
+    }
+
+    fn y(&self, unit: f64) -> f64 {
+        self.top + (1.0 - unit) * self.height
+    }
+}
+
+impl Svg {
+    pub(crate) fn document(&self, data: &[Plot]) -> Result<String> {
+        flat("svg", data, |p| !matches!(p, Plot::Surface { .. }))?;
+        let mut doc = String::new();
+        let area = Area {
+            left: MARGIN_LEFT,
+            top: MARGIN_TOP,
+            width: self.width - MARGIN_LEFT - MARGIN_RIGHT,
+            height: self.height - MARGIN_TOP - MARGIN_BOTTOM,
+        };
+
+        writeln!(
+            doc,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.width,
+            h = self.height
+        )?;
+        writeln!(doc, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+
+        if let Some(axes) = Axes::fit(data) {
+            axis_lines(&mut doc, &axes, area)?;
+
+            let bars = data.iter().filter(|p| matches!(p, Plot::Bar { .. })).count();
+            let horizontal_bars = data
+                .iter()
+                .filter(|p| matches!(p, Plot::HorizontalBar { .. }))
+                .count();
+            let (mut bar, mut horizontal_bar) = (0, 0);
+
+            for (i, plot) in data.iter().enumerate() {
+                match plot {
+                    Plot::Scatter { x, y, color, size, .. } => {
+                        scatter(&mut doc, &axes, area, i, x, y, color, size)?
+                    }
+                    Plot::Line { x, y, missing, color, size, .. } => {
+                        line(&mut doc, &axes, area, i, x, y, *missing, color, size)?
+                    }
+                    Plot::Bar { x, y, color, .. } => {
+                        let slot = (bar, bars);
+                        bar += 1;
+                        bars_vertical(&mut doc, &axes, area, i, slot, x, y, color)?
+                    }
+                    Plot::HorizontalBar { x, y, color, .. } => {
+                        let slot = (horizontal_bar, horizontal_bars);
+                        horizontal_bar += 1;
+                        bars_horizontal(&mut doc, &axes, area, i, slot, x, y, color)?
+                    }
+                    Plot::Box {
+                        x,
+                        y,
+                        options,
+                        color,
+                        name,
+                    } => boxplot(&mut doc, &axes, area, &box_shapes(i, x, y, *options, color, name, &axes))?,
+                    Plot::Histogram { x, binning, color, .. } => {
+                        histogram(&mut doc, &axes, area, i, x, *binning, color)?
+                    }
+                    Plot::Violin {
+                        x,
+                        group,
+                        bandwidth,
+                        inner,
+                        color,
+                        name,
+                    } => {
+                        let shapes = violin_shapes(x, group, &layer_name(i, name), *bandwidth, &axes.y);
+                        violin(&mut doc, &axes, area, i, &shapes, *inner, color)?
+                    }
+                    Plot::Heatmap { x, y, z, options, .. } => {
+                        let cells = heatmap_cells(x, y, z, options);
+                        heatmap(&mut doc, &axes, area, &cells, &ColorMap::heatmap(z, options))?
+                    }
+                    Plot::Contour { x, y, z, options, .. } => {
+                        contour(&mut doc, &axes, area, &contour_lines(x, y, z, options))?
+                    }
+                    Plot::Pie { .. } | Plot::Treemap { .. } | Plot::ParallelCoordinates { .. } => {}
+                    Plot::Surface { .. } => unreachable!("surfaces are rejected before drawing"),
+                }
+            }
+        }
+
+        // Pie charts, treemaps and parallel coordinates don't share the cartesian axes, so they're drawn side by side
+        // across the plot area instead
+        let panels: Vec<(usize, &Plot)> = data
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| matches!(p, Plot::Pie { .. } | Plot::Treemap { .. } | Plot::ParallelCoordinates { .. }))
+            .collect();
+        let slot_width = area.width / panels.len().max(1) as f64;
+        for (i, (idx, plot)) in panels.iter().enumerate() {
+            let left = area.left + slot_width * i as f64;
+            match plot {
+                Plot::Pie { x, color, .. } => {
+                    let cx = left + slot_width / 2.0;
+                    let cy = area.top + area.height / 2.0;
+                    let radius = (slot_width.min(area.height) / 2.0) * 0.9;
+                    pie(&mut doc, cx, cy, radius, x, color)?;
+                }
+                Plot::Treemap {
+                    label,
+                    parent,
+                    value,
+                    color,
+                    ..
+                } => {
+                    let rect = Rect {
+                        x0: left + 5.0,
+                        y0: area.top,
+                        x1: left + slot_width - 5.0,
+                        y1: area.top + area.height,
+                    };
+                    treemap(&mut doc, rect, label, parent, value, color)?;
+                }
+                Plot::ParallelCoordinates { dimensions, color, .. } => {
+                    let panel = Area {
+                        left: left + 20.0,
+                        top: area.top,
+                        width: slot_width - 40.0,
+                        height: area.height,
+                    };
+                    parallel_coordinates(&mut doc, panel, *idx, dimensions, color)?;
+                }
+                _ => {}
+            }
+        }
+
+        legend(&mut doc, data, self.width - MARGIN_RIGHT + 20.0, MARGIN_TOP)?;
+
+        writeln!(doc, "</svg>")?;
+        Ok(doc)
+    }
+}
+
+impl Renderable for Svg {
+    fn render(&self, data: &[Plot], display: bool) -> Result<()> {
+        use super::util::{open_in_viewer, write_to_file};
+        use std::path::Path;
+
+        let path = Path::new("render.svg");
+        write_to_file(path, &self.document(data)?)?;
+
+        if display {
+            open_in_viewer(path)?;
+        }
+        Ok(())
+    }
+
+    fn write(&self, data: &[Plot], writer: &mut dyn io::Write) -> Result<()> {
+        writer.write_all(self.document(data)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Escape text for use inside an SVG document
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn axis_lines(doc: &mut String, axes: &Axes, area: Area) -> fmt::Result {
+    let (left, right) = (area.x(0.0), area.x(1.0));
+    let (top, bottom) = (area.y(1.0), area.y(0.0));
+
+    for tick in axes.x.ticks(8) {
+        let x = area.x(tick.position);
+        writeln!(
+            doc,
+            r##"<line x1="{x:.2}" y1="{top:.2}" x2="{x:.2}" y2="{bottom:.2}" stroke="#eeeeee"/>"##,
+            x = x,
+            top = top,
+            bottom = bottom
+        )?;
+        writeln!(
+            doc,
+            r#"<line x1="{x:.2}" y1="{y:.2}" x2="{x:.2}" y2="{y2:.2}" stroke="black"/>"#,
+            x = x,
+            y = bottom,
+            y2 = bottom + 5.0
+        )?;
+        writeln!(
+            doc,
+            r#"<text x="{:.2}" y="{:.2}" text-anchor="middle" {}>{}</text>"#,
+            x,
+            bottom + 18.0,
+            FONT,
+            escape(&tick.label)
+        )?;
+    }
+
+    for tick in axes.y.ticks(6) {
+        let y = area.y(tick.position);
+        writeln!(
+            doc,
+            r##"<line x1="{left:.2}" y1="{y:.2}" x2="{right:.2}" y2="{y:.2}" stroke="#eeeeee"/>"##,
+            left = left,
+            right = right,
+            y = y
+        )?;
+        writeln!(
+            doc,
+            r#"<line x1="{x:.2}" y1="{y:.2}" x2="{x2:.2}" y2="{y:.2}" stroke="black"/>"#,
+            x = left - 5.0,
+            x2 = left,
+            y = y
+        )?;
+        writeln!(
+            doc,
+            r#"<text x="{:.2}" y="{:.2}" text-anchor="end" dominant-baseline="middle" {}>{}</text>"#,
+            left - 8.0,
+            y,
+            FONT,
+            escape(&tick.label)
+        )?;
+    }
+
+    writeln!(
+        doc,
+        r#"<polyline points="{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}" fill="none" stroke="black"/>"#,
+        left, top, left, bottom, right, bottom
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn scatter(
+    doc: &mut String,
+    axes: &Axes,
+    area: Area,
+    idx: usize,
+    x: &VectorData,
+    y: &VectorData,
+    color: &Option<VectorData>,
+    size: &Option<VectorData>,
+) -> fmt::Result {
+    let xs = axes.x.units(x);
+    let ys = axes.y.units(y);
+    let colors = point_colors(color, xs.len(), PALETTE[idx % PALETTE.len()]);
+    let sizes = point_sizes(size, xs.len(), 6.0);
+
+    for (i, (px, py)) in xs.iter().zip(ys.iter()).enumerate() {
+        if !px.is_finite() || !py.is_finite() {
+            continue;
+        }
+        writeln!(
+            doc,
+            r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}"/>"#,
+            area.x(*px),
+            area.y(*py),
+            sizes[i] / 2.0,
+            colors[i].hex()
+        )?;
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn line(
+    doc: &mut String,
+    axes: &Axes,
+    area: Area,
+    idx: usize,
+    x: &VectorData,
+    y: &VectorData,
+    missing: Missing,
+    color: &Option<VectorData>,
+    size: &Option<VectorData>,
+) -> fmt::Result {
+    let width = point_sizes(size, 1, 2.0)[0];
+
+    let (xs, ys) = (axes.x.units(x), axes.y.units(y));
+
+    for run in line_runs(x, y, missing) {
+        let points: Vec<String> = run
+            .iter()
+            .map(|i| format!("{:.2},{:.2}", area.x(xs[*i]), area.y(ys[*i])))
+            .collect();
+        writeln!(
+            doc,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{:.2}"/>"#,
+            points.join(" "),
+            layer_color(idx, color).hex(),
+            width
+        )?;
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn bars_vertical(
+    doc: &mut String,
+    axes: &Axes,
+    area: Area,
+    idx: usize,
+    (slot, slots): (usize, usize),
+    x: &VectorData,
+    y: &VectorData,
+    color: &Option<VectorData>,
+) -> fmt::Result {
+    let width = axes.x.bar_width(x);
+    let offset = bar_offset(slot, slots, width);
+    let bar_width = width / slots as f64;
+    let baseline = axes.y.baseline();
+    let xs = axes.x.units(x);
+    let ys = axes.y.units(y);
+    let colors = point_colors(color, xs.len(), PALETTE[idx % PALETTE.len()]);
+
+    for (i, (px, py)) in xs.iter().zip(ys.iter()).enumerate() {
+        if !px.is_finite() || !py.is_finite() {
+            continue;
+        }
+        let left = area.x(px + offset - bar_width / 2.0);
+        let (top, bottom) = (area.y(py.max(baseline)), area.y(py.min(baseline)));
+        writeln!(
+            doc,
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}"/>"#,
+            left,
+            top,
+            bar_width * area.width,
+            bottom - top,
+            colors[i].hex()
+        )?;
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn bars_horizontal(
+    doc: &mut String,
+    axes: &Axes,
+    area: Area,
+    idx: usize,
+    (slot, slots): (usize, usize),
+    x: &VectorData,
+    y: &VectorData,
+    color: &Option<VectorData>,
+) -> fmt::Result {
+    let height = axes.y.bar_width(y);
+    let offset = bar_offset(slot, slots, height);
+    let bar_height = height / slots as f64;
+    let baseline = axes.x.baseline();
+    let xs = axes.x.units(x);
+    let ys = axes.y.units(y);
+    let colors = point_colors(color, xs.len(), PALETTE[idx % PALETTE.len()]);
+
+    for (i, (px, py)) in xs.iter().zip(ys.iter()).enumerate() {
+        if !px.is_finite() || !py.is_finite() {
+            continue;
+        }
+        let (left, right) = (area.x(px.min(baseline)), area.x(px.max(baseline)));
+        let top = area.y(py + offset + bar_height / 2.0);
+        writeln!(
+            doc,
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}"/>"#,
+            left,
+            top,
+            right - left,
+            bar_height * area.height,
+            colors[i].hex()
+        )?;
+    }
+    Ok(())
+}
+
+fn histogram(
+    doc: &mut String,
+    axes: &Axes,
+    area: Area,
+    idx: usize,
+    x: &VectorData,
+    binning: Binning,
+    color: &Option<VectorData>,
+) -> fmt::Result {
+    let fill = layer_color(idx, color).hex();
+    let baseline = axes.y.baseline();
+    for (start, end, count) in histogram_bars(x, binning, &axes.x) {
+        let (left, right) = (area.x(axes.x.unit(start)), area.x(axes.x.unit(end)));
+        let (top, bottom) = (area.y(axes.y.unit(count)), area.y(baseline));
+        writeln!(
+            doc,
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}" stroke="white" stroke-width="0.5"/>"#,
+            left,
+            top,
+            right - left,
+            bottom - top,
+            fill
+        )?;
+    }
+    Ok(())
+}
+
+fn boxplot(doc: &mut String, axes: &Axes, area: Area, shapes: &[BoxShape]) -> fmt::Result {
+    let px = |(x, y): (f64, f64)| (area.x(axes.x.unit(x)), area.y(axes.y.unit(y)));
+    let line = |doc: &mut String, [from, to]: [(f64, f64); 2], stroke: &str, style: &str| {
+        let ((x1, y1), (x2, y2)) = (px(from), px(to));
+        writeln!(
+            doc,
+            r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="{}"{}/>"#,
+            x1, y1, x2, y2, stroke, style
+        )
+    };
+
+    for shape in shapes {
+        let stroke = shape.color.hex();
+        let points: Vec<String> = shape
+            .outline()
+            .into_iter()
+            .map(|p| {
+                let (x, y) = px(p);
+                format!("{:.2},{:.2}", x, y)
+            })
+            .collect();
+        writeln!(
+            doc,
+            r#"<polygon points="{}" fill="{}" fill-opacity="0.5" stroke="{}"/>"#,
+            points.join(" "),
+            stroke,
+            stroke
+        )?;
+        line(doc, shape.median(), &stroke, r#" stroke-width="2""#)?;
+        for whisker in shape.whiskers() {
+            line(doc, whisker, &stroke, "")?;
+        }
+        if let Some(mean) = shape.mean() {
+            line(doc, mean, &stroke, r#" stroke-dasharray="4 3""#)?;
+        }
+        for outlier in shape.outliers() {
+            let (cx, cy) = px(outlier);
+            writeln!(
+                doc,
+                r#"<circle cx="{:.2}" cy="{:.2}" r="3" fill="none" stroke="{}"/>"#,
+                cx, cy, stroke
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn violin(
+    doc: &mut String,
+    axes: &Axes,
+    area: Area,
+    idx: usize,
+    shapes: &[ViolinShape],
+    inner: ViolinInner,
+    color: &Option<VectorData>,
+) -> fmt::Result {
+    let stroke = layer_color(idx, color).hex();
+    let px = |v: f64| area.x(axes.x.unit(v));
+    let py = |v: f64| area.y(axes.y.unit(v));
+
+    for shape in shapes {
+        let points: Vec<String> = shape
+            .outline()
+            .into_iter()
+            .map(|(x, y)| format!("{:.2},{:.2}", px(x), py(y)))
+            .collect();
+        writeln!(
+            doc,
+            r#"<polygon points="{}" fill="{}" fill-opacity="0.5" stroke="{}"/>"#,
+            points.join(" "),
+            stroke,
+            stroke
+        )?;
+
+        let stats = &shape.violin.stats;
+        match inner {
+            ViolinInner::Box => {
+                let (bottom, top) = (py(shape.center - shape.box_half()), py(shape.center + shape.box_half()));
+                writeln!(
+                    doc,
+                    r#"<line x1="{:.2}" y1="{y:.2}" x2="{:.2}" y2="{y:.2}" stroke="{}"/>"#,
+                    px(stats.lower_whisker),
+                    px(stats.upper_whisker),
+                    stroke,
+                    y = py(shape.center)
+                )?;
+                writeln!(
+                    doc,
+                    r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}"/>"#,
+                    px(stats.q1),
+                    top,
+                    px(stats.q3) - px(stats.q1),
+                    bottom - top,
+                    stroke
+                )?;
+                writeln!(
+                    doc,
+                    r#"<line x1="{x:.2}" y1="{:.2}" x2="{x:.2}" y2="{:.2}" stroke="white" stroke-width="2"/>"#,
+                    top,
+                    bottom,
+                    x = px(stats.median)
+                )?;
+            }
+            ViolinInner::Quartiles => {
+                let dashed = r#" stroke-dasharray="4 3""#;
+                for (quartile, dash) in &[(stats.q1, dashed), (stats.median, ""), (stats.q3, dashed)] {
+                    let (bottom, top) = shape.span(*quartile);
+                    writeln!(
+                        doc,
+                        r#"<line x1="{x:.2}" y1="{:.2}" x2="{x:.2}" y2="{:.2}" stroke="{}"{}/>"#,
+                        py(bottom),
+                        py(top),
+                        stroke,
+                        dash,
+                        x = px(*quartile)
+                    )?;
+                }
+            }
+            ViolinInner::None => {}
+        }
+    }
+    Ok(())
+}
+
+fn heatmap(doc: &mut String, axes: &Axes, area: Area, cells: &[HeatmapCell], map: &ColorMap) -> fmt::Result {
+    for cell in cells {
+        let (left, right) = (area.x(axes.x.unit(cell.left)), area.x(axes.x.unit(cell.right)));
+        let (top, bottom) = (area.y(axes.y.unit(cell.top)), area.y(axes.y.unit(cell.bottom)));
+        let color = map.color(cell.value);
+        writeln!(
+            doc,
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}"/>"#,
+            left.min(right),
+            top.min(bottom),
+            (right - left).abs(),
+            (bottom - top).abs(),
+            color.hex()
+        )?;
+        if let Some(text) = &cell.text {
+            writeln!(
+                doc,
+                r#"<text x="{:.2}" y="{:.2}" text-anchor="middle" dominant-baseline="middle" fill="{}" {}>{}</text>"#,
+                (left + right) / 2.0,
+                (top + bottom) / 2.0,
+                color.contrast().hex(),
+                FONT,
+                escape(text)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn contour(doc: &mut String, axes: &Axes, area: Area, lines: &[ContourLine]) -> fmt::Result {
+    for line in lines {
+        let path: Vec<String> = line
+            .segments
+            .iter()
+            .map(|[(x0, y0), (x1, y1)]| {
+                format!(
+                    "M{:.2},{:.2}L{:.2},{:.2}",
+                    area.x(axes.x.unit(*x0)),
+                    area.y(axes.y.unit(*y0)),
+                    area.x(axes.x.unit(*x1)),
+                    area.y(axes.y.unit(*y1))
+                )
+            })
+            .collect();
+        writeln!(
+            doc,
+            r#"<path d="{}" fill="none" stroke="{}" stroke-width="1.5"/>"#,
+            path.join(""),
+            line.color.hex()
+        )?;
+    }
+    Ok(())
+}
+
+fn pie(doc: &mut String, cx: f64, cy: f64, radius: f64, x: &VectorData, color: &Option<VectorData>) -> fmt::Result {
+    let slices = pie_slices(x);
+    let total: f64 = slices.iter().map(|(_, v)| v).sum();
+    let colors = slice_colors(color, slices.len());
+
+    if slices.len() == 1 {
+        return writeln!(
+            doc,
+            r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}"/>"#,
+            cx,
+            cy,
+            radius,
+            colors[0].hex()
+        );
+    }
+
+    // Slices start at twelve o'clock and go clockwise, as in Plotly
+    let point = |angle: f64| (cx + radius * angle.sin(), cy - radius * angle.cos());
+    let mut angle = 0.0;
+    for (i, (_, value)) in slices.iter().enumerate() {
+        let sweep = value / total * 2.0 * PI;
+        let (x0, y0) = point(angle);
+        let (x1, y1) = point(angle + sweep);
+        writeln!(
+            doc,
+            r#"<path d="M {:.2} {:.2} L {:.2} {:.2} A {r:.2} {r:.2} 0 {} 1 {:.2} {:.2} Z" fill="{}" stroke="white"/>"#,
+            cx,
+            cy,
+            x0,
+            y0,
+            if sweep > PI { 1 } else { 0 },
+            x1,
+            y1,
+            colors[i].hex(),
+            r = radius
+        )?;
+        angle += sweep;
+    }
+    Ok(())
+}
+
+fn treemap(
+    doc: &mut String,
+    rect: Rect,
+    label: &VectorData,
+    parent: &VectorData,
+    value: &Option<VectorData>,
+    color: &Option<VectorData>,
+) -> fmt::Result {
+    for tile in tiles(label, parent, value, color, rect) {
+        let Rect { x0, y0, x1, y1 } = tile.rect;
+        writeln!(
+            doc,
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}" stroke="white"/>"#,
+            x0,
+            y0,
+            x1 - x0,
+            y1 - y0,
+            tile.color.hex()
+        )?;
+        // Labels are only written where they fit, guessing at the width of the text
+        if tile.leaf && tile.label.chars().count() as f64 * 7.0 + 8.0 < x1 - x0 && y1 - y0 > 18.0 {
+            writeln!(
+                doc,
+                r#"<text x="{:.2}" y="{:.2}" text-anchor="middle" dominant-baseline="middle" fill="{}" {}>{}</text>"#,
+                (x0 + x1) / 2.0,
+                (y0 + y1) / 2.0,
+                tile.text_color().hex(),
+                FONT,
+                escape(&tile.label)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn parallel_coordinates(
+    doc: &mut String,
+    area: Area,
+    idx: usize,
+    dimensions: &[(String, VectorData)],
+    color: &Option<VectorData>,
+) -> fmt::Result {
+    let scales: Vec<Scale> = dimensions.iter().map(|(_, data)| dimension_scale(data)).collect();
+    let units: Vec<Vec<f64>> = dimensions.iter().zip(&scales).map(|((_, data), s)| s.units(data)).collect();
+    let len = units.first().map_or(0, |u| u.len());
+    let colors = point_colors(color, len, PALETTE[idx % PALETTE.len()]);
+    let x = |d: usize| area.x(dimension_position(d, dimensions.len()));
+
+    for (i, c) in colors.iter().enumerate() {
+        let points: Vec<String> = units
+            .iter()
+            .enumerate()
+            .filter(|(_, u)| u[i].is_finite())
+            .map(|(d, u)| format!("{:.2},{:.2}", x(d), area.y(u[i])))
+            .collect();
+        writeln!(
+            doc,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-opacity="0.7"/>"#,
+            points.join(" "),
+            c.hex()
+        )?;
+    }
+
+    for (d, ((name, _), scale)) in dimensions.iter().zip(&scales).enumerate() {
+        writeln!(
+            doc,
+            r#"<line x1="{x:.2}" y1="{:.2}" x2="{x:.2}" y2="{:.2}" stroke="black"/>"#,
+            area.y(1.0),
+            area.y(0.0),
+            x = x(d)
+        )?;
+        for tick in scale.ticks(5) {
+            writeln!(
+                doc,
+                r#"<text x="{:.2}" y="{:.2}" text-anchor="end" dominant-baseline="middle" {}>{}</text>"#,
+                x(d) - 4.0,
+                area.y(tick.position),
+                FONT,
+                escape(&tick.label)
+            )?;
+        }
+        writeln!(
+            doc,
+            r#"<text x="{:.2}" y="{:.2}" text-anchor="middle" {}>{}</text>"#,
+            x(d),
+            area.y(0.0) + 18.0,
+            FONT,
+            escape(name)
+        )?;
+    }
+    Ok(())
+}
+
+/// Colors of pie slices, which default to a palette color per slice rather than one color for the whole layer
+fn slice_colors(color: &Option<VectorData>, len: usize) -> Vec<Rgb> {
+    match color {
+        Some(_) => point_colors(color, len, PALETTE[0]),
+        None => (0..len).map(|i| PALETTE[i % PALETTE.len()]).collect(),
+    }
+}
+
+fn legend(doc: &mut String, data: &[Plot], left: f64, top: f64) -> fmt::Result {
+    let mut entries: Vec<(String, Rgb)> = Vec::new();
+    let mut colorbar_map = None;
+
+    for (i, plot) in data.iter().enumerate() {
+        match plot {
+            Plot::Scatter { color, name, .. }
+            | Plot::Line { color, name, .. }
+            | Plot::Bar { color, name, .. }
+            | Plot::HorizontalBar { color, name, .. }
+            | Plot::Violin { color, name, .. }
+            | Plot::Histogram { color, name, .. } => entries.push((layer_name(i, name), layer_color(i, color))),
+            Plot::Box { color, name, .. } => entries.extend(box_legend(i, color, name)),
+            Plot::Pie { x, color, .. } => {
+                let slices = pie_slices(x);
+                let colors = slice_colors(color, slices.len());
+                entries.extend(slices.into_iter().map(|(label, _)| label).zip(colors));
+            }
+            Plot::Heatmap { z, options, .. } => colorbar_map = Some((i, ColorMap::heatmap(z, options))),
+            Plot::Contour { z, options, .. } => colorbar_map = Some((i, ColorMap::grid(z, &options.colorscale))),
+            Plot::ParallelCoordinates {
+                color: Some(VectorData::Quantitative(v)),
+                ..
+            } if v.len() > 1 => colorbar_map = range(v).map(|(min, max)| (i, ColorMap::sequential(min, max))),
+            Plot::Treemap { .. } | Plot::ParallelCoordinates { .. } | Plot::Surface { .. } => {}
+        }
+    }
+
+    for (i, (label, color)) in entries.iter().enumerate() {
+        let y = top + i as f64 * 20.0;
+        writeln!(
+            doc,
+            r#"<rect x="{:.2}" y="{:.2}" width="12" height="12" fill="{}"/>"#,
+            left,
+            y,
+            color.hex()
+        )?;
+        writeln!(
+            doc,
+            r#"<text x="{:.2}" y="{:.2}" dominant-baseline="middle" {}>{}</text>"#,
+            left + 18.0,
+            y + 6.0,
+            FONT,
+            escape(label)
+        )?;
+    }
+
+    if let Some((i, map)) = colorbar_map {
+        colorbar(doc, left, top + entries.len() as f64 * 20.0, i, &map)?;
+    }
+    Ok(())
+}
+
+/// The color scale of layer `idx`, whose gradient is named after the layer so that it doesn't clash with the gradients
+/// of other plots on the same page
+fn colorbar(doc: &mut String, left: f64, top: f64, idx: usize, map: &ColorMap) -> fmt::Result {
+    let height = 200.0;
+    let scale = Scale::Linear {
+        min: map.min,
+        max: map.max,
+    };
+
+    writeln!(
+        doc,
+        r#"<defs><linearGradient id="colorbar-{}" x1="0" y1="1" x2="0" y2="0">"#,
+        idx
+    )?;
+    for (t, color) in &map.stops {
+        writeln!(doc, r#"<stop offset="{:.3}" stop-color="{}"/>"#, t, color.hex())?;
+    }
+    writeln!(doc, "</linearGradient></defs>")?;
+    writeln!(
+        doc,
+        r#"<rect x="{:.2}" y="{:.2}" width="15" height="{:.2}" fill="url(#colorbar-{})"/>"#,
+        left, top, height, idx
+    )?;
+
+    for tick in scale.ticks(5) {
+        writeln!(
+            doc,
+            r#"<text x="{:.2}" y="{:.2}" dominant-baseline="middle" {}>{}</text>"#,
+            left + 20.0,
+            top + (1.0 - tick.position) * height,
+            FONT,
+            escape(&tick.label)
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Svg;
+    use crate::error::ConspireError;
+    use crate::{Annotations, ContourOptions, HeatmapOptions, Layer, Plot};
+
+    #[test]
+    fn heatmap_of_a_single_value() {
+        let single = Layer::new().z(vec![vec![2.0]]);
+        let constant = Layer::new().z(vec![vec![4.0, 4.0], vec![4.0, 4.0]]);
+        for layer in &[single, constant] {
+            let doc = Svg::default().document(&[Plot::heatmap(layer)]).unwrap();
+            assert!(doc.contains(r#"id="colorbar-0""#));
+        }
+    }
+
+    #[test]
+    fn parallel_coordinates_with_a_constant_color() {
+        let layer = Layer::new()
+            .dimension("a", vec![1.0, 2.0])
+            .dimension("b", vec![3.0, 1.0])
+            .color(vec![3.0, 3.0]);
+        let doc = Svg::default().document(&[Plot::parallel_coordinates(&layer)]).unwrap();
+        assert!(doc.contains(r#"id="colorbar-0""#));
+    }
+
+    #[test]
+    fn colorbars_are_named_after_their_layer() {
+        let scatter = Layer::new().x(vec![1.0]).y(vec![1.0]);
+        let heatmap = Layer::new().z(vec![vec![1.0, 2.0]]);
+        let doc = Svg::default().document(&[Plot::scatter(&scatter), Plot::heatmap(&heatmap)]).unwrap();
+        assert!(doc.contains(r#"id="colorbar-1""#));
+        assert!(doc.contains("url(#colorbar-1)"));
+        assert!(!doc.contains(r#"id="colorbar""#));
+    }
+
+    #[test]
+    fn annotations() {
+        let layer = Layer::new().z(vec![vec![1.0, 2.5]]);
+        let values = HeatmapOptions { annotations: Annotations::Values(1), ..Default::default() };
+        let doc = Svg::default().document(&[Plot::heatmap_with(&layer, values)]).unwrap();
+        assert!(doc.contains(">1.0</text>") && doc.contains(">2.5</text>"));
+
+        let text = vec![vec![String::from("low"), String::from("<high>")]];
+        let text = HeatmapOptions { annotations: Annotations::Text(text), ..Default::default() };
+        let doc = Svg::default().document(&[Plot::heatmap_with(&layer, text)]).unwrap();
+        assert!(doc.contains(">low</text>") && doc.contains(">&lt;high&gt;</text>"));
+    }
+
+    #[test]
+    fn contours() {
+        let layer = Layer::new().z(vec![vec![0.0, 4.0], vec![0.0, 4.0]]);
+        let options = ContourOptions { levels: 3, ..Default::default() };
+        let doc = Svg::default().document(&[Plot::contour_with(&layer, options)]).unwrap();
+        assert_eq!(doc.matches("<path d=\"M").count(), 3);
+        assert!(doc.contains(r#"id="colorbar-0""#));
+
+        match Svg::default().document(&[Plot::surface(&layer)]) {
+            Err(ConspireError::UnsupportedPlot { backend: "svg", plot: "surface" }) => {}
+            other => panic!("expected surfaces to be unsupported, got {:?}", other),
+        }
+    }
+}
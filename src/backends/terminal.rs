@@ -0,0 +1,653 @@
+use super::scale::{
+    bar_offset, box_legend, box_shapes, contour_lines, dimension_position, dimension_scale, heatmap_cells,
+    histogram_bars, layer_color, layer_name, line_runs, point_colors, supported, violin_shapes, Axes, ColorMap,
+    HeatmapCell, Rgb, Scale, PALETTE,
+};
+use super::time::format_timestamp;
+use super::treemap::{tiles, Rect};
+use super::Renderable;
+use crate::error::ConspireError;
+use crate::stats::ViolinInner;
+use crate::Plot;
+
+use std::io::{self, Write};
+
+type Result<T> = std::result::Result<T, ConspireError>;
+
+const RESET: &str = "\x1b[0m";
+
+/// Renders plots as text, using braille characters for marks and ANSI escape codes for colors
+pub struct Terminal {
+    width: usize,
+    height: usize,
+}
+
+impl Terminal {
+    /// Create a renderer for a plot of `width` by `height` characters, axes and legend included
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+
+    pub(crate) fn draw(&self, data: &[Plot], out: &mut dyn Write) -> Result<()> {
+        // Pies and surfaces can't be made out of braille dots
+        supported("terminal", data, |p| !matches!(p, Plot::Pie { .. } | Plot::Surface { .. }))?;
+        let axes = match Axes::fit(data) {
+            Some(axes) => axes,
+            None => {
+                self.parallel_coordinates(data, out)?;
+                self.treemaps(data, out)?;
+                return Ok(());
+            }
+        };
+
+        let y_ticks = axes.y.ticks((self.height / 4).max(2));
+        let label_width = y_ticks.iter().map(|t| t.label.chars().count()).max().unwrap_or(0);
+        // Leave room for the y axis labels and axis line, and for the x axis, its labels and the legend below
+        let cols = self.width.saturating_sub(label_width + 1).max(1);
+        let rows = self.height.saturating_sub(3).max(1);
+        let mut canvas = Canvas::new(cols, rows);
+
+        let bars = data.iter().filter(|p| matches!(p, Plot::Bar { .. })).count();
+        let horizontal_bars = data
+            .iter()
+            .filter(|p| matches!(p, Plot::HorizontalBar { .. }))
+            .count();
+        let (mut bar, mut horizontal_bar) = (0, 0);
+
+        for (i, plot) in data.iter().enumerate() {
+            match plot {
+                Plot::Scatter { x, y, color, .. } => {
+                    let xs = axes.x.units(x);
+                    let colors = point_colors(color, xs.len(), PALETTE[i % PALETTE.len()]);
+                    for ((px, py), c) in xs.iter().zip(axes.y.units(y)).zip(colors) {
+                        canvas.point(*px, py, c);
+                    }
+                }
+                Plot::Line { x, y, missing, color, .. } => {
+                    let c = layer_color(i, color);
+                    let (xs, ys) = (axes.x.units(x), axes.y.units(y));
+                    for run in line_runs(x, y, *missing) {
+                        for w in run.windows(2) {
+                            canvas.line((xs[w[0]], ys[w[0]]), (xs[w[1]], ys[w[1]]), c);
+                        }
+                    }
+                }
+                Plot::Bar { x, y, color, .. } => {
+                    let width = axes.x.bar_width(x);
+                    let bar_width = width / bars as f64;
+                    let offset = bar_offset(bar, bars, width);
+                    let baseline = axes.y.baseline();
+                    let xs = axes.x.units(x);
+                    let colors = point_colors(color, xs.len(), PALETTE[i % PALETTE.len()]);
+                    for ((px, py), c) in xs.iter().zip(axes.y.units(y)).zip(colors) {
+                        let left = px + offset - bar_width / 2.0;
+                        canvas.fill((left, baseline), (left + bar_width, py), c);
+                    }
+                    bar += 1;
+                }
+                Plot::HorizontalBar { x, y, color, .. } => {
+                    let height = axes.y.bar_width(y);
+                    let bar_height = height / horizontal_bars as f64;
+                    let offset = bar_offset(horizontal_bar, horizontal_bars, height);
+                    let baseline = axes.x.baseline();
+                    let xs = axes.x.units(x);
+                    let colors = point_colors(color, xs.len(), PALETTE[i % PALETTE.len()]);
+                    for ((px, py), c) in xs.iter().zip(axes.y.units(y)).zip(colors) {
+                        let bottom = py + offset - bar_height / 2.0;
+                        canvas.fill((baseline, bottom), (*px, bottom + bar_height), c);
+                    }
+                    horizontal_bar += 1;
+                }
+                Plot::Box {
+                    x,
+                    y,
+                    options,
+                    color,
+                    name,
+                } => {
+                    let unit = |(x, y): (f64, f64)| (axes.x.unit(x), axes.y.unit(y));
+                    for shape in box_shapes(i, x, y, *options, color, name, &axes) {
+                        let c = shape.color;
+                        let outline: Vec<(f64, f64)> = shape.outline().into_iter().map(unit).collect();
+                        for (from, to) in outline.iter().zip(outline.iter().cycle().skip(1)) {
+                            canvas.line(*from, *to, c);
+                        }
+                        // There are no dashes in braille, so the mean is drawn like the median
+                        for [from, to] in shape.whiskers().into_iter().chain(Some(shape.median())).chain(shape.mean()) {
+                            canvas.line(unit(from), unit(to), c);
+                        }
+                        for outlier in shape.outliers() {
+                            let (px, py) = unit(outlier);
+                            canvas.point(px, py, c);
+                        }
+                    }
+                }
+                Plot::Violin {
+                    x,
+                    group,
+                    bandwidth,
+                    inner,
+                    color,
+                    name,
+                } => {
+                    let c = layer_color(i, color);
+                    let unit = |(x, y): (f64, f64)| (axes.x.unit(x), axes.y.unit(y));
+                    for shape in violin_shapes(x, group, &layer_name(i, name), *bandwidth, &axes.y) {
+                        let outline: Vec<(f64, f64)> = shape.outline().into_iter().map(unit).collect();
+                        for w in outline.windows(2) {
+                            canvas.line(w[0], w[1], c);
+                        }
+
+                        let stats = &shape.violin.stats;
+                        let (center, half) = (shape.center, shape.box_half());
+                        match inner {
+                            ViolinInner::Box => {
+                                let (bottom, top) = (center - half, center + half);
+                                let (lower, upper) = (stats.lower_whisker, stats.upper_whisker);
+                                canvas.line(unit((lower, center)), unit((upper, center)), c);
+                                for y in &[bottom, top] {
+                                    canvas.line(unit((stats.q1, *y)), unit((stats.q3, *y)), c);
+                                }
+                                canvas.line(unit((stats.median, bottom)), unit((stats.median, top)), c);
+                            }
+                            ViolinInner::Quartiles => {
+                                for quartile in &[stats.q1, stats.median, stats.q3] {
+                                    let (bottom, top) = shape.span(*quartile);
+                                    canvas.line(unit((*quartile, bottom)), unit((*quartile, top)), c);
+                                }
+                            }
+                            ViolinInner::None => {}
+                        }
+                    }
+                }
+                Plot::Histogram { x, binning, color, .. } => {
+                    let c = layer_color(i, color);
+                    let baseline = axes.y.baseline();
+                    for (start, end, count) in histogram_bars(x, *binning, &axes.x) {
+                        canvas.fill((axes.x.unit(start), baseline), (axes.x.unit(end), axes.y.unit(count)), c);
+                    }
+                }
+                // A cell is often a single character, which leaves no room for annotations
+                Plot::Heatmap { x, y, z, options, .. } => {
+                    heatmap(&mut canvas, &axes, &heatmap_cells(x, y, z, options), &ColorMap::heatmap(z, options));
+                }
+                Plot::Contour { x, y, z, options, .. } => {
+                    let unit = |(x, y): (f64, f64)| (axes.x.unit(x), axes.y.unit(y));
+                    for line in contour_lines(x, y, z, options) {
+                        for [from, to] in line.segments {
+                            canvas.line(unit(from), unit(to), line.color);
+                        }
+                    }
+                }
+                Plot::Pie { .. } | Plot::Surface { .. } | Plot::Treemap { .. } | Plot::ParallelCoordinates { .. } => {
+                    unreachable!(
+                        "pies, surfaces, and treemaps and parallel coordinates on axes, are rejected before drawing"
+                    )
+                }
+            }
+        }
+
+        // The y axis, with a label at every row a tick falls on
+        for row in 0..rows {
+            let tick = y_ticks
+                .iter()
+                .find(|t| ((1.0 - t.position) * (rows - 1) as f64).round() as usize == row);
+            let label = tick.map(|t| t.label.as_str()).unwrap_or("");
+            let axis = if tick.is_some() { '┤' } else { '│' };
+            writeln!(out, "{:>w$}{}{}", label, axis, canvas.row(row), w = label_width)?;
+        }
+
+        // The x axis, with as many labels as fit without overlapping
+        let mut axis: Vec<char> = vec!['─'; cols];
+        let mut labels: Vec<char> = vec![' '; cols];
+        let mut free_from = 0;
+        for tick in axes.x.ticks((cols / 10).max(2)) {
+            let col = (tick.position * (cols - 1) as f64).round() as usize;
+            if col >= cols {
+                continue;
+            }
+            axis[col] = '┬';
+            let label: Vec<char> = tick.label.chars().collect();
+            let start = col.saturating_sub(label.len() / 2);
+            if start >= free_from && start + label.len() <= cols {
+                labels[start..start + label.len()].copy_from_slice(&label);
+                free_from = start + label.len() + 1;
+            }
+        }
+        let axis: String = axis.into_iter().collect();
+        let labels: String = labels.into_iter().collect();
+        writeln!(out, "{:>w$}└{}", "", axis, w = label_width)?;
+        writeln!(out, "{:>w$} {}", "", labels.trim_end(), w = label_width)?;
+
+        let legend: Vec<String> = data
+            .iter()
+            .enumerate()
+            .flat_map(|(i, plot)| match plot {
+                Plot::Scatter { color, name, .. }
+                | Plot::Line { color, name, .. }
+                | Plot::Bar { color, name, .. }
+                | Plot::HorizontalBar { color, name, .. }
+                | Plot::Violin { color, name, .. }
+                | Plot::Histogram { color, name, .. } => vec![(layer_name(i, name), layer_color(i, color))],
+                Plot::Box { color, name, .. } => box_legend(i, color, name),
+                Plot::Heatmap { .. }
+                | Plot::Contour { .. }
+                | Plot::Surface { .. }
+                | Plot::Pie { .. }
+                | Plot::Treemap { .. }
+                | Plot::ParallelCoordinates { .. } => Vec::new(),
+            })
+            .map(|(name, color)| format!("{}■{} {}", ansi(color), RESET, name))
+            .collect();
+        writeln!(out, "{:>w$} {}", "", legend.join("  "), w = label_width)?;
+        Ok(())
+    }
+}
+
+/// Fill every character of the canvas whose center falls in a cell of a heatmap with the color of the cell
+fn heatmap(canvas: &mut Canvas, axes: &Axes, cells: &[HeatmapCell], map: &ColorMap) {
+    for row in 0..canvas.rows {
+        for col in 0..canvas.cols {
+            let cx = axes.x.value((col as f64 + 0.5) / canvas.cols as f64);
+            let cy = axes.y.value(1.0 - (row as f64 + 0.5) / canvas.rows as f64);
+            let inside = |a: f64, b: f64, v: f64| a.min(b) <= v && v < a.max(b);
+            if let Some(cell) = cells
+                .iter()
+                .find(|c| inside(c.left, c.right, cx) && inside(c.bottom, c.top, cy))
+            {
+                canvas.block(col, row, map.color(cell.value));
+            }
+        }
+    }
+}
+
+impl Terminal {
+    /// Draw the parallel coordinates plots of a plot without axes, one after the other. There's no room for ticks, so
+    /// the range of each dimension is written below the plot instead.
+    fn parallel_coordinates(&self, data: &[Plot], out: &mut dyn Write) -> io::Result<()> {
+        for (i, plot) in data.iter().enumerate() {
+            if let Plot::ParallelCoordinates { dimensions, color, .. } = plot {
+                let (cols, rows) = (self.width.max(1), self.height.saturating_sub(1 + dimensions.len()).max(1));
+                let mut canvas = Canvas::new(cols, rows);
+                let scales: Vec<Scale> = dimensions.iter().map(|(_, data)| dimension_scale(data)).collect();
+                let units: Vec<Vec<f64>> = dimensions.iter().zip(&scales).map(|((_, d), s)| s.units(d)).collect();
+                let len = units.first().map_or(0, |u| u.len());
+                let x = |d: usize| dimension_position(d, dimensions.len());
+
+                for d in 0..dimensions.len() {
+                    canvas.line((x(d), 0.0), (x(d), 1.0), Rgb(128, 128, 128));
+                }
+                for (p, c) in point_colors(color, len, PALETTE[i % PALETTE.len()]).into_iter().enumerate() {
+                    let points: Vec<(f64, f64)> = units
+                        .iter()
+                        .enumerate()
+                        .map(|(d, u)| (x(d), u[p]))
+                        .filter(|(_, y)| y.is_finite())
+                        .collect();
+                    for w in points.windows(2) {
+                        canvas.line(w[0], w[1], c);
+                    }
+                }
+                for row in 0..rows {
+                    writeln!(out, "{}", canvas.row(row))?;
+                }
+
+                let mut names: Vec<char> = vec![' '; cols];
+                let mut free_from = 0;
+                for (d, (name, _)) in dimensions.iter().enumerate() {
+                    let col = (x(d) * (cols - 1) as f64).round() as usize;
+                    let name: Vec<char> = name.chars().collect();
+                    let start = col.saturating_sub(name.len() / 2).min(cols.saturating_sub(name.len()));
+                    if start >= free_from && start + name.len() <= cols {
+                        names[start..start + name.len()].copy_from_slice(&name);
+                        free_from = start + name.len() + 1;
+                    }
+                }
+                writeln!(out, "{}", names.into_iter().collect::<String>().trim_end())?;
+                for ((name, _), scale) in dimensions.iter().zip(&scales) {
+                    let range = match scale {
+                        Scale::Linear { min, max } => format!("{} to {}", min, max),
+                        Scale::Time { min, max } => format!("{} to {}", format_timestamp(*min), format_timestamp(*max)),
+                        Scale::Band(categories) => categories.join(", "),
+                    };
+                    writeln!(out, "{}: {}, bottom to top", name, range)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw the treemaps of a plot without axes side by side, as tiles of colored background with the labels of the
+    /// leaves written on them
+    fn treemaps(&self, data: &[Plot], out: &mut dyn Write) -> io::Result<()> {
+        let treemaps: Vec<&Plot> = data.iter().filter(|p| matches!(p, Plot::Treemap { .. })).collect();
+        if treemaps.is_empty() {
+            return Ok(());
+        }
+
+        let (cols, rows) = (self.width.max(1), self.height.max(1));
+        let mut cells: Vec<(char, Option<Rgb>, Rgb)> = vec![(' ', None, Rgb(0, 0, 0)); cols * rows];
+        let slot_width = cols as f64 / treemaps.len() as f64;
+
+        for (i, plot) in treemaps.iter().enumerate() {
+            if let Plot::Treemap {
+                label,
+                parent,
+                value,
+                color,
+                ..
+            } = plot
+            {
+                // Characters are about twice as tall as they're wide, so the tiles are laid out on half rows to come
+                // out square
+                let rect = Rect {
+                    x0: slot_width * i as f64,
+                    y0: 0.0,
+                    x1: slot_width * (i + 1) as f64,
+                    y1: rows as f64 * 2.0,
+                };
+                for tile in tiles(label, parent, value, color, rect) {
+                    let (left, right) = (tile.rect.x0.round() as usize, (tile.rect.x1.round() as usize).min(cols));
+                    let (top, bottom) =
+                        ((tile.rect.y0 / 2.0).round() as usize, ((tile.rect.y1 / 2.0).round() as usize).min(rows));
+                    // The last column of each tile is left empty, to tell apart neighbours of the same color
+                    let right = if right > left + 1 { right - 1 } else { right };
+                    for row in top..bottom {
+                        for col in left..right {
+                            cells[row * cols + col] = (' ', Some(tile.color), tile.text_color());
+                        }
+                        if right < cols {
+                            cells[row * cols + right] = (' ', None, Rgb(0, 0, 0));
+                        }
+                    }
+                    if tile.leaf && top < bottom {
+                        let row = (top + bottom - 1) / 2;
+                        let text: Vec<char> = tile.label.chars().take(right - left).collect();
+                        let start = left + (right - left - text.len()) / 2;
+                        for (offset, c) in text.into_iter().enumerate() {
+                            cells[row * cols + start + offset].0 = c;
+                        }
+                    }
+                }
+            }
+        }
+
+        for row in cells.chunks(cols) {
+            let mut line = String::new();
+            let mut current = None;
+            for (c, background, foreground) in row {
+                let style = background.map(|b| (b, *foreground));
+                if style != current {
+                    match style {
+                        Some((b, f)) => line.push_str(&format!("\x1b[48;2;{};{};{}m{}", b.0, b.1, b.2, ansi(f))),
+                        None => line.push_str(RESET),
+                    }
+                    current = style;
+                }
+                line.push(*c);
+            }
+            if current.is_some() {
+                line.push_str(RESET);
+            }
+            writeln!(out, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl Renderable for Terminal {
+    fn render(&self, data: &[Plot], _display: bool) -> Result<()> {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        self.draw(data, &mut out)
+    }
+
+    fn write(&self, data: &[Plot], writer: &mut dyn Write) -> Result<()> {
+        self.draw(data, writer)
+    }
+}
+
+/// The ANSI escape code setting the foreground to a 24-bit color
+fn ansi(color: Rgb) -> String {
+    format!("\x1b[38;2;{};{};{}m", color.0, color.1, color.2)
+}
+
+/// A single character of the canvas: either a set of braille dots, or a solid block
+#[derive(Clone, Copy, Default)]
+struct Cell {
+    dots: u8,
+    block: bool,
+    color: Option<Rgb>,
+}
+
+/// A grid of characters, each holding 2 by 4 braille dots
+struct Canvas {
+    cols: usize,
+    rows: usize,
+    cells: Vec<Cell>,
+}
+
+impl Canvas {
+    fn new(cols: usize, rows: usize) -> Self {
+        Self {
+            cols,
+            rows,
+            cells: vec![Cell::default(); cols * rows],
+        }
+    }
+
+    /// The dot closest to a position given in units
+    fn dot_at(&self, x: f64, y: f64) -> (i64, i64) {
+        let width = (self.cols * 2 - 1) as f64;
+        let height = (self.rows * 4 - 1) as f64;
+        ((x * width).round() as i64, ((1.0 - y) * height).round() as i64)
+    }
+
+    fn dot(&mut self, x: i64, y: i64, color: Rgb) {
+        if x < 0 || y < 0 || x >= (self.cols * 2) as i64 || y >= (self.rows * 4) as i64 {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        // Braille dots are numbered down the left column, then the right, with the bottom row added last
+        let bit = match (x % 2, y % 4) {
+            (0, 3) => 0x40,
+            (1, 3) => 0x80,
+            (0, row) => 1 << row,
+            (_, row) => 1 << (row + 3),
+        };
+        let cell = &mut self.cells[(y / 4) * self.cols + x / 2];
+        cell.dots |= bit;
+        cell.color = Some(color);
+    }
+
+    fn point(&mut self, x: f64, y: f64, color: Rgb) {
+        if x.is_finite() && y.is_finite() {
+            let (dx, dy) = self.dot_at(x, y);
+            self.dot(dx, dy, color);
+        }
+    }
+
+    /// Draw a straight line between two positions, using Bresenham's algorithm
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), color: Rgb) {
+        if !(from.0.is_finite() && from.1.is_finite() && to.0.is_finite() && to.1.is_finite()) {
+            return;
+        }
+        let (mut x, mut y) = self.dot_at(from.0, from.1);
+        let (x1, y1) = self.dot_at(to.0, to.1);
+        let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+        let (sx, sy) = (if x < x1 { 1 } else { -1 }, if y < y1 { 1 } else { -1 });
+        let mut err = dx + dy;
+
+        loop {
+            self.dot(x, y, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// Fill the rectangle between two corners
+    fn fill(&mut self, from: (f64, f64), to: (f64, f64), color: Rgb) {
+        if !(from.0.is_finite() && from.1.is_finite() && to.0.is_finite() && to.1.is_finite()) {
+            return;
+        }
+        let (x0, y0) = self.dot_at(from.0, from.1);
+        let (x1, y1) = self.dot_at(to.0, to.1);
+        for x in x0.min(x1)..=x0.max(x1) {
+            for y in y0.min(y1)..=y0.max(y1) {
+                self.dot(x, y, color);
+            }
+        }
+    }
+
+    fn block(&mut self, col: usize, row: usize, color: Rgb) {
+        let cell = &mut self.cells[row * self.cols + col];
+        cell.block = true;
+        cell.color = Some(color);
+    }
+
+    fn row(&self, row: usize) -> String {
+        let mut line = String::new();
+        let mut current = None;
+
+        for cell in &self.cells[row * self.cols..(row + 1) * self.cols] {
+            if cell.color != current {
+                match cell.color {
+                    Some(color) => line.push_str(&ansi(color)),
+                    None => line.push_str(RESET),
+                }
+                current = cell.color;
+            }
+            if cell.dots != 0 {
+                line.push(std::char::from_u32(0x2800 + u32::from(cell.dots)).unwrap());
+            } else if cell.block {
+                line.push('█');
+            } else {
+                line.push(' ');
+            }
+        }
+
+        if current.is_some() {
+            line.push_str(RESET);
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Layer;
+
+    const RED: Rgb = Rgb(255, 0, 0);
+
+    fn render(width: usize, height: usize, data: &[Plot]) -> Result<String> {
+        let mut out = Vec::new();
+        Terminal::new(width, height).draw(data, &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    /// The text of a rendered plot, without its colors
+    fn plain(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn braille_dots() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.point(0.0, 1.0, RED);
+        canvas.point(1.0, 0.0, RED);
+        assert_eq!(plain(&canvas.row(0)), "⠁⢀");
+        assert_eq!(canvas.row(0), format!("{}⠁⢀{}", ansi(RED), RESET));
+    }
+
+    #[test]
+    fn points_outside_the_canvas_are_dropped() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.point(f64::NAN, 0.5, RED);
+        canvas.point(2.0, 0.5, RED);
+        canvas.dot(-1, 0, RED);
+        assert_eq!(canvas.row(0), " ");
+    }
+
+    #[test]
+    fn bresenham_lines() {
+        let mut horizontal = Canvas::new(2, 1);
+        horizontal.line((0.0, 1.0), (1.0, 1.0), RED);
+        assert_eq!(plain(&horizontal.row(0)), "⠉⠉");
+
+        // From the top left to the bottom right dot of a single character, stepping down more often than across
+        let mut diagonal = Canvas::new(1, 1);
+        diagonal.line((0.0, 1.0), (1.0, 0.0), RED);
+        assert_eq!(plain(&diagonal.row(0)), "⢣");
+
+        let mut vertical = Canvas::new(1, 2);
+        vertical.line((0.0, 0.0), (0.0, 1.0), RED);
+        assert_eq!((plain(&vertical.row(0)), plain(&vertical.row(1))), (String::from("⡇"), String::from("⡇")));
+    }
+
+    #[test]
+    fn axes_and_legend() {
+        let layer = Layer::new().x(vec![0.0, 10.0]).y(vec![0.0, 10.0]).name("points");
+        let text = plain(&render(30, 8, &[Plot::line(&layer)]).unwrap());
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 8);
+        // Five rows of canvas, each starting with the y axis, then the x axis, its labels and the legend
+        let label_width = lines[0].find(['┤', '│']).unwrap();
+        for line in &lines[..5] {
+            assert!(line[label_width..].starts_with(['┤', '│']), "{}", line);
+        }
+        assert!(lines[0].trim_start().starts_with("10"));
+        assert!(lines[4].trim_start().starts_with('0'));
+        assert!(lines[5].trim_start().starts_with('└') && lines[5].contains('┬'));
+        assert!(lines[6].trim_start().starts_with('0') && lines[6].trim_end().ends_with("10"));
+        assert_eq!(lines[7].trim(), "■ points");
+    }
+
+    #[test]
+    fn category_labels() {
+        let layer = Layer::new().x(vec!["low", "high"]).y(vec![1.0, 2.0]);
+        let text = plain(&render(40, 10, &[Plot::bar(&layer)]).unwrap());
+        let labels = text.lines().nth(8).unwrap();
+        assert!(labels.find("low").unwrap() < labels.find("high").unwrap());
+    }
+
+    #[test]
+    fn unsupported_plots() {
+        let pie = Layer::new().x(vec!["a", "b"]);
+        match render(30, 8, &[Plot::pie(&pie)]) {
+            Err(ConspireError::UnsupportedPlot { backend: "terminal", plot: "pie" }) => {}
+            other => panic!("expected pies to be unsupported, got {:?}", other),
+        }
+
+        let treemap = Layer::new().label(vec!["a", "b"]).parent(vec!["", "a"]);
+        let points = Layer::new().x(vec![1.0]).y(vec![1.0]);
+        assert!(render(30, 8, &[Plot::treemap(&treemap), Plot::scatter(&points)]).is_err());
+        assert!(render(30, 8, &[Plot::treemap(&treemap)]).is_ok());
+
+        let grid = Layer::new().z(vec![vec![0.0, 4.0], vec![0.0, 4.0]]);
+        match render(30, 8, &[Plot::surface(&grid)]) {
+            Err(ConspireError::UnsupportedPlot { backend: "terminal", plot: "surface" }) => {}
+            other => panic!("expected surfaces to be unsupported, got {:?}", other),
+        }
+        assert!(render(30, 8, &[Plot::contour(&grid)]).is_ok());
+    }
+}
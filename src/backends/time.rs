@@ -0,0 +1,282 @@
+//! Calendar arithmetic for time axes, shared by the backends that place their own ticks. Times are seconds since the
+//! Unix epoch, and every date and time is in UTC.
+
+const MINUTE: f64 = 60.0;
+const HOUR: f64 = 60.0 * MINUTE;
+const DAY: f64 = 24.0 * HOUR;
+const WEEK: f64 = 7.0 * DAY;
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// Steps between ticks that fall on round times, from a second up to a week
+const STEPS: [f64; 20] = [
+    1.0,
+    2.0,
+    5.0,
+    10.0,
+    15.0,
+    30.0,
+    MINUTE,
+    2.0 * MINUTE,
+    5.0 * MINUTE,
+    10.0 * MINUTE,
+    15.0 * MINUTE,
+    30.0 * MINUTE,
+    HOUR,
+    2.0 * HOUR,
+    3.0 * HOUR,
+    6.0 * HOUR,
+    12.0 * HOUR,
+    DAY,
+    2.0 * DAY,
+    WEEK,
+];
+
+/// A date and time of day
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    seconds: f64,
+}
+
+impl DateTime {
+    fn from_timestamp(timestamp: f64) -> Self {
+        let days = (timestamp / DAY).floor();
+        let (year, month, day) = civil_from_days(days as i64);
+        DateTime {
+            year,
+            month,
+            day,
+            seconds: timestamp - days * DAY,
+        }
+    }
+
+    fn hour(&self) -> u32 {
+        (self.seconds / HOUR) as u32
+    }
+
+    fn minute(&self) -> u32 {
+        (self.seconds % HOUR / MINUTE) as u32
+    }
+
+    fn second(&self) -> f64 {
+        self.seconds % MINUTE
+    }
+
+    fn date(&self) -> String {
+        format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+
+    fn time(&self) -> String {
+        format!("{:02}:{:02}:{:02}", self.hour(), self.minute(), self.second().floor())
+    }
+}
+
+/// The year, month and day of a number of days since the Unix epoch, using Howard Hinnant's `civil_from_days`
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// The number of days since the Unix epoch of a date, using Howard Hinnant's `days_from_civil`
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// The timestamp of midnight on the first day of a month, where months past December roll over into later years
+fn month_start(year: i64, month: i64) -> f64 {
+    let year = year + (month - 1).div_euclid(12);
+    let month = (month - 1).rem_euclid(12) as u32 + 1;
+    days_from_civil(year, month, 1) as f64 * DAY
+}
+
+/// A timestamp as an ISO 8601 date and time, such as `2024-03-15 14:30:00`, with milliseconds when it has any
+pub fn format_timestamp(timestamp: f64) -> String {
+    let date_time = DateTime::from_timestamp(timestamp);
+    let millis = (date_time.second().fract() * 1000.0).round() as u32;
+    if millis == 0 || millis == 1000 {
+        format!("{} {}", date_time.date(), date_time.time())
+    } else {
+        format!("{} {}.{:03}", date_time.date(), date_time.time(), millis)
+    }
+}
+
+/// Ticks on round times between two timestamps, aiming for roughly `count` of them, each with a label as precise as
+/// the step between ticks needs. Steps under a second fall back to a decimal step, given by `decimal_step`.
+pub fn ticks(min: f64, max: f64, count: usize, decimal_step: impl Fn(f64, usize) -> f64) -> Vec<(f64, String)> {
+    let range = max - min;
+    let count = count.max(1);
+    if range.is_nan() || range <= 0.0 {
+        return vec![(min, format_timestamp(min))];
+    }
+
+    let start = DateTime::from_timestamp(min);
+    let months = (range / (30.0 * DAY) / count as f64).ceil() as i64;
+    let values = if range / (count as f64) < 1.0 {
+        steps_of(min, max, decimal_step(range, count), 0.0)
+    } else if let Some(step) = STEPS.iter().find(|s| range / **s <= count as f64) {
+        // Weeks start on a Monday, and the epoch was a Thursday
+        let offset = if *step == WEEK { 4.0 * DAY } else { 0.0 };
+        steps_of(min, max, *step, offset)
+    } else if months < 12 {
+        let step = [1, 2, 3, 6].iter().find(|s| **s >= months).cloned().unwrap_or(6);
+        // The first month of the year is always a tick, so the ticks fall on the same months every year
+        let first = (i64::from(start.month) - 1) / step * step + 1;
+        calendar_steps(min, max, first, step, |month| month_start(start.year, month))
+    } else {
+        let step = decimal_step(range / (365.25 * DAY), count).round().max(1.0) as i64;
+        calendar_steps(min, max, start.year.div_euclid(step) * step, step, |year| month_start(year, 1))
+    };
+
+    let step = match values.as_slice() {
+        [first, second, ..] => second - first,
+        _ => range,
+    };
+    let same_day = DateTime::from_timestamp(min).date() == DateTime::from_timestamp(max).date();
+    values.iter().map(|t| (*t, label(*t, step, same_day))).collect()
+}
+
+/// The timestamps of every `step`th month or year from `first`, between two timestamps
+fn calendar_steps(min: f64, max: f64, first: i64, step: i64, timestamp: impl Fn(i64) -> f64) -> Vec<f64> {
+    (0..)
+        .map(|i| timestamp(first + i * step))
+        .take_while(|t| *t <= max)
+        .filter(|t| *t >= min)
+        .collect()
+}
+
+/// Every multiple of a step, counted from an offset, between two timestamps
+fn steps_of(min: f64, max: f64, step: f64, offset: f64) -> Vec<f64> {
+    let first = ((min - offset) / step).ceil() as i64;
+    let last = ((max - offset) / step).floor() as i64;
+    (first..=last).map(|i| offset + i as f64 * step).collect()
+}
+
+/// The label of a tick, leaving out the parts of the date and time that are the same for neighbouring ticks
+fn label(timestamp: f64, step: f64, same_day: bool) -> String {
+    let t = DateTime::from_timestamp(timestamp);
+    let month = MONTHS[t.month as usize - 1];
+    if step >= 360.0 * DAY {
+        format!("{}", t.year)
+    } else if step >= 28.0 * DAY {
+        format!("{} {}", month, t.year)
+    } else if step >= DAY {
+        format!("{} {}", month, t.day)
+    } else if step >= MINUTE && same_day {
+        format!("{:02}:{:02}", t.hour(), t.minute())
+    } else if step >= MINUTE {
+        format!("{} {} {:02}:{:02}", month, t.day, t.hour(), t.minute())
+    } else if step >= 1.0 {
+        t.time()
+    } else {
+        let decimals = (-step.log10().floor()).max(0.0) as usize;
+        format!("{:02}:{:02}:{:0w$.p$}", t.hour(), t.minute(), t.second(), w = decimals + 3, p = decimals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(range: f64, count: usize) -> f64 {
+        (range / count as f64).ceil()
+    }
+
+    fn date(year: i64, month: u32, day: u32) -> f64 {
+        days_from_civil(year, month, day) as f64 * DAY
+    }
+
+    #[test]
+    fn epoch() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(format_timestamp(0.0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(-1.0), "1969-12-31 23:59:59");
+        assert_eq!(format_timestamp(1.25), "1970-01-01 00:00:01.250");
+    }
+
+    #[test]
+    fn negative_years() {
+        assert_eq!(civil_from_days(-719_468), (0, 3, 1));
+        assert_eq!(civil_from_days(days_from_civil(0, 1, 1) - 1), (-1, 12, 31));
+        assert_eq!(civil_from_days(days_from_civil(-4713, 11, 24)), (-4713, 11, 24));
+        let ides = DateTime::from_timestamp(date(-44, 3, 15) + HOUR);
+        assert_eq!((ides.year, ides.month, ides.day, ides.hour()), (-44, 3, 15, 1));
+    }
+
+    #[test]
+    fn days_round_trip() {
+        let mut previous = civil_from_days(-1_000_001);
+        for days in -1_000_000..1_000_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+            assert!((year, month, day) > previous);
+            previous = (year, month, day);
+        }
+    }
+
+    #[test]
+    fn leap_days() {
+        assert_eq!(days_from_civil(2024, 3, 1) - days_from_civil(2024, 2, 28), 2);
+        assert_eq!(days_from_civil(2000, 3, 1) - days_from_civil(2000, 2, 28), 2);
+        assert_eq!(days_from_civil(1900, 3, 1) - days_from_civil(1900, 2, 28), 1);
+        assert_eq!(days_from_civil(2023, 3, 1) - days_from_civil(2023, 2, 28), 1);
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+        assert_eq!(format_timestamp(date(2024, 2, 29) + 12.0 * HOUR), "2024-02-29 12:00:00");
+    }
+
+    #[test]
+    fn month_starts_roll_over() {
+        assert_eq!(month_start(2024, 13), date(2025, 1, 1));
+        assert_eq!(month_start(2024, 0), date(2023, 12, 1));
+    }
+
+    #[test]
+    fn day_ticks_across_months() {
+        let ticks = ticks(date(2024, 1, 30), date(2024, 2, 3), 5, step);
+        let labels: Vec<&str> = ticks.iter().map(|(_, l)| l.as_str()).collect();
+        assert_eq!(labels, vec!["Jan 30", "Jan 31", "Feb 1", "Feb 2", "Feb 3"]);
+        assert_eq!(ticks[2].0, date(2024, 2, 1));
+    }
+
+    #[test]
+    fn month_ticks_start_on_the_first() {
+        let ticks = ticks(date(2024, 1, 15), date(2024, 6, 15), 5, step);
+        assert_eq!(
+            ticks,
+            vec![(date(2024, 3, 1), String::from("Mar 2024")), (date(2024, 5, 1), String::from("May 2024"))]
+        );
+        // Over a leap day, February's tick still falls on its first day
+        let ticks = super::ticks(date(2023, 12, 20), date(2024, 3, 10), 3, step);
+        let starts: Vec<f64> = ticks.iter().map(|(t, _)| *t).collect();
+        assert_eq!(starts, vec![date(2024, 1, 1), date(2024, 2, 1), date(2024, 3, 1)]);
+    }
+
+    #[test]
+    fn year_ticks() {
+        let ticks = ticks(date(2000, 1, 1), date(2024, 6, 1), 5, step);
+        let labels: Vec<&str> = ticks.iter().map(|(_, l)| l.as_str()).collect();
+        assert_eq!(labels, vec!["2000", "2005", "2010", "2015", "2020"]);
+    }
+
+    #[test]
+    fn ticks_of_a_single_time() {
+        assert_eq!(ticks(0.0, 0.0, 5, step), vec![(0.0, String::from("1970-01-01 00:00:00"))]);
+    }
+}
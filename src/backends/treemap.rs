@@ -0,0 +1,344 @@
+//! The squarified treemap layout (Bruls, Huizing and van Wijk), shared by the backends that draw treemaps themselves.
+//! Tiles are laid out in whatever coordinates the backend passes in, so each backend can lay them out directly on its
+//! own canvas and keep them square in its own pixels.
+
+use super::scale::{point_colors, Rgb, PALETTE};
+use crate::data::VectorData;
+
+use std::collections::HashMap;
+
+/// The color of the nodes above the top-level branches, such as the root of a tree with a single root
+const TRUNK: Rgb = Rgb(220, 220, 220);
+
+/// A rectangle, spanning `x0` to `x1` and `y0` to `y1`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x0: f64,
+    pub y0: f64,
+    pub x1: f64,
+    pub y1: f64,
+}
+
+impl Rect {
+    pub fn width(&self) -> f64 {
+        self.x1 - self.x0
+    }
+
+    pub fn height(&self) -> f64 {
+        self.y1 - self.y0
+    }
+
+    fn inset(&self, by: f64) -> Rect {
+        Rect {
+            x0: self.x0 + by,
+            y0: self.y0 + by,
+            x1: self.x1 - by,
+            y1: self.y1 - by,
+        }
+    }
+}
+
+/// A node of the tree, placed in its rectangle. Children are placed inside their parent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tile {
+    pub label: String,
+    pub depth: usize,
+    pub leaf: bool,
+    pub color: Rgb,
+    pub rect: Rect,
+}
+
+impl Tile {
+    /// Black or white, whichever stands out more against the tile
+    pub fn text_color(&self) -> Rgb {
+        self.color.contrast()
+    }
+}
+
+struct Tree<'a> {
+    labels: Vec<&'a str>,
+    children: Vec<Vec<usize>>,
+    own: Vec<f64>,
+    weights: Vec<f64>,
+    colors: Option<Vec<Rgb>>,
+    branch_depth: usize,
+    padding: f64,
+}
+
+/// Lay out a treemap within a rectangle, returning every node with a positive value, parents before their children.
+///
+/// Nodes whose parent is empty or isn't one of the labels are roots. A node is as large as its own value plus the
+/// values of its children, so parents may leave room beside their children, as Plotly does. Without values, every leaf
+/// counts as one. Without colors, every top-level branch gets a color of its own, which fades for its descendants.
+pub fn tiles(
+    label: &VectorData,
+    parent: &VectorData,
+    value: &Option<VectorData>,
+    color: &Option<VectorData>,
+    rect: Rect,
+) -> Vec<Tile> {
+    let (labels, parents) = match (label, parent) {
+        (
+            VectorData::Categorical(labels) | VectorData::Ordinal { values: labels, .. },
+            VectorData::Categorical(parents) | VectorData::Ordinal { values: parents, .. },
+        ) => (labels, parents),
+        _ => return Vec::new(),
+    };
+    let index: HashMap<&str, usize> = labels.iter().enumerate().map(|(i, l)| (l.as_str(), i)).collect();
+
+    let mut children = vec![Vec::new(); labels.len()];
+    let mut roots = Vec::new();
+    for (i, p) in parents.iter().enumerate() {
+        match index.get(p.as_str()) {
+            Some(&p) if p != i => children[p].push(i),
+            _ => roots.push(i),
+        }
+    }
+
+    let own: Vec<f64> = match value {
+        Some(VectorData::Quantitative(v)) => {
+            v.iter().map(|v| if *v > 0.0 && v.is_finite() { *v } else { 0.0 }).collect()
+        }
+        _ => children.iter().map(|c| if c.is_empty() { 1.0 } else { 0.0 }).collect(),
+    };
+    let mut tree = Tree {
+        labels: labels.iter().map(|l| l.as_str()).collect(),
+        weights: vec![0.0; labels.len()],
+        own,
+        children,
+        colors: color.as_ref().map(|_| point_colors(color, labels.len(), PALETTE[0])),
+        branch_depth: if roots.len() == 1 { 1 } else { 0 },
+        padding: 0.01 * rect.width().abs().min(rect.height().abs()),
+    };
+    for root in &roots {
+        tree.weigh(*root);
+    }
+
+    let mut tiles = Vec::new();
+    tree.place(&roots, None, rect, 0, None, &mut tiles);
+    tiles
+}
+
+impl<'a> Tree<'a> {
+    /// Compute the weight of a node and its descendants. Only nodes reachable from a root are visited, and labels are
+    /// unique, so this can't loop.
+    fn weigh(&mut self, node: usize) -> f64 {
+        let mut weight = self.own[node];
+        for i in 0..self.children[node].len() {
+            weight += self.weigh(self.children[node][i]);
+        }
+        self.weights[node] = weight;
+        weight
+    }
+
+    /// Place sibling nodes, and any room left for their parent's own value, within a rectangle
+    fn place(
+        &self,
+        nodes: &[usize],
+        remainder: Option<f64>,
+        rect: Rect,
+        depth: usize,
+        branch: Option<usize>,
+        tiles: &mut Vec<Tile>,
+    ) {
+        let mut items: Vec<(Option<usize>, f64)> = nodes
+            .iter()
+            .map(|n| (Some(*n), self.weights[*n]))
+            .chain(remainder.map(|r| (None, r)))
+            .filter(|(_, w)| *w > 0.0)
+            .collect();
+        items.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        for (node, rect) in squarify(&items, rect) {
+            let node = match node {
+                Some(node) => node,
+                None => continue,
+            };
+            let branch = branch.or_else(|| {
+                if depth == self.branch_depth {
+                    nodes.iter().position(|n| *n == node)
+                } else {
+                    None
+                }
+            });
+            let color = match (&self.colors, branch) {
+                (Some(colors), _) => colors[node],
+                (None, Some(branch)) => fade(PALETTE[branch % PALETTE.len()], depth - self.branch_depth),
+                (None, None) => TRUNK,
+            };
+            tiles.push(Tile {
+                label: self.labels[node].to_string(),
+                depth,
+                leaf: self.children[node].is_empty(),
+                color,
+                rect,
+            });
+
+            if !self.children[node].is_empty() {
+                let inner = if rect.width() > 4.0 * self.padding && rect.height() > 4.0 * self.padding {
+                    rect.inset(self.padding)
+                } else {
+                    rect
+                };
+                self.place(&self.children[node], Some(self.own[node]), inner, depth + 1, branch, tiles);
+            }
+        }
+    }
+}
+
+/// Lighten the color of a branch for each level below the top of the branch, like Plotly's depth fade
+fn fade(color: Rgb, levels: usize) -> Rgb {
+    let t = (0.2 * levels as f64).min(0.6);
+    let mix = |c: u8| (f64::from(c) + (255.0 - f64::from(c)) * t).round() as u8;
+    Rgb(mix(color.0), mix(color.1), mix(color.2))
+}
+
+/// Divide a rectangle between items sorted by decreasing weight, laying them out in rows along the shorter side of
+/// the space left, and starting a new row whenever adding an item would make the row's tiles less square
+fn squarify<T: Copy>(items: &[(T, f64)], rect: Rect) -> Vec<(T, Rect)> {
+    let total: f64 = items.iter().map(|(_, w)| w).sum();
+    if total <= 0.0 || rect.width() <= 0.0 || rect.height() <= 0.0 {
+        return Vec::new();
+    }
+    let scale = rect.width() * rect.height() / total;
+    let areas: Vec<(T, f64)> = items.iter().map(|(item, w)| (*item, w * scale)).collect();
+
+    let mut placed = Vec::with_capacity(areas.len());
+    let mut remaining = rect;
+    let mut start = 0;
+    while start < areas.len() {
+        let side = remaining.width().min(remaining.height());
+        let mut end = start + 1;
+        while end < areas.len() && worst(&areas[start..=end], side) <= worst(&areas[start..end], side) {
+            end += 1;
+        }
+        remaining = lay_row(&areas[start..end], remaining, &mut placed);
+        start = end;
+    }
+    placed
+}
+
+/// The largest aspect ratio among the tiles of a row laid along a side
+fn worst<T>(row: &[(T, f64)], side: f64) -> f64 {
+    let sum: f64 = row.iter().map(|(_, a)| a).sum();
+    let (min, max) = row
+        .iter()
+        .fold((f64::INFINITY, 0.0_f64), |(min, max), (_, a)| (min.min(*a), max.max(*a)));
+    (side * side * max / (sum * sum)).max(sum * sum / (side * side * min))
+}
+
+/// Place a row of tiles along the shorter side of a rectangle, returning the space left beside it
+fn lay_row<T: Copy>(row: &[(T, f64)], rect: Rect, placed: &mut Vec<(T, Rect)>) -> Rect {
+    let sum: f64 = row.iter().map(|(_, a)| a).sum();
+    if rect.width() >= rect.height() {
+        let width = sum / rect.height();
+        let mut y = rect.y0;
+        for (item, area) in row {
+            let height = area / width;
+            placed.push((*item, Rect { x0: rect.x0, y0: y, x1: rect.x0 + width, y1: y + height }));
+            y += height;
+        }
+        Rect { x0: rect.x0 + width, ..rect }
+    } else {
+        let height = sum / rect.width();
+        let mut x = rect.x0;
+        for (item, area) in row {
+            let width = area / height;
+            placed.push((*item, Rect { x0: x, y0: rect.y0, x1: x + width, y1: rect.y0 + height }));
+            x += width;
+        }
+        Rect { y0: rect.y0 + height, ..rect }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANVAS: Rect = Rect { x0: 0.0, y0: 0.0, x1: 6.0, y1: 4.0 };
+
+    fn text(values: &[&str]) -> VectorData {
+        VectorData::Categorical(values.iter().map(|v| v.to_string()).collect())
+    }
+
+    fn area(rect: &Rect) -> f64 {
+        rect.width() * rect.height()
+    }
+
+    fn tile<'a>(tiles: &'a [Tile], label: &str) -> &'a Tile {
+        tiles.iter().find(|t| t.label == label).unwrap()
+    }
+
+    #[test]
+    fn squarify_conserves_area() {
+        let items: Vec<(usize, f64)> = [6.0, 6.0, 4.0, 3.0, 2.0, 2.0, 1.0].iter().cloned().enumerate().collect();
+        let placed = squarify(&items, CANVAS);
+        assert_eq!(placed.len(), items.len());
+        for (i, ((item, rect), (_, weight))) in placed.iter().zip(&items).enumerate() {
+            assert_eq!(*item, i);
+            assert!((area(rect) - weight).abs() < 1e-9);
+            assert!(rect.x0 >= -1e-9 && rect.y0 >= -1e-9 && rect.x1 <= 6.0 + 1e-9 && rect.y1 <= 4.0 + 1e-9);
+        }
+        for (i, (_, a)) in placed.iter().enumerate() {
+            for (_, b) in &placed[i + 1..] {
+                let overlap = (a.x1.min(b.x1) - a.x0.max(b.x0)).max(0.0) * (a.y1.min(b.y1) - a.y0.max(b.y0)).max(0.0);
+                assert!(overlap < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn squarify_without_weight() {
+        assert!(squarify(&[(0, 0.0)], CANVAS).is_empty());
+        assert!(squarify::<usize>(&[], CANVAS).is_empty());
+    }
+
+    #[test]
+    fn zero_values_are_left_out() {
+        let value = Some(VectorData::Quantitative(vec![2.0, 0.0, f64::NAN, 1.0]));
+        let tiles = tiles(&text(&["a", "b", "c", "d"]), &text(&["", "", "", ""]), &value, &None, CANVAS);
+        let labels: Vec<&str> = tiles.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(labels, vec!["a", "d"]);
+        assert!((area(&tile(&tiles, "a").rect) - 16.0).abs() < 1e-9);
+        assert!((area(&tile(&tiles, "d").rect) - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parents_keep_room_for_their_own_value() {
+        let value = Some(VectorData::Quantitative(vec![3.0, 1.0]));
+        let tiles = tiles(&text(&["parent", "child"]), &text(&["", "parent"]), &value, &None, CANVAS);
+        let (parent, child) = (tile(&tiles, "parent"), tile(&tiles, "child"));
+        assert!(!parent.leaf && child.leaf);
+        assert_eq!((parent.depth, child.depth), (0, 1));
+        assert_eq!(parent.rect, CANVAS);
+        // The child gets a quarter of its parent, inside the padding
+        let inner = CANVAS.inset(0.04);
+        assert!((area(&child.rect) - area(&inner) / 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn leaves_count_one_without_values() {
+        let tiles = tiles(&text(&["root", "a", "b"]), &text(&["", "root", "root"]), &None, &None, CANVAS);
+        assert_eq!(tiles.len(), 3);
+        assert!((area(&tile(&tiles, "a").rect) - area(&tile(&tiles, "b").rect)).abs() < 1e-9);
+        assert_eq!(tile(&tiles, "root").color, TRUNK);
+        assert_eq!(tile(&tiles, "a").color, PALETTE[0]);
+    }
+
+    #[test]
+    fn self_parented_nodes_are_roots() {
+        let tiles = tiles(&text(&["a", "b"]), &text(&["a", ""]), &None, &None, CANVAS);
+        assert_eq!(tiles.len(), 2);
+        assert!(tiles.iter().all(|t| t.depth == 0 && t.leaf));
+    }
+
+    #[test]
+    fn duplicate_labels_and_cycles_terminate() {
+        // The last of the duplicated labels is the one children attach to
+        let tiles_of_duplicates = tiles(&text(&["a", "b", "a"]), &text(&["b", "a", ""]), &None, &None, CANVAS);
+        let depths: Vec<(&str, usize)> = tiles_of_duplicates.iter().map(|t| (t.label.as_str(), t.depth)).collect();
+        assert_eq!(depths, vec![("a", 0), ("b", 1), ("a", 2)]);
+        // Nodes which are each other's parents have no root to be reached from
+        assert!(tiles(&text(&["a", "b"]), &text(&["b", "a"]), &None, &None, CANVAS).is_empty());
+    }
+}
@@ -1,17 +1,54 @@
-use std::error::Error;
-use std::io::prelude::*;
+use std::io::{self, prelude::*};
 use std::fs::File;
 use std::path::Path;
+use std::process::Command;
 
-pub fn write_to_file<'a>(path: &Path, contents: &'a str) {
+use crate::error::ConspireError;
+
+pub fn write_to_file(path: &Path, contents: &str) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+/// Open a rendered file in the default viewer for its file type
+pub fn open_in_viewer(path: &Path) -> Result<(), ConspireError> {
     let display = path.display();
-    let mut file = match File::create(&path) {
-        Err(why) => panic!("Couldn't create {}: {}", display, why.description()),
-        Ok(file) => file,
+    let (program, output) = if cfg!(target_os = "windows") {
+        ("start", Command::new("cmd").args(["/C", &format!("start {}", display)]).output())
+    } else {
+        ("open", Command::new("sh").arg("-c").arg(format!("open {}", display)).output())
     };
 
-    match file.write_all(contents.as_bytes()) {
-        Err(why) => panic!("Couldn't write to {}: {}", display, why.description()),
-        Ok(_) => println!("Successfully wrote to {}", display),
+    let output = output.map_err(|error| viewer_error(program, error))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = match stderr.trim() {
+            "" => format!("exited with {}", output.status),
+            reason => reason.to_string(),
+        };
+        Err(viewer_error(program, io::Error::other(reason)))
+    }
+}
+
+/// Run an external program to completion, failing if it can't be started or exits unsuccessfully
+pub fn run_program(program: &str, args: &[&str]) -> Result<(), ConspireError> {
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|error| viewer_error(program, error))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(viewer_error(program, io::Error::other(format!("exited with {}", status))))
+    }
+}
+
+pub fn viewer_error(program: &str, error: io::Error) -> ConspireError {
+    ConspireError::Viewer {
+        program: program.to_string(),
+        error,
     }
 }
@@ -0,0 +1,865 @@
+use super::scale::{
+    contour_lines, dimension_scale, flat, heatmap_cells, layer_name, levels, line_runs, pie_slices, point_colors,
+    subset, violin_shapes, ColorMap, Rgb, Scale, PALETTE,
+};
+use super::treemap::{tiles, Rect};
+use super::Renderable;
+use crate::data::VectorData;
+use crate::error::ConspireError;
+use crate::stats::{histogram, violins, Histogram, ViolinInner, Whiskers};
+use crate::Plot;
+
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+type Result<T> = std::result::Result<T, ConspireError>;
+
+const SCHEMA: &str = "https://vega.github.io/schema/vega-lite/v5.json";
+
+/// Serializes plots into a Vega-Lite specification, with one Vega-Lite layer per Conspire layer
+pub struct VegaLite {}
+
+impl VegaLite {
+    pub(crate) fn spec(&self, data: &[Plot]) -> Result<String> {
+        flat("vega-lite", data, |p| !matches!(p, Plot::Surface { .. }))?;
+        let layers: Vec<String> = data.iter().enumerate().map(|(i, plot)| layer(i, plot)).collect();
+        Ok(format!(
+            "{{\n  \"$schema\": {},\n  \"width\": 600,\n  \"height\": 400,\n  \"layer\": [\n    {}\n  ]\n}}\n",
+            string(SCHEMA),
+            layers.join(",\n    ")
+        ))
+    }
+
+    fn html(spec: &str) -> String {
+        format!(
+            r#"<head>
+    <script src="https://cdn.jsdelivr.net/npm/vega@5"></script>
+    <script src="https://cdn.jsdelivr.net/npm/vega-lite@5"></script>
+    <script src="https://cdn.jsdelivr.net/npm/vega-embed@6"></script>
+    </head>
+    <body>
+    <div id="vis"></div>
+    <script>
+    vegaEmbed('#vis', {});
+    </script>
+    </body>
+    "#,
+            spec
+        )
+    }
+}
+
+impl Renderable for VegaLite {
+    fn render(&self, data: &[Plot], display: bool) -> Result<()> {
+        use super::util::{open_in_viewer, write_to_file};
+        use std::path::Path;
+
+        let spec = self.spec(data)?;
+        write_to_file(Path::new("render.vl.json"), &spec)?;
+
+        // The spec itself can't be viewed directly, so it's embedded in a page using vega-embed
+        if display {
+            let path = Path::new("render.html");
+            write_to_file(path, &VegaLite::html(&spec))?;
+            open_in_viewer(path)?;
+        }
+        Ok(())
+    }
+
+    fn write(&self, data: &[Plot], writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(self.spec(data)?.as_bytes())?;
+        Ok(())
+    }
+
+    fn html_fragment(&self, data: &[Plot]) -> Result<String> {
+        static FRAGMENTS: AtomicUsize = AtomicUsize::new(0);
+        let div = format!("conspire-vega-{}", FRAGMENTS.fetch_add(1, Ordering::Relaxed));
+        Ok(format!(
+            r#"<div id="{div}"></div>
+<script src="https://cdn.jsdelivr.net/npm/vega@5"></script>
+<script src="https://cdn.jsdelivr.net/npm/vega-lite@5"></script>
+<script src="https://cdn.jsdelivr.net/npm/vega-embed@6"></script>
+<script>vegaEmbed('#{div}', {spec});</script>"#,
+            div = div,
+            spec = self.spec(data)?
+        ))
+    }
+}
+
+/// A single Vega-Lite layer, with its own inline data, mark and encoding
+fn layer(idx: usize, plot: &Plot) -> String {
+    let (mark, columns, encoding) = match plot {
+        Plot::Scatter { x, y, color, size, name } => (
+            r#"{"type": "point", "filled": true}"#,
+            vec![("x", Some(*x)), ("y", Some(*y)), ("color", color.as_ref()), ("size", size.as_ref())],
+            vec![
+                field("x", x),
+                field("y", y),
+                color_encoding(idx, color, name),
+                size.as_ref().map(|s| format!("\"size\": {}", field_definition("size", s))),
+            ],
+        ),
+        Plot::Line { x, y, missing, color, size, name } => {
+            // Vega-Lite versions differ in whether lines break at null values, so the missing points are left out
+            // here, and each run between gaps is numbered to be drawn as a line of its own
+            let runs = line_runs(x, y, *missing);
+            let points = runs.concat();
+            let run = VectorData::Quantitative(
+                runs.iter()
+                    .enumerate()
+                    .flat_map(|(r, run)| run.iter().map(move |_| r as f64))
+                    .collect(),
+            );
+            let (x, y) = (subset(x, &points), subset(y, &points));
+            let color = color.as_ref().map(|c| subset(c, &points));
+            let size = size.as_ref().map(|s| subset(s, &points));
+            let columns = vec![
+                ("x", Some(&x)),
+                ("y", Some(&y)),
+                ("color", color.as_ref()),
+                ("size", size.as_ref()),
+                ("run", Some(&run)),
+            ];
+            let encoding = vec![
+                field("x", &x),
+                field("y", &y),
+                color_encoding(idx, &color, name),
+                size.as_ref().map(|s| format!("\"strokeWidth\": {}", field_definition("size", s))),
+                Some(String::from(r#""detail": {"field": "run", "type": "nominal"}"#)),
+            ];
+            return inline_layer(r#"{"type": "line"}"#, &columns, encoding);
+        }
+        Plot::Bar { x, y, color, name } | Plot::HorizontalBar { x, y, color, name } => (
+            r#"{"type": "bar"}"#,
+            vec![("x", Some(*x)), ("y", Some(*y)), ("color", color.as_ref())],
+            vec![field("x", x), field("y", y), color_encoding(idx, color, name)],
+        ),
+        Plot::Box {
+            x,
+            y,
+            options,
+            color,
+            name,
+        } => {
+            // Vega-Lite's boxplot mark has no notches, so those are left out. The mean is a dashed tick on a layer of
+            // its own, and color groups are placed side by side with an offset along the category axis.
+            let extent = match options.whiskers {
+                Whiskers::Tukey => "1.5",
+                Whiskers::MinMax => "\"min-max\"",
+            };
+            let (position, value, offset) = match y {
+                Some(_) => (field("x", x), "y", "xOffset"),
+                None => (Some(format!("\"y\": {{\"datum\": {}}}", string(&layer_name(idx, name)))), "x", "yOffset"),
+            };
+            let offset = match color {
+                Some(groups @ (VectorData::Categorical(c) | VectorData::Ordinal { values: c, .. })) if c.len() > 1 => {
+                    Some(format!("\"{}\": {}", offset, field_definition("color", groups)))
+                }
+                _ => None,
+            };
+            let encoding = |aggregate: &str| -> String {
+                let value = Some(format!(
+                    "\"{v}\": {{\"field\": \"{v}\", \"type\": \"quantitative\"{}}}",
+                    aggregate,
+                    v = value
+                ));
+                let channels = vec![position.clone(), value, color_encoding(idx, color, name), offset.clone()];
+                channels.into_iter().flatten().collect::<Vec<String>>().join(", ")
+            };
+
+            let boxes = format!(
+                "{{\"mark\": {{\"type\": \"boxplot\", \"extent\": {}}}, \"encoding\": {{{}}}}}",
+                extent,
+                encoding("")
+            );
+            let mean = if options.mean {
+                format!(
+                    ", {{\"mark\": {{\"type\": \"tick\", \"strokeDash\": [4, 3]}}, \"encoding\": {{{}}}}}",
+                    encoding(", \"aggregate\": \"mean\"")
+                )
+            } else {
+                String::new()
+            };
+            let columns = vec![("x", Some(*x)), ("y", y.as_ref()), ("color", color.as_ref())];
+            return format!(
+                "{{\"data\": {{\"values\": [{}]}}, \"layer\": [{}{}]}}",
+                rows(&columns).join(", "),
+                boxes,
+                mean
+            );
+        }
+        Plot::Pie { x, .. } => {
+            let slices = pie_slices(x);
+            let values: Vec<String> = slices
+                .iter()
+                .map(|(label, value)| format!("{{\"label\": {}, \"value\": {}}}", string(label), number(*value)))
+                .collect();
+            return format!(
+                "{{\"data\": {{\"values\": [{}]}}, \"mark\": {{\"type\": \"arc\"}}, \"encoding\": {{{}}}}}",
+                values.join(", "),
+                r#""theta": {"field": "value", "type": "quantitative"}, "color": {"field": "label", "type": "nominal", "sort": null}"#
+            );
+        }
+        Plot::Histogram { x, binning, color, name } => {
+            // The bins are computed by Conspire, so they match the other backends rather than Vega-Lite's own binning
+            let (values, position) = match histogram(x, *binning) {
+                Histogram::Bins(bins) => {
+                    let (edge, kind): (fn(f64) -> String, _) = match x {
+                        VectorData::Temporal(_) => (timestamp, TEMPORAL),
+                        _ => (number, r#""type": "quantitative""#),
+                    };
+                    (
+                        bins.iter()
+                            .map(|b| {
+                                format!(
+                                    "{{\"start\": {}, \"end\": {}, \"count\": {}}}",
+                                    edge(b.start),
+                                    edge(b.end),
+                                    b.count
+                                )
+                            })
+                            .collect::<Vec<String>>(),
+                        format!(r#""x": {{"field": "start", {}, "title": "x"}}, "x2": {{"field": "end"}}"#, kind),
+                    )
+                }
+                Histogram::Counts(counts) => (
+                    counts
+                        .iter()
+                        .map(|(c, n)| format!("{{\"x\": {}, \"count\": {}}}", string(c), n))
+                        .collect(),
+                    format!(
+                        r#""x": {{"field": "x", "type": "{}", "sort": null}}"#,
+                        if let VectorData::Ordinal { .. } = x { "ordinal" } else { "nominal" }
+                    ),
+                ),
+            };
+            let color = match color {
+                Some(VectorData::Categorical(c)) if c.len() == 1 => format!("{{\"value\": {}}}", string(&c[0])),
+                _ => format!("{{\"datum\": {}}}", string(&layer_name(idx, name))),
+            };
+            let encoding = format!(
+                r#"{}, "y": {{"field": "count", "type": "quantitative"}}, "color": {}"#,
+                position, color
+            );
+            return format!(
+                "{{\"data\": {{\"values\": [{}]}}, \"mark\": {{\"type\": \"bar\"}}, \"encoding\": {{{}}}}}",
+                values.join(", "),
+                encoding
+            );
+        }
+        Plot::Violin {
+            x,
+            group,
+            bandwidth,
+            inner,
+            color,
+            name,
+        } => {
+            // The densities are estimated by Conspire, so they match the other backends. Vega-Lite can't offset an
+            // area from a band, so each group is placed at its index along a quantitative y axis, labelled with the
+            // names of the groups.
+            let name = layer_name(idx, name);
+            let groups: Vec<String> = violins(x, group, &name, *bandwidth).into_iter().map(|v| v.group).collect();
+            let scale = Scale::Band(groups.clone());
+            let shapes = violin_shapes(x, group, &name, *bandwidth, &scale);
+            // Like a histogram, the whole layer gets a single color, and a legend entry unless the color is given
+            let c = match color {
+                Some(VectorData::Categorical(c)) if c.len() == 1 => format!("{{\"value\": {}}}", string(&c[0])),
+                _ => format!("{{\"datum\": {}}}", string(&name)),
+            };
+
+            let mut outline = Vec::new();
+            let mut quartiles = Vec::new();
+            let mut boxes = Vec::new();
+            for shape in &shapes {
+                let group = string(&shape.violin.group);
+                for (value, _) in &shape.violin.density {
+                    let (low, high) = shape.span(*value);
+                    outline.push(format!(
+                        "{{\"group\": {}, \"value\": {}, \"low\": {}, \"high\": {}}}",
+                        group,
+                        number(*value),
+                        number(low),
+                        number(high)
+                    ));
+                }
+                let stats = &shape.violin.stats;
+                for (quartile, dashed) in &[(stats.q1, true), (stats.median, false), (stats.q3, true)] {
+                    let (low, high) = shape.span(*quartile);
+                    quartiles.push(format!(
+                        "{{\"value\": {}, \"low\": {}, \"high\": {}, \"dashed\": {}}}",
+                        number(*quartile),
+                        number(low),
+                        number(high),
+                        dashed
+                    ));
+                }
+                boxes.push(format!(
+                    "{{\"center\": {}, \"low\": {}, \"high\": {}, \"lower\": {}, \"q1\": {}, \"median\": {}, \
+                     \"q3\": {}, \"upper\": {}}}",
+                    number(shape.center),
+                    number(shape.center - shape.box_half()),
+                    number(shape.center + shape.box_half()),
+                    number(stats.lower_whisker),
+                    number(stats.q1),
+                    number(stats.median),
+                    number(stats.q3),
+                    number(stats.upper_whisker)
+                ));
+            }
+
+            let labels: Vec<String> = groups.iter().map(|g| string(g)).collect();
+            let positions: Vec<String> = (0..groups.len()).map(|p| p.to_string()).collect();
+            let y_scale = format!(
+                r#""scale": {{"domain": [-0.5, {}]}}, "axis": {{"values": [{}], "labelExpr": {}}}"#,
+                number(groups.len() as f64 - 0.5),
+                positions.join(", "),
+                string(&format!("[{}][datum.value]", labels.join(", ")))
+            );
+            let areas = format!(
+                concat!(
+                    r#"{{"data": {{"values": [{}]}}, "#,
+                    r#""mark": {{"type": "area", "orient": "vertical", "opacity": 0.5}}, "#,
+                    r#""encoding": {{"x": {{"field": "value", "type": "quantitative", "title": "x"}}, "#,
+                    r#""y": {{"field": "high", "type": "quantitative", "title": null, {}}}, "#,
+                    r#""y2": {{"field": "low"}}, "#,
+                    r#""detail": {{"field": "group"}}, "color": {}}}}}"#
+                ),
+                outline.join(", "),
+                y_scale,
+                c
+            );
+            let inner = match inner {
+                ViolinInner::Box => format!(
+                    concat!(
+                        r#", {{"data": {{"values": [{values}]}}, "layer": ["#,
+                        r#"{{"mark": {{"type": "rule"}}, "#,
+                        r#""encoding": {{"x": {{"field": "lower", "type": "quantitative"}}, "#,
+                        r#""x2": {{"field": "upper"}}, "#,
+                        r#""y": {{"field": "center", "type": "quantitative"}}, "color": {c}}}}}, "#,
+                        r#"{{"mark": {{"type": "rect"}}, "#,
+                        r#""encoding": {{"x": {{"field": "q1", "type": "quantitative"}}, "#,
+                        r#""x2": {{"field": "q3"}}, "y": {{"field": "low", "type": "quantitative"}}, "#,
+                        r#""y2": {{"field": "high"}}, "color": {c}}}}}, "#,
+                        r#"{{"mark": {{"type": "rule", "color": "white", "strokeWidth": 2}}, "#,
+                        r#""encoding": {{"x": {{"field": "median", "type": "quantitative"}}, "#,
+                        r#""y": {{"field": "low", "type": "quantitative"}}, "y2": {{"field": "high"}}}}}}]}}"#
+                    ),
+                    values = boxes.join(", "),
+                    c = c
+                ),
+                ViolinInner::Quartiles => format!(
+                    concat!(
+                        r#", {{"data": {{"values": [{}]}}, "mark": {{"type": "rule"}}, "#,
+                        r#""encoding": {{"x": {{"field": "value", "type": "quantitative"}}, "#,
+                        r#""y": {{"field": "low", "type": "quantitative"}}, "y2": {{"field": "high"}}, "color": {}, "#,
+                        r#""strokeDash": {{"field": "dashed", "type": "nominal", "legend": null, "#,
+                        r#""scale": {{"domain": [true, false], "range": [[4, 3], [1, 0]]}}}}}}}}"#
+                    ),
+                    quartiles.join(", "),
+                    c
+                ),
+                ViolinInner::None => String::new(),
+            };
+            return format!("{{\"layer\": [{}{}]}}", areas, inner);
+        }
+        Plot::Treemap {
+            label,
+            parent,
+            value,
+            color,
+            ..
+        } => {
+            // Vega-Lite has no treemap mark, so the tiles are laid out by Conspire and drawn as rectangles, with a
+            // text layer labelling the leaves
+            let rect = Rect { x0: 0.0, y0: 0.0, x1: 600.0, y1: 400.0 };
+            let values: Vec<String> = tiles(label, parent, value, color, rect)
+                .iter()
+                .map(|t| {
+                    let Rect { x0, y0, x1, y1 } = t.rect;
+                    format!(
+                        "{{\"label\": {}, \"leaf\": {}, \"x0\": {}, \"x1\": {}, \"y0\": {}, \"y1\": {}, \"cx\": {}, \"cy\": {}, \
+                         \"color\": {}, \"text\": {}}}",
+                        string(&t.label),
+                        t.leaf,
+                        number(x0),
+                        number(x1),
+                        number(400.0 - y0),
+                        number(400.0 - y1),
+                        number((x0 + x1) / 2.0),
+                        number(400.0 - (y0 + y1) / 2.0),
+                        string(&t.color.hex()),
+                        string(&t.text_color().hex())
+                    )
+                })
+                .collect();
+            let position = concat!(
+                r#""x": {"field": "x0", "type": "quantitative", "axis": null, "scale": {"domain": [0, 600]}}, "#,
+                r#""x2": {"field": "x1"}, "#,
+                r#""y": {"field": "y0", "type": "quantitative", "axis": null, "scale": {"domain": [0, 400]}}, "#,
+                r#""y2": {"field": "y1"}"#
+            );
+            let tiles = format!(
+                r#"{{"mark": {{"type": "rect", "stroke": "white"}}, "encoding": {{{}, {}}}}}"#,
+                position, r#""color": {"field": "color", "type": "nominal", "scale": null}"#
+            );
+            let labels = format!(
+                r#"{{"transform": [{{"filter": "datum.leaf"}}], "mark": {{"type": "text"}}, "encoding": {{{}}}}}"#,
+                concat!(
+                    r#""x": {"field": "cx", "type": "quantitative"}, "y": {"field": "cy", "type": "quantitative"}, "#,
+                    r#""text": {"field": "label"}, "color": {"field": "text", "type": "nominal", "scale": null}"#
+                )
+            );
+            return format!(
+                "{{\"data\": {{\"values\": [{}]}}, \"layer\": [{}, {}]}}",
+                values.join(", "),
+                tiles,
+                labels
+            );
+        }
+        Plot::ParallelCoordinates { dimensions, color, .. } => {
+            // The values are scaled onto each dimension's axis by Conspire, as Vega-Lite can't give each position
+            // along the x axis a scale of its own. The axes are drawn as rules, with their ticks as text.
+            let scales: Vec<Scale> = dimensions.iter().map(|(_, data)| dimension_scale(data)).collect();
+            let units: Vec<Vec<f64>> = dimensions.iter().zip(&scales).map(|((_, d), s)| s.units(d)).collect();
+            let len = units.first().map_or(0, |u| u.len());
+            let colors = point_colors(color, len, PALETTE[idx % PALETTE.len()]);
+            let mut values = Vec::new();
+            for (p, c) in colors.iter().enumerate() {
+                for ((name, _), u) in dimensions.iter().zip(&units) {
+                    values.push(format!(
+                        "{{\"line\": {}, \"dimension\": {}, \"unit\": {}, \"color\": {}}}",
+                        p,
+                        string(name),
+                        number(u[p]),
+                        string(&c.hex())
+                    ));
+                }
+            }
+            let mut ticks = Vec::new();
+            for ((name, _), scale) in dimensions.iter().zip(&scales) {
+                for tick in scale.ticks(5) {
+                    ticks.push(format!(
+                        "{{\"dimension\": {}, \"unit\": {}, \"label\": {}}}",
+                        string(name),
+                        number(tick.position),
+                        string(&tick.label)
+                    ));
+                }
+            }
+            let axes: Vec<String> = dimensions
+                .iter()
+                .map(|(name, _)| format!("{{\"dimension\": {}}}", string(name)))
+                .collect();
+
+            let x = r#""x": {"field": "dimension", "type": "ordinal", "sort": null, "title": null}"#;
+            let y = r#""y": {"field": "unit", "type": "quantitative", "axis": null, "scale": {"domain": [0, 1]}}"#;
+            let lines = format!(
+                r#"{{"data": {{"values": [{}]}}, "mark": {{"type": "line", "opacity": 0.7}}, "encoding": {{{}, {}, {}}}}}"#,
+                values.join(", "),
+                x,
+                y,
+                r#""detail": {"field": "line"}, "color": {"field": "color", "type": "nominal", "scale": null}"#
+            );
+            let rules = format!(
+                r#"{{"data": {{"values": [{}]}}, "mark": {{"type": "rule", "color": "black"}}, "encoding": {{{}}}}}"#,
+                axes.join(", "),
+                x
+            );
+            let labels = format!(
+                r#"{{"data": {{"values": [{}]}}, "mark": {{"type": "text", "align": "right", "dx": -4}}, "encoding": {{{}, {}, {}}}}}"#,
+                ticks.join(", "),
+                x,
+                y,
+                r#""text": {"field": "label"}"#
+            );
+            return format!("{{\"layer\": [{}, {}, {}]}}", lines, rules, labels);
+        }
+        Plot::Heatmap { x, y, z, options, .. } => {
+            // Categorical ticks are nominal axes, while other cells span from one edge to the other on a quantitative
+            // axis, so uneven grids keep their spacing. Rows count upwards from the bottom, like the other backends.
+            let map = ColorMap::heatmap(z, options);
+            let cells = heatmap_cells(x, y, z, options);
+            let mut values = Vec::new();
+            for cell in &cells {
+                let mut fields = Vec::new();
+                for (channel, ticks, index, (start, end)) in &[
+                    ("x", x, cell.column, (cell.left, cell.right)),
+                    ("y", y, cell.row, (cell.bottom, cell.top)),
+                ] {
+                    match ticks {
+                        Some(VectorData::Categorical(c) | VectorData::Ordinal { values: c, .. }) => {
+                            fields.push(format!("\"{}\": {}", channel, string(&c[*index])))
+                        }
+                        Some(VectorData::Temporal(_)) => fields.push(format!(
+                            "\"{c}\": {}, \"{c}2\": {}, \"{c}_center\": {}",
+                            timestamp(*start),
+                            timestamp(*end),
+                            timestamp((start + end) / 2.0),
+                            c = channel
+                        )),
+                        _ => fields.push(format!(
+                            "\"{c}\": {}, \"{c}2\": {}, \"{c}_center\": {}",
+                            number(*start),
+                            number(*end),
+                            number((start + end) / 2.0),
+                            c = channel
+                        )),
+                    }
+                }
+                fields.push(format!("\"z\": {}", number(cell.value)));
+                if let Some(text) = &cell.text {
+                    fields.push(format!("\"text\": {}", string(text)));
+                    fields.push(format!("\"text_color\": {}", string(&map.color(cell.value).contrast().hex())));
+                }
+                values.push(format!("{{{}}}", fields.join(", ")));
+            }
+
+            // The cells span their edges, while their annotations are placed at their centers
+            let position = |centered: bool| -> String {
+                let mut encoding = Vec::new();
+                for (channel, ticks, descending) in &[("x", x, false), ("y", y, true)] {
+                    match ticks {
+                        Some(data @ (VectorData::Categorical(_) | VectorData::Ordinal { .. })) => {
+                            let mut order: Vec<String> = levels(data).iter().map(|c| string(c)).collect();
+                            if *descending {
+                                order.reverse();
+                            }
+                            encoding.push(format!(
+                                "\"{c}\": {{\"field\": \"{c}\", \"type\": \"nominal\", \"sort\": [{}]}}",
+                                order.join(", "),
+                                c = channel
+                            ));
+                        }
+                        _ => {
+                            let kind = match ticks {
+                                Some(VectorData::Temporal(_)) => TEMPORAL,
+                                _ => r#""type": "quantitative""#,
+                            };
+                            if centered {
+                                encoding.push(format!("\"{c}\": {{\"field\": \"{c}_center\", {}}}", kind, c = channel))
+                            } else {
+                                encoding.push(format!(
+                                    r#""{c}": {{"field": "{c}", {}}}, "{c}2": {{"field": "{c}2"}}"#,
+                                    kind,
+                                    c = channel
+                                ))
+                            }
+                        }
+                    }
+                }
+                encoding.join(", ")
+            };
+            let color = color_scale(&map, "z");
+            let cells_layer = format!("{{\"mark\": \"rect\", \"encoding\": {{{}, {}}}}}", position(false), color);
+            let text_layer = if cells.iter().any(|c| c.text.is_some()) {
+                format!(
+                    ", {{\"mark\": \"text\", \"encoding\": {{{}, {}}}}}",
+                    position(true),
+                    r#""text": {"field": "text"}, "color": {"field": "text_color", "type": "nominal", "scale": null}"#
+                )
+            } else {
+                String::new()
+            };
+            return format!(
+                "{{\"data\": {{\"values\": [{}]}}, \"layer\": [{}{}]}}",
+                values.join(", "),
+                cells_layer,
+                text_layer
+            );
+        }
+        Plot::Contour { x, y, z, options, .. } => {
+            // Each segment is a line of its own, between two points on the same level
+            let map = ColorMap::grid(z, &options.colorscale);
+            let position = |channel: &str, ticks: &Option<VectorData>, value: f64| match ticks {
+                Some(VectorData::Temporal(_)) => format!("\"{}\": {}", channel, timestamp(value)),
+                _ => format!("\"{}\": {}", channel, number(value)),
+            };
+            let mut values = Vec::new();
+            for line in contour_lines(x, y, z, options) {
+                for [from, to] in &line.segments {
+                    for (px, py) in &[from, to] {
+                        values.push(format!(
+                            "{{{}, {}, \"level\": {}, \"segment\": {}}}",
+                            position("x", x, *px),
+                            position("y", y, *py),
+                            number(line.level),
+                            values.len() / 2
+                        ));
+                    }
+                }
+            }
+            let kind = |ticks: &Option<VectorData>| match ticks {
+                Some(VectorData::Temporal(_)) => TEMPORAL,
+                _ => r#""type": "quantitative""#,
+            };
+            let encoding = [
+                format!("\"x\": {{\"field\": \"x\", {}}}", kind(x)),
+                format!("\"y\": {{\"field\": \"y\", {}}}", kind(y)),
+                color_scale(&map, "level"),
+                String::from(r#""detail": {"field": "segment", "type": "nominal"}"#),
+            ];
+            return format!(
+                "{{\"data\": {{\"values\": [{}]}}, \"mark\": {{\"type\": \"line\"}}, \"encoding\": {{{}}}}}",
+                values.join(", "),
+                encoding.join(", ")
+            );
+        }
+        Plot::Surface { .. } => unreachable!("surfaces are rejected before drawing"),
+    };
+
+    inline_layer(mark, &columns, encoding)
+}
+
+/// A color encoding of a quantitative field through a color map, with each color of the scale pinned to the value at
+/// its position
+fn color_scale(map: &ColorMap, field: &str) -> String {
+    let domain: Vec<String> = map
+        .stops
+        .iter()
+        .map(|(t, _)| number(map.min + t * (map.max - map.min)))
+        .collect();
+    let range: Vec<String> = map.stops.iter().map(|(_, c)| string(&c.hex())).collect();
+    format!(
+        "\"color\": {{\"field\": {}, \"type\": \"quantitative\", \"scale\": {{\"domain\": [{}], \"range\": [{}]}}}}",
+        string(field),
+        domain.join(", "),
+        range.join(", ")
+    )
+}
+
+/// A layer drawing a single mark from its own inline data, leaving out the channels without an encoding
+fn inline_layer(mark: &str, columns: &[(&str, Option<&VectorData>)], encoding: Vec<Option<String>>) -> String {
+    let encoding: Vec<String> = encoding.into_iter().flatten().collect();
+    format!(
+        "{{\"data\": {{\"values\": [{}]}}, \"mark\": {}, \"encoding\": {{{}}}}}",
+        rows(columns).join(", "),
+        mark,
+        encoding.join(", ")
+    )
+}
+
+/// Inline data rows, one object per data point. Channels holding a single value are repeated on every row.
+fn rows(columns: &[(&str, Option<&VectorData>)]) -> Vec<String> {
+    let len = columns
+        .iter()
+        .filter_map(|(_, data)| data.map(|d| d.len()))
+        .max()
+        .unwrap_or(0);
+
+    (0..len)
+        .map(|i| {
+            let fields: Vec<String> = columns
+                .iter()
+                .filter_map(|(name, data)| data.map(|d| (name, d)))
+                .filter_map(|(name, data)| {
+                    let i = if data.len() == 1 { 0 } else { i };
+                    let value = match data {
+                        VectorData::Quantitative(v) => v.get(i).map(|v| number(*v)),
+                        VectorData::Categorical(v) | VectorData::Ordinal { values: v, .. } => {
+                            v.get(i).map(|v| string(v))
+                        }
+                        VectorData::Temporal(v) => v.get(i).map(|v| timestamp(*v)),
+                    };
+                    value.map(|v| format!("{}: {}", string(name), v))
+                })
+                .collect();
+            format!("{{{}}}", fields.join(", "))
+        })
+        .collect()
+}
+
+fn field(channel: &str, data: &VectorData) -> Option<String> {
+    Some(format!("{}: {}", string(channel), field_definition(channel, data)))
+}
+
+/// Encode a field, with its type taken from the kind of data. Points in time are shown in UTC, like the other
+/// backends, and ordinal categories are sorted in their order.
+fn field_definition(name: &str, data: &VectorData) -> String {
+    match data {
+        VectorData::Temporal(_) => format!("{{\"field\": {}, {}}}", string(name), TEMPORAL),
+        VectorData::Ordinal { order, .. } => {
+            let order: Vec<String> = order.iter().map(|c| string(c)).collect();
+            format!("{{\"field\": {}, \"type\": \"ordinal\", \"sort\": [{}]}}", string(name), order.join(", "))
+        }
+        _ => format!("{{\"field\": {}, \"type\": {}}}", string(name), string(data_type(data))),
+    }
+}
+
+fn data_type(data: &VectorData) -> &'static str {
+    match data {
+        VectorData::Quantitative(_) => "quantitative",
+        VectorData::Categorical(_) => "nominal",
+        VectorData::Temporal(_) => "temporal",
+        VectorData::Ordinal { .. } => "ordinal",
+    }
+}
+
+/// The type and scale of a field holding points in time, as milliseconds since the Unix epoch
+const TEMPORAL: &str = r#""type": "temporal", "scale": {"type": "utc"}"#;
+
+/// A point in time as milliseconds since the Unix epoch, which is how Vega-Lite reads numeric dates
+fn timestamp(value: f64) -> String {
+    number(value * 1000.0)
+}
+
+/// Encode the color channel. Categorical colors naming actual colors are used as-is, and layers without a color
+/// channel get a legend entry with their name.
+fn color_encoding(idx: usize, color: &Option<VectorData>, name: &Option<String>) -> Option<String> {
+    let encoding = match color {
+        Some(VectorData::Categorical(v)) if v.iter().all(|c| Rgb::parse(c).is_some()) => {
+            r#"{"field": "color", "type": "nominal", "scale": null}"#.to_string()
+        }
+        Some(color) => field_definition("color", color),
+        None => format!("{{\"datum\": {}}}", string(&layer_name(idx, name))),
+    };
+    Some(format!("\"color\": {}", encoding))
+}
+
+/// A JSON number, with non-finite values (which JSON can't represent) as null
+fn number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        String::from("null")
+    }
+}
+
+/// A JSON string literal, with `</` escaped so that it can't close the HTML script element it's embedded in
+fn string(text: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            '/' if literal.ends_with('<') => literal.push_str("\\/"),
+            c if (c as u32) < 0x20 => literal.push_str(&format!("\\u{:04x}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{string, VegaLite};
+    use crate::error::ConspireError;
+    use crate::{BoxOptions, ContourOptions, HeatmapOptions, Layer, Missing, Ordinal, Plot, Whiskers};
+    use serde_json::Value;
+
+    fn spec(data: &[Plot]) -> Value {
+        serde_json::from_str(&VegaLite {}.spec(data).unwrap()).unwrap()
+    }
+
+    /// Every value stored under `key`, anywhere in the specification
+    fn find<'a>(value: &'a Value, key: &str, found: &mut Vec<&'a Value>) {
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map {
+                    if k == key {
+                        found.push(v);
+                    }
+                    find(v, key, found);
+                }
+            }
+            Value::Array(values) => values.iter().for_each(|v| find(v, key, found)),
+            _ => {}
+        }
+    }
+
+    fn values<'a>(spec: &'a Value, key: &str) -> Vec<&'a Value> {
+        let mut found = Vec::new();
+        find(spec, key, &mut found);
+        found
+    }
+
+    fn numbers(values: &[&Value]) -> Vec<f64> {
+        values.iter().map(|v| v.as_f64().unwrap()).collect()
+    }
+
+    #[test]
+    fn lines_are_split_into_runs_at_gaps() {
+        let layer = Layer::new().x(vec![1.0, 2.0, 3.0, 4.0]).y(vec![Some(1.0), None, Some(3.0), Some(4.0)]);
+
+        let skipped = spec(&[Plot::line_with(&layer, Missing::Skip)]);
+        assert_eq!(numbers(&values(&skipped, "run")), vec![0.0, 1.0, 1.0]);
+
+        let joined = spec(&[Plot::line_with(&layer, Missing::Interpolate)]);
+        assert_eq!(numbers(&values(&joined, "run")), vec![0.0; 3]);
+    }
+
+    #[test]
+    fn box_extent() {
+        let layer = Layer::new().x(vec!["a", "a", "a"]).y(vec![1.0, 2.0, 3.0]);
+        let extent = |whiskers| {
+            let options = BoxOptions { whiskers, ..Default::default() };
+            values(&spec(&[Plot::boxplot_with(&layer, options)]), "extent")[0].clone()
+        };
+        assert_eq!(extent(Whiskers::Tukey), Value::from(1.5));
+        assert_eq!(extent(Whiskers::MinMax), Value::from("min-max"));
+    }
+
+    #[test]
+    fn heatmap_color_domain() {
+        let layer = Layer::new().z(vec![vec![1.0, 2.0], vec![3.0, 5.0]]);
+        let options = HeatmapOptions { zmin: Some(0.0), ..Default::default() };
+        let spec = spec(&[Plot::heatmap_with(&layer, options)]);
+        let color = values(&spec, "color").into_iter().find(|c| c.get("scale").is_some()).unwrap();
+        let domain: Vec<&Value> = color["scale"]["domain"].as_array().unwrap().iter().collect();
+        let domain = numbers(&domain);
+        assert_eq!((domain[0], domain[domain.len() - 1]), (0.0, 5.0));
+    }
+
+    #[test]
+    fn ordinal_sort_keeps_the_given_order() {
+        let layer = Layer::new()
+            .x(Ordinal::new(vec!["high", "low", "medium"], vec!["low", "medium", "high"]))
+            .y(vec![3, 1, 2]);
+        let bars = spec(&[Plot::bar(&layer)]);
+        let sort = values(&bars, "sort");
+        assert_eq!(sort[0], &serde_json::json!(["low", "medium", "high"]));
+
+        // Categories missing from the order are sorted after it, while ones without any values keep their place
+        let layer = Layer::new()
+            .x(vec![1, 2, 3])
+            .y(Ordinal::new(vec!["extreme", "low", "high"], vec!["low", "medium", "high"]));
+        let bars = spec(&[Plot::horizontal_bar(&layer)]);
+        let sort = values(&bars["layer"][0]["encoding"]["y"], "sort");
+        assert_eq!(sort, vec![&serde_json::json!(["low", "medium", "high", "extreme"])]);
+    }
+
+    #[test]
+    fn strings_cannot_close_the_script_element() {
+        assert_eq!(string("a</script>\"\u{1}"), r#""a<\/script>\"\u0001""#);
+
+        let layer = Layer::new().x(vec!["</script>"]).y(vec![1.0]).name("</b>");
+        let spec = VegaLite {}.spec(&[Plot::bar(&layer)]).unwrap();
+        assert!(!spec.contains("</"));
+        let parsed: Value = serde_json::from_str(&spec).unwrap();
+        assert!(values(&parsed, "x").contains(&&Value::from("</script>")));
+    }
+
+    #[test]
+    fn contour_segments() {
+        let layer = Layer::new().z(vec![vec![0.0, 4.0], vec![0.0, 4.0]]).x(vec![10.0, 20.0]);
+        let options = ContourOptions { levels: 1, ..Default::default() };
+        let spec = spec(&[Plot::contour_with(&layer, options)]);
+        let points = &spec["layer"][0]["data"]["values"];
+        let column = |key: &str| numbers(&values(points, key));
+        assert_eq!(column("x"), vec![15.0, 15.0]);
+        assert_eq!(column("y"), vec![0.0, 1.0]);
+        assert_eq!(column("level"), vec![2.0, 2.0]);
+        assert_eq!(column("segment"), vec![0.0, 0.0]);
+        assert_eq!(spec["layer"][0]["encoding"]["detail"]["field"], "segment");
+
+        let surface = VegaLite {}.spec(&[Plot::surface(&layer)]);
+        match surface {
+            Err(ConspireError::UnsupportedPlot { backend: "vega-lite", plot: "surface" }) => {}
+            other => panic!("expected surfaces to be unsupported, got {:?}", other),
+        }
+    }
+}
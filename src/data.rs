@@ -1,12 +1,36 @@
-use core::fmt::Debug;
+use core::fmt::{Debug, Display};
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2};
+use std::collections::VecDeque;
+use std::iter::FromIterator;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 type QuantitativeType = f64;
 type CategoricalType = String;
 
-/// One dimensional data, such as vectors and arrays
+/// One dimensional data, such as vectors and arrays. Missing numbers are stored as NaN, and infinite numbers are
+/// treated as missing too, as they can't be placed on an axis.
 pub enum VectorData {
     Quantitative(Vec<QuantitativeType>),
     Categorical(Vec<CategoricalType>),
+    /// Points in time, as seconds since the Unix epoch (UTC)
+    Temporal(Vec<QuantitativeType>),
+    /// Categories with an order, holding every value in `order`
+    Ordinal {
+        values: Vec<CategoricalType>,
+        order: Vec<CategoricalType>,
+    },
+}
+
+impl VectorData {
+    /// The number of values in the data
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            VectorData::Quantitative(v) => v.len(),
+            VectorData::Categorical(v) => v.len(),
+            VectorData::Temporal(v) => v.len(),
+            VectorData::Ordinal { values, .. } => values.len(),
+        }
+    }
 }
 
 pub trait Plottable: Debug {
@@ -15,74 +39,239 @@ pub trait Plottable: Debug {
     fn to_conspire_data(&self) -> Self::D;
 }
 
-impl Plottable for Vec<f32> {
+/// Numbers that can be plotted as quantitative data.
+///
+/// Every value is converted to a 64-bit float. Integers of up to 32 bits, and floats, convert exactly. 64-bit integers
+/// (`i64`, `u64`, `isize` and `usize`) convert exactly up to 2^53 in magnitude, and beyond that are rounded to the
+/// nearest float, so neighbouring values may end up as the same number. Such differences are far too small to show on
+/// a plot, so they're rounded rather than rejected.
+pub trait Numeric: Copy + Debug {
+    fn to_quantitative(self) -> QuantitativeType;
+}
+
+macro_rules! numeric {
+    (exact: $($exact:ty),*; rounded: $($rounded:ty),*) => {
+        $(
+            impl Numeric for $exact {
+                fn to_quantitative(self) -> QuantitativeType {
+                    QuantitativeType::from(self)
+                }
+            }
+        )*
+        $(
+            impl Numeric for $rounded {
+                fn to_quantitative(self) -> QuantitativeType {
+                    self as QuantitativeType
+                }
+            }
+        )*
+    };
+}
+
+numeric!(exact: f32, f64, u8, i8, u16, i16, u32, i32; rounded: u64, i64, usize, isize);
+
+/// Numbers with gaps, such as readings from a sensor that dropped out. `None` is a missing value.
+impl<T: Numeric> Numeric for Option<T> {
+    fn to_quantitative(self) -> QuantitativeType {
+        self.map_or(QuantitativeType::NAN, Numeric::to_quantitative)
+    }
+}
+
+/// How lines are drawn past missing values (`None`, NaN or infinite numbers)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Missing {
+    /// Leave the missing points out, breaking the line at each gap
+    #[default]
+    Skip,
+    /// Join the points either side of a gap with a straight line, as if the missing values were interpolated
+    Interpolate,
+}
+
+fn quantitative<'a, T: Numeric + 'a>(values: impl IntoIterator<Item = &'a T>) -> VectorData {
+    VectorData::Quantitative(values.into_iter().map(|x| x.to_quantitative()).collect())
+}
+
+impl<T: Numeric> Plottable for Vec<T> {
+    type D = VectorData;
+
+    fn to_conspire_data(&self) -> Self::D {
+        quantitative(self)
+    }
+}
+
+impl<T: Numeric> Plottable for &[T] {
     type D = VectorData;
 
     fn to_conspire_data(&self) -> Self::D {
-        VectorData::Quantitative(
-            self.iter()
-                .map(|x| QuantitativeType::from(*x))
-                .collect::<Vec<QuantitativeType>>(),
-        )
+        quantitative(*self)
     }
 }
 
-impl Plottable for Vec<f64> {
+impl<T: Numeric, const N: usize> Plottable for [T; N] {
     type D = VectorData;
 
     fn to_conspire_data(&self) -> Self::D {
-        VectorData::Quantitative(self.to_vec())
+        quantitative(self)
     }
 }
 
-impl Plottable for Vec<u8> {
+impl<T: Numeric> Plottable for VecDeque<T> {
     type D = VectorData;
 
     fn to_conspire_data(&self) -> Self::D {
-        VectorData::Quantitative(
-            self.iter()
-                .map(|x| QuantitativeType::from(*x))
-                .collect::<Vec<QuantitativeType>>(),
-        )
+        quantitative(self)
     }
 }
 
-impl Plottable for Vec<i8> {
+impl<T: Numeric> Plottable for Array1<T> {
     type D = VectorData;
 
     fn to_conspire_data(&self) -> Self::D {
-        VectorData::Quantitative(
-            self.iter()
-                .map(|x| QuantitativeType::from(*x))
-                .collect::<Vec<QuantitativeType>>(),
-        )
+        quantitative(self)
     }
 }
 
-impl Plottable for Vec<u32> {
+impl<'a, T: Numeric> Plottable for ArrayView1<'a, T> {
     type D = VectorData;
 
     fn to_conspire_data(&self) -> Self::D {
-        VectorData::Quantitative(
-            self.iter()
-                .map(|x| QuantitativeType::from(*x))
-                .collect::<Vec<QuantitativeType>>(),
-        )
+        quantitative(self)
+    }
+}
+
+/// Numbers collected from an iterator, for plotting data that isn't stored in a collection of its own:
+///
+/// ```
+/// use conspire::{Layer, Values};
+///
+/// let layer = Layer::new().x((0..10).map(|i| i * i).collect::<Values>());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Values(Vec<QuantitativeType>);
+
+impl<T: Numeric> FromIterator<T> for Values {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Values(iter.into_iter().map(Numeric::to_quantitative).collect())
     }
 }
 
-impl Plottable for Vec<i32> {
+impl Plottable for Values {
     type D = VectorData;
 
     fn to_conspire_data(&self) -> Self::D {
-        VectorData::Quantitative(
-            self.iter()
-                .map(|x| QuantitativeType::from(*x))
-                .collect::<Vec<QuantitativeType>>(),
-        )
+        VectorData::Quantitative(self.0.clone())
     }
 }
 
+/// Points in time that can be plotted on a time axis. Times are stored as seconds since the Unix epoch in UTC, and
+/// dates and times without a time zone are taken to be in UTC.
+pub trait Timestamp: Debug {
+    fn to_seconds(&self) -> QuantitativeType;
+}
+
+impl Timestamp for SystemTime {
+    fn to_seconds(&self) -> QuantitativeType {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(after) => after.as_secs_f64(),
+            Err(before) => -before.duration().as_secs_f64(),
+        }
+    }
+}
+
+/// Times with gaps. `None` is a missing value.
+impl<T: Timestamp> Timestamp for Option<T> {
+    fn to_seconds(&self) -> QuantitativeType {
+        self.as_ref().map_or(QuantitativeType::NAN, Timestamp::to_seconds)
+    }
+}
+
+/// Dates, placed at midnight
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::NaiveDate {
+    fn to_seconds(&self) -> QuantitativeType {
+        use chrono::Datelike;
+
+        // Days from the first day of the common era to the Unix epoch
+        const EPOCH_DAYS: i32 = 719_163;
+        QuantitativeType::from(self.num_days_from_ce() - EPOCH_DAYS) * 86_400.0
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::NaiveDateTime {
+    fn to_seconds(&self) -> QuantitativeType {
+        use chrono::Timelike;
+
+        let time = self.time();
+        self.date().to_seconds()
+            + QuantitativeType::from(time.num_seconds_from_midnight())
+            + QuantitativeType::from(time.nanosecond()) * 1e-9
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> Timestamp for chrono::DateTime<Tz> {
+    fn to_seconds(&self) -> QuantitativeType {
+        self.naive_utc().to_seconds()
+    }
+}
+
+fn temporal<'a, T: Timestamp + 'a>(values: impl IntoIterator<Item = &'a T>) -> VectorData {
+    VectorData::Temporal(values.into_iter().map(Timestamp::to_seconds).collect())
+}
+
+/// Plottable collections of a type of timestamp. These can't be implemented for every `Timestamp` at once, as they
+/// would overlap with the implementations for numbers.
+macro_rules! temporal_plottable {
+    ($([$($generics:tt)*] $t:ty),*) => {
+        $(
+            impl<$($generics)*> Plottable for Vec<$t> {
+                type D = VectorData;
+
+                fn to_conspire_data(&self) -> Self::D {
+                    temporal(self)
+                }
+            }
+
+            impl<$($generics)*> Plottable for &[$t] {
+                type D = VectorData;
+
+                fn to_conspire_data(&self) -> Self::D {
+                    temporal(*self)
+                }
+            }
+
+            impl<$($generics)* const N: usize> Plottable for [$t; N] {
+                type D = VectorData;
+
+                fn to_conspire_data(&self) -> Self::D {
+                    temporal(self)
+                }
+            }
+
+            impl<$($generics)*> Plottable for VecDeque<$t> {
+                type D = VectorData;
+
+                fn to_conspire_data(&self) -> Self::D {
+                    temporal(self)
+                }
+            }
+        )*
+    };
+}
+
+temporal_plottable!([] SystemTime, [] Option<SystemTime>);
+
+#[cfg(feature = "chrono")]
+temporal_plottable!(
+    [] chrono::NaiveDate,
+    [] Option<chrono::NaiveDate>,
+    [] chrono::NaiveDateTime,
+    [] Option<chrono::NaiveDateTime>,
+    [Tz: chrono::TimeZone,] chrono::DateTime<Tz>,
+    [Tz: chrono::TimeZone,] Option<chrono::DateTime<Tz>>
+);
+
 impl Plottable for Vec<String> {
     type D = VectorData;
 
@@ -107,31 +296,191 @@ impl Plottable for &'static str {
     }
 }
 
+/// Categories with an order, such as "low", "medium" and "high". Axes, legends and color scales keep to the order,
+/// rather than placing the categories in the order they first appear:
+///
+/// ```
+/// use conspire::{Layer, Ordinal};
+///
+/// let risk = Ordinal::new(vec!["high", "low", "medium"], vec!["low", "medium", "high"]);
+/// let layer = Layer::new().x(risk).y(vec![3, 1, 2]);
+/// ```
+///
+/// Values with an order of their own, such as the variants of an enum deriving `Ord`, can be collected into ordinal
+/// data too, labelled by their `Display` implementation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ordinal {
+    values: Vec<CategoricalType>,
+    order: Vec<CategoricalType>,
+}
+
+impl Ordinal {
+    /// Categories in the given order. Values that are missing from the order are placed after it, in the order they
+    /// first appear.
+    pub fn new<V: ToString, O: ToString>(
+        values: impl IntoIterator<Item = V>,
+        order: impl IntoIterator<Item = O>,
+    ) -> Self {
+        let values: Vec<CategoricalType> = values.into_iter().map(|v| v.to_string()).collect();
+        let mut levels: Vec<CategoricalType> = Vec::new();
+        for category in order.into_iter().map(|o| o.to_string()).chain(values.iter().cloned()) {
+            if !levels.contains(&category) {
+                levels.push(category);
+            }
+        }
+        Ordinal { values, order: levels }
+    }
+}
+
+impl<T: Ord + Display> FromIterator<T> for Ordinal {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut values: Vec<T> = iter.into_iter().collect();
+        let labels: Vec<CategoricalType> = values.iter().map(|v| v.to_string()).collect();
+        values.sort();
+        values.dedup();
+        Ordinal::new(labels, values)
+    }
+}
+
+impl Plottable for Ordinal {
+    type D = VectorData;
+
+    fn to_conspire_data(&self) -> Self::D {
+        VectorData::Ordinal {
+            values: self.values.clone(),
+            order: self.order.clone(),
+        }
+    }
+}
+
 /// Two-dimensional data, such as matrices
 pub enum MatrixData {
     Quantitative(Vec<Vec<QuantitativeType>>),
 }
 
-impl Plottable for Vec<Vec<f64>> {
+fn rows<'a, T: Numeric + 'a, R: IntoIterator<Item = &'a T>>(rows: impl Iterator<Item = R>) -> MatrixData {
+    MatrixData::Quantitative(rows.map(|row| row.into_iter().map(|x| x.to_quantitative()).collect()).collect())
+}
+
+impl<T: Numeric> Plottable for Vec<Vec<T>> {
     type D = MatrixData;
 
     fn to_conspire_data(&self) -> Self::D {
-        MatrixData::Quantitative(self.to_vec())
+        rows(self.iter())
     }
 }
 
-impl Plottable for Vec<Vec<i32>> {
+impl<T: Numeric> Plottable for Array2<T> {
     type D = MatrixData;
 
     fn to_conspire_data(&self) -> Self::D {
-        MatrixData::Quantitative(
-            self.iter()
-                .map(|v| {
-                    v.iter()
-                        .map(|x| QuantitativeType::from(*x))
-                        .collect::<Vec<QuantitativeType>>()
-                })
-                .collect::<Vec<Vec<QuantitativeType>>>(),
-        )
+        rows(self.outer_iter())
+    }
+}
+
+impl<'a, T: Numeric> Plottable for ArrayView2<'a, T> {
+    type D = MatrixData;
+
+    fn to_conspire_data(&self) -> Self::D {
+        rows(self.outer_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{arr1, arr2, s};
+
+    fn numbers(data: impl Plottable<D = VectorData>) -> Vec<f64> {
+        match data.to_conspire_data() {
+            VectorData::Quantitative(v) => v,
+            _ => panic!("expected quantitative data"),
+        }
+    }
+
+    fn matrix(data: impl Plottable<D = MatrixData>) -> Vec<Vec<f64>> {
+        let MatrixData::Quantitative(rows) = data.to_conspire_data();
+        rows
+    }
+
+    #[test]
+    fn ndarray_vectors() {
+        let array = arr1(&[1, 2, 3, 4, 5]);
+        assert_eq!(numbers(array.clone()), vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(numbers(array.view()), vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        // Views that skip elements, or run backwards, keep the values they see in their own order
+        assert_eq!(numbers(array.slice(s![..;2])), vec![1.0, 3.0, 5.0]);
+        assert_eq!(numbers(array.slice(s![..;-1])), vec![5.0, 4.0, 3.0, 2.0, 1.0]);
+
+        let grid = arr2(&[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        assert_eq!(numbers(grid.column(1)), vec![2.0, 5.0]);
+    }
+
+    #[test]
+    fn ndarray_matrices() {
+        let grid = arr2(&[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let expected = vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]];
+        assert_eq!(matrix(grid.clone()), expected);
+        assert_eq!(matrix(grid.view()), expected);
+        // The rows of a transposed view are the columns of the array
+        assert_eq!(matrix(grid.t()), vec![vec![1.0, 4.0], vec![2.0, 5.0], vec![3.0, 6.0]]);
+        assert_eq!(matrix(grid.slice(s![.., ..;2])), vec![vec![1.0, 3.0], vec![4.0, 6.0]]);
+        // An array stored column by column still gives its rows
+        assert_eq!(matrix(grid.reversed_axes()), vec![vec![1.0, 4.0], vec![2.0, 5.0], vec![3.0, 6.0]]);
+    }
+
+    #[test]
+    fn wide_integers_are_rounded() {
+        let big = 1u64 << 53;
+        // Up to 2^53 every integer has a float of its own, and past it odd numbers round to an even neighbour
+        assert_eq!(big.to_quantitative(), 9007199254740992.0);
+        assert_eq!((big + 1).to_quantitative(), 9007199254740992.0);
+        assert_eq!((big + 3).to_quantitative(), 9007199254740996.0);
+        assert_eq!(u64::MAX.to_quantitative(), 18446744073709551616.0);
+        assert_eq!((-(1i64 << 53) - 1).to_quantitative(), -9007199254740992.0);
+        assert_eq!(i64::MIN.to_quantitative(), -9223372036854775808.0);
+        assert_eq!(((1usize << 53) + 1).to_quantitative(), 9007199254740992.0);
+        assert_eq!((-(1isize << 53) - 1).to_quantitative(), -9007199254740992.0);
+        // Smaller numbers convert exactly
+        assert_eq!((big - 1).to_quantitative(), 9007199254740991.0);
+        assert_eq!(u32::MAX.to_quantitative(), 4294967295.0);
+        assert_eq!(Some(-3i64).to_quantitative(), -3.0);
+        assert!(None::<u64>.to_quantitative().is_nan());
+    }
+
+    #[test]
+    fn collections() {
+        let mut deque: VecDeque<u8> = vec![2, 3].into();
+        deque.push_front(1);
+        assert_eq!(numbers(deque), vec![1.0, 2.0, 3.0]);
+        assert_eq!(numbers([1.5f32, 2.5]), vec![1.5, 2.5]);
+        let gaps = numbers([Some(1i32), None]);
+        assert_eq!(gaps[0], 1.0);
+        assert!(gaps[1].is_nan());
+        assert!(numbers([0u16; 0]).is_empty());
+        assert_eq!(numbers(&[4i16, 5][..]), vec![4.0, 5.0]);
+
+        let values: Values = (1..4u64).map(|i| i * i).collect();
+        assert_eq!(values, Values(vec![1.0, 4.0, 9.0]));
+        assert_eq!(numbers(values), vec![1.0, 4.0, 9.0]);
+        let gaps: Values = vec![Some(1.0), None].into_iter().collect();
+        assert!(numbers(gaps)[1].is_nan());
+    }
+
+    #[test]
+    fn ordinal_values_missing_from_the_order_come_after_it() {
+        let risk = Ordinal::new(vec!["high", "extreme", "low", "unknown", "extreme"], vec!["low", "medium", "high"]);
+        match risk.to_conspire_data() {
+            VectorData::Ordinal { values, order } => {
+                assert_eq!(values, vec!["high", "extreme", "low", "unknown", "extreme"]);
+                assert_eq!(order, vec!["low", "medium", "high", "extreme", "unknown"]);
+            }
+            _ => panic!("expected ordinal data"),
+        }
+        // Repeats in the order are only kept once
+        assert_eq!(Ordinal::new(vec!["a"], vec!["b", "a", "b"]), Ordinal::new(vec!["a"], vec!["b", "a"]));
+
+        let sizes: Ordinal = vec![10, 2, 33, 2].into_iter().collect();
+        assert_eq!(sizes, Ordinal::new(vec!["10", "2", "33", "2"], vec!["2", "10", "33"]));
     }
 }
@@ -1,28 +1,96 @@
 use std::error::Error;
 use std::fmt;
+use std::io;
 
+#[deprecated(note = "plots report a missing channel with `ConspireError::MissingChannel` instead")]
 #[derive(Debug, Clone)]
 pub enum DimensionError {
     NoX,
     NoY,
 }
 
+#[allow(deprecated)]
 impl fmt::Display for DimensionError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             DimensionError::NoX => write!(f, "Missing X dimension"),
-            DimensionError::NoY => write!(f, "Missing X dimension"),
+            DimensionError::NoY => write!(f, "Missing Y dimension"),
         }
     }
 }
 
+#[allow(deprecated)]
 impl Error for DimensionError {
     fn description(&self) -> &str {
         "Couldn't parse X/Y dimension"
     }
 
-    fn cause(&self) -> Option<&Error> {
+    fn cause(&self) -> Option<&dyn Error> {
         None
     }
 }
-    
+
+/// Anything that can go wrong when making or rendering a plot
+#[derive(Debug)]
+pub enum ConspireError {
+    /// Reading or writing a file, or writing to a writer, failed
+    Io(io::Error),
+    /// A plot was made from a layer without a channel that kind of plot needs
+    MissingChannel { plot: &'static str, channel: &'static str },
+    /// A channel holds a different number of values than the plot expects, e.g. than the other channels of its layer
+    LengthMismatch { channel: &'static str, expected: usize, found: usize },
+    /// A setting of a plot, such as the binning of a histogram, can't be used
+    InvalidSetting(String),
+    /// The data can't be drawn as the requested plot, such as a treemap whose labels aren't unique
+    InvalidData(String),
+    /// The backend can't draw this kind of plot
+    UnsupportedPlot { backend: &'static str, plot: &'static str },
+    /// An external program, such as the viewer for the rendered file or gnuplot, couldn't be started or failed
+    Viewer { program: String, error: io::Error },
+    /// The backend failed to produce its output
+    Render(String),
+}
+
+impl fmt::Display for ConspireError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConspireError::Io(e) => write!(f, "I/O error: {}", e),
+            ConspireError::MissingChannel { plot, channel } => {
+                write!(f, "A {} plot needs the {} channel, which is missing", plot, channel)
+            }
+            ConspireError::LengthMismatch { channel, expected, found } => write!(
+                f,
+                "The {} channel has {} values, where {} were expected",
+                channel, found, expected
+            ),
+            ConspireError::InvalidSetting(reason) => write!(f, "Invalid setting: {}", reason),
+            ConspireError::InvalidData(reason) => write!(f, "Invalid data: {}", reason),
+            ConspireError::UnsupportedPlot { backend, plot } => {
+                write!(f, "The {} backend can't draw {} plots", backend, plot)
+            }
+            ConspireError::Viewer { program, error } => write!(f, "Couldn't run {}: {}", program, error),
+            ConspireError::Render(reason) => write!(f, "Couldn't render the plot: {}", reason),
+        }
+    }
+}
+
+impl Error for ConspireError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConspireError::Io(e) | ConspireError::Viewer { error: e, .. } => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConspireError {
+    fn from(e: io::Error) -> Self {
+        ConspireError::Io(e)
+    }
+}
+
+impl From<fmt::Error> for ConspireError {
+    fn from(_: fmt::Error) -> Self {
+        ConspireError::Render(String::from("formatting the output failed"))
+    }
+}
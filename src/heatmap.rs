@@ -0,0 +1,62 @@
+//! Settings for heatmaps, contour plots and surfaces, which map the values of a matrix onto colors.
+
+/// The colors the values of a heatmap are mapped onto, from the lowest value to the highest
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Colorscale {
+    /// Dark purple through blue and green to yellow, the sequential scale used for quantitative colors elsewhere
+    #[default]
+    Viridis,
+    /// White to black
+    Greys,
+    /// White to dark blue
+    Blues,
+    /// White to dark red
+    Reds,
+    /// Blue through white to red, for values either side of a midpoint such as correlations
+    BlueRed,
+    /// Colors at increasing positions, starting at 0 and ending at 1, given as hex codes (`"#ff8800"`) or common color
+    /// names
+    Custom(Vec<(f64, String)>),
+}
+
+/// Text written on the cells of a heatmap
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Annotations {
+    #[default]
+    None,
+    /// The value of each cell, with the given number of decimals
+    Values(usize),
+    /// A text per cell, in rows matching the rows of the matrix
+    Text(Vec<Vec<String>>),
+}
+
+/// How to color and label the cells of a heatmap
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HeatmapOptions {
+    pub colorscale: Colorscale,
+    /// The value given the lowest color, instead of the smallest value of the matrix
+    pub zmin: Option<f64>,
+    /// The value given the highest color, instead of the largest value of the matrix
+    pub zmax: Option<f64>,
+    /// Widen the range of values evenly around this value, so that it gets the middle color of the scale. Limits set
+    /// with `zmin` or `zmax` are kept as they are.
+    pub midpoint: Option<f64>,
+    pub annotations: Annotations,
+}
+
+/// How to draw the lines of a contour plot
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContourOptions {
+    pub colorscale: Colorscale,
+    /// The number of lines, drawn at evenly spaced values between the lowest and highest value of the matrix
+    pub levels: usize,
+}
+
+impl Default for ContourOptions {
+    fn default() -> Self {
+        ContourOptions {
+            colorscale: Colorscale::default(),
+            levels: 10,
+        }
+    }
+}
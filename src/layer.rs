@@ -1,13 +1,26 @@
-use crate::data::{Plottable};
+use crate::data::{MatrixData, Plottable, VectorData};
 
 pub struct Layer<T> {
     x: Option<T>,
     y: Option<T>,
     color: Option<T>,
     size: Option<T>,
+    label: Option<T>,
+    parent: Option<T>,
+    value: Option<T>,
+    dimensions: Vec<(String, T)>,
+    z: Option<MatrixData>,
+    x_ticks: Option<VectorData>,
+    y_ticks: Option<VectorData>,
     name: Option<String>,
 }
 
+impl<T> Default for Layer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Layer<T> {
     pub fn new() -> Self {
         Self {
@@ -15,11 +28,18 @@ impl<T> Layer<T> {
             y: None,
             color: None,
             size: None,
+            label: None,
+            parent: None,
+            value: None,
+            dimensions: Vec::new(),
+            z: None,
+            x_ticks: None,
+            y_ticks: None,
             name: None,
         }
     }
 
-    pub fn name<'a>(mut self, name: &'a str) -> Self {
+    pub fn name(mut self, name: &str) -> Self {
         self.name = Some(name.to_string());
         self
     }
@@ -46,6 +66,18 @@ impl<T> Layer<T> {
         &self.y
     }
 
+    /// Set the values of a grid, such as the cells of a heatmap. Each row of the matrix is a row of the grid, placed
+    /// at the matching value of the y channel, while its columns are placed along the x channel. Layers of matrices
+    /// place them with their ticks instead.
+    pub fn z(mut self, data: impl Plottable<D = MatrixData>) -> Self {
+        self.z = Some(data.to_conspire_data());
+        self
+    }
+
+    pub fn get_z(&self) -> &Option<MatrixData> {
+        &self.z
+    }
+
     pub fn color(mut self, data: impl Plottable<D = T>) -> Self {
         self.color = Some(data.to_conspire_data());
         self
@@ -63,4 +95,79 @@ impl<T> Layer<T> {
     pub fn get_size(&self) -> &Option<T> {
         &self.size
     }
+
+    pub fn label(mut self, data: impl Plottable<D = T>) -> Self {
+        self.label = Some(data.to_conspire_data());
+        self
+    }
+
+    pub fn get_label(&self) -> &Option<T> {
+        &self.label
+    }
+
+    pub fn parent(mut self, data: impl Plottable<D = T>) -> Self {
+        self.parent = Some(data.to_conspire_data());
+        self
+    }
+
+    pub fn get_parent(&self) -> &Option<T> {
+        &self.parent
+    }
+
+    pub fn value(mut self, data: impl Plottable<D = T>) -> Self {
+        self.value = Some(data.to_conspire_data());
+        self
+    }
+
+    pub fn get_value(&self) -> &Option<T> {
+        &self.value
+    }
+
+    /// Add a named dimension, such as a column of a table, for plots with any number of dimensions
+    pub fn dimension(mut self, name: &str, data: impl Plottable<D = T>) -> Self {
+        self.dimensions.push((name.to_string(), data.to_conspire_data()));
+        self
+    }
+
+    pub fn get_dimensions(&self) -> &[(String, T)] {
+        &self.dimensions
+    }
+}
+
+impl Layer<VectorData> {
+    /// Add every column of a matrix as a dimension, named by its number. Each row of the matrix holds the values of a
+    /// single data point, and rows too short to have a column get a NaN in it.
+    pub fn dimensions(mut self, data: impl Plottable<D = MatrixData>) -> Self {
+        let MatrixData::Quantitative(rows) = data.to_conspire_data();
+        let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        for c in 0..columns {
+            let column = rows.iter().map(|r| r.get(c).cloned().unwrap_or(f64::NAN)).collect();
+            let name = format!("Dimension {}", self.dimensions.len() + 1);
+            self.dimensions.push((name, VectorData::Quantitative(column)));
+        }
+        self
+    }
+}
+
+impl Layer<MatrixData> {
+    /// Label the columns of a matrix, such as the categories of a confusion matrix or the coordinates of a grid. Text
+    /// labels are placed at evenly spaced ticks, while numbers place each column at its value.
+    pub fn x_ticks(mut self, data: impl Plottable<D = VectorData>) -> Self {
+        self.x_ticks = Some(data.to_conspire_data());
+        self
+    }
+
+    pub fn get_x_ticks(&self) -> &Option<VectorData> {
+        &self.x_ticks
+    }
+
+    /// Label the rows of a matrix, like `x_ticks` does for its columns
+    pub fn y_ticks(mut self, data: impl Plottable<D = VectorData>) -> Self {
+        self.y_ticks = Some(data.to_conspire_data());
+        self
+    }
+
+    pub fn get_y_ticks(&self) -> &Option<VectorData> {
+        &self.y_ticks
+    }
 }
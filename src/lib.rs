@@ -4,8 +4,8 @@
 //! ## Goals
 //! 
 //! * Provide a large set of available plots. Many plotting libraries only provide the basics, such as bar charts,
-//!    scatterplots and line charts. Conspire aims to also provide various plots for statistics, 3D charts, statistical
-//!    plots, and various scientific charts for applicable backends.
+//!   scatterplots and line charts. Conspire aims to also provide various plots for statistics, 3D charts, statistical
+//!   plots, and various scientific charts for applicable backends.
 //!    
 //! * Flexibility. Allow for specifying multiple different channels for each plot, and let the user display multiple
 //!   plots simultaneously, in different layouts.
@@ -25,9 +25,9 @@
 //! # How to use
 //! The following plot plots two lines using the Plotly backend.
 //! 
-//! ```
+//! ```no_run
 //! use conspire::{Backend, PlotBuilder, Plot, Layer};
-//! use conspire::channels::{PositionX, PositionY, Color, Size};
+//! # fn main() -> Result<(), conspire::ConspireError> {
 //! 
 //! let layer1 = Layer::new()
 //!     .x(vec![1.0, 1.3, 2.0, 1.7, 3.0, 4.0])
@@ -40,11 +40,13 @@
 //!  
 //!  let plot = PlotBuilder::new(Backend::Plotly)
 //!    .display(true)
-//!    .add_layer(Plot::scatter(layer1))
-//!    .add_layer(Plot::scatter(layer2))
+//!    .add_layer(Plot::scatter(&layer1))
+//!    .add_layer(Plot::scatter(&layer2))
 //!    .build();
 //!  
-//!  plot.render();
+//!  plot.render()?;
+//! # Ok(())
+//! # }
 //! ```
 //! 
 //! To create a plot, you make one or more `Layer`s. A Layer is a generalized plot, specifying what data should go into
@@ -59,14 +61,27 @@
 mod data;
 mod backends;
 mod error;
+mod heatmap;
 mod layer;
+mod stats;
 
-pub use backends::Backend;
-pub use error::DimensionError;
+pub use backends::{Backend, GnuplotTerminal, PlotlyJs};
+#[cfg(feature = "plotters")]
+pub use backends::PlottersFormat;
+pub use data::{Missing, Ordinal, Values};
+#[allow(deprecated)]
+pub use error::{ConspireError, DimensionError};
+pub use heatmap::{Annotations, Colorscale, ContourOptions, HeatmapOptions};
 pub use layer::Layer;
-use backends::*;
+pub use stats::{Bandwidth, Binning, BoxOptions, ViolinInner, Whiskers};
+use backends::Rgb;
 use data::{ VectorData, MatrixData };
 
+use std::cmp::Ordering;
+use std::fs;
+use std::io;
+use std::path::Path;
+
 pub enum Plot<'a> {
     Scatter {
         x: &'a VectorData,
@@ -78,6 +93,7 @@ pub enum Plot<'a> {
     Line {
         x: &'a VectorData,
         y: &'a VectorData,
+        missing: Missing,
         color: &'a Option<VectorData>,
         size: &'a Option<VectorData>,
         name: &'a Option<String>,
@@ -101,84 +117,666 @@ pub enum Plot<'a> {
     },
     Box {
         x: &'a VectorData,
+        y: &'a Option<VectorData>,
+        options: BoxOptions,
+        color: &'a Option<VectorData>,
+        name: &'a Option<String>,
+    },
+    Histogram {
+        x: &'a VectorData,
+        binning: Binning,
+        color: &'a Option<VectorData>,
+        name: &'a Option<String>,
+    },
+    Violin {
+        x: &'a VectorData,
+        group: &'a Option<VectorData>,
+        bandwidth: Bandwidth,
+        inner: ViolinInner,
+        color: &'a Option<VectorData>,
+        name: &'a Option<String>,
+    },
+    Treemap {
+        label: &'a VectorData,
+        parent: &'a VectorData,
+        value: &'a Option<VectorData>,
+        color: &'a Option<VectorData>,
+        name: &'a Option<String>,
+    },
+    ParallelCoordinates {
+        dimensions: &'a [(String, VectorData)],
         color: &'a Option<VectorData>,
         name: &'a Option<String>,
     },
-    SimpleHeatmap {
+    Heatmap {
+        x: &'a Option<VectorData>,
+        y: &'a Option<VectorData>,
+        z: &'a MatrixData,
+        options: HeatmapOptions,
+        name: &'a Option<String>,
+    },
+    Contour {
+        x: &'a Option<VectorData>,
+        y: &'a Option<VectorData>,
         z: &'a MatrixData,
-        color: &'a Option<MatrixData>,
+        options: ContourOptions,
+        name: &'a Option<String>,
+    },
+    Surface {
+        x: &'a Option<VectorData>,
+        y: &'a Option<VectorData>,
+        z: &'a MatrixData,
+        colorscale: Colorscale,
         name: &'a Option<String>,
     },
 }
 
 impl<'a> Plot<'a> {
+    /// The kind of plot, as named in error messages
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Plot::Scatter { .. } => "scatter",
+            Plot::Line { .. } => "line",
+            Plot::Bar { .. } => "bar",
+            Plot::HorizontalBar { .. } => "horizontal bar",
+            Plot::Pie { .. } => "pie",
+            Plot::Box { .. } => "box",
+            Plot::Histogram { .. } => "histogram",
+            Plot::Violin { .. } => "violin",
+            Plot::Treemap { .. } => "treemap",
+            Plot::ParallelCoordinates { .. } => "parallel coordinates",
+            Plot::Heatmap { .. } => "heatmap",
+            Plot::Contour { .. } => "contour",
+            Plot::Surface { .. } => "surface",
+        }
+    }
+
+    /// Make a scatter plot from a layer with x and y channels, panicking if the layer can't be plotted. See
+    /// `try_scatter` for a version returning an error instead.
     pub fn scatter(plot: &'a Layer<VectorData>) -> Plot<'a> {
-        Plot::Scatter {
-            x: plot.get_x().as_ref().expect("No X axis found"),
-            y: plot.get_y().as_ref().expect("No X axis found"),
+        Plot::try_scatter(plot).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_scatter(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        let x = channel(plot.get_x(), "scatter", "x")?;
+        let y = channel(plot.get_y(), "scatter", "y")?;
+        same_length(x.len(), y, "y")?;
+        broadcastable(x.len(), plot.get_color(), "color")?;
+        broadcastable(x.len(), plot.get_size(), "size")?;
+        Ok(Plot::Scatter {
+            x,
+            y,
             color: plot.get_color(),
             size: plot.get_size(),
             name: plot.get_name(),
-        }
+        })
     }
 
+    /// Make a line plot from a layer with x and y channels, breaking the line at missing values, panicking if the layer
+    /// can't be plotted. See `try_line` for a version returning an error instead.
     pub fn line(plot: &'a Layer<VectorData>) -> Plot<'a> {
-        Plot::Line {
-            x: plot.get_x().as_ref().expect("No X axis found"),
-            y: plot.get_y().as_ref().expect("No X axis found"),
+        Plot::line_with(plot, Missing::default())
+    }
+
+    pub fn try_line(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        Plot::try_line_with(plot, Missing::default())
+    }
+
+    /// Make a line plot from a layer with x and y channels, drawing past missing values as given, panicking if the
+    /// layer can't be plotted. See `try_line_with` for a version returning an error instead.
+    pub fn line_with(plot: &'a Layer<VectorData>, missing: Missing) -> Plot<'a> {
+        Plot::try_line_with(plot, missing).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_line_with(plot: &'a Layer<VectorData>, missing: Missing) -> Result<Plot<'a>, ConspireError> {
+        let x = channel(plot.get_x(), "line", "x")?;
+        let y = channel(plot.get_y(), "line", "y")?;
+        same_length(x.len(), y, "y")?;
+        broadcastable(x.len(), plot.get_color(), "color")?;
+        broadcastable(x.len(), plot.get_size(), "size")?;
+        Ok(Plot::Line {
+            x,
+            y,
+            missing,
             color: plot.get_color(),
             size: plot.get_size(),
             name: plot.get_name(),
-        }
+        })
     }
 
+    /// Make a bar chart from a layer with x and y channels, panicking if the layer can't be plotted. See `try_bar` for
+    /// a version returning an error instead.
     pub fn bar(plot: &'a Layer<VectorData>) -> Plot<'a> {
-        Plot::Bar {
-            x: plot.get_x().as_ref().expect("No X axis found"),
-            y: plot.get_y().as_ref().expect("No X axis found"),
+        Plot::try_bar(plot).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_bar(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        let x = channel(plot.get_x(), "bar", "x")?;
+        let y = channel(plot.get_y(), "bar", "y")?;
+        same_length(x.len(), y, "y")?;
+        broadcastable(x.len(), plot.get_color(), "color")?;
+        Ok(Plot::Bar {
+            x,
+            y,
             color: plot.get_color(),
             name: plot.get_name(),
-        }
+        })
     }
 
+    /// Make a pie chart from a layer with an x channel, panicking if the layer can't be plotted. See `try_pie` for a
+    /// version returning an error instead.
     pub fn pie(plot: &'a Layer<VectorData>) -> Plot<'a> {
-        Plot::Pie {
-            x: plot.get_x().as_ref().expect("No X axis found"),
+        Plot::try_pie(plot).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_pie(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        let x = channel(plot.get_x(), "pie", "x")?;
+        broadcastable(x.len(), plot.get_color(), "color")?;
+        Ok(Plot::Pie {
+            x,
             color: plot.get_color(),
             name: plot.get_name(),
-        }
+        })
     }
 
+    /// Make a horizontal bar chart from a layer with x and y channels, panicking if the layer can't be plotted. See
+    /// `try_horizontal_bar` for a version returning an error instead.
     pub fn horizontal_bar(plot: &'a Layer<VectorData>) -> Plot<'a> {
-        Plot::HorizontalBar {
-            x: plot.get_x().as_ref().expect("No X axis found"),
-            y: plot.get_y().as_ref().expect("No X axis found"),
+        Plot::try_horizontal_bar(plot).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_horizontal_bar(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        let x = channel(plot.get_x(), "horizontal bar", "x")?;
+        let y = channel(plot.get_y(), "horizontal bar", "y")?;
+        same_length(x.len(), y, "y")?;
+        broadcastable(x.len(), plot.get_color(), "color")?;
+        Ok(Plot::HorizontalBar {
+            x,
+            y,
             color: plot.get_color(),
             name: plot.get_name(),
-        }
+        })
     }
 
+    /// Make a box plot from a layer, panicking if the layer can't be plotted. A layer with only an x channel gets a
+    /// single horizontal box, while a layer with a categorical x channel and a quantitative y channel gets an upright
+    /// box per category. A categorical color channel splits each box into side-by-side boxes, one per color. See
+    /// `try_boxplot` for a version returning an error instead.
     pub fn boxplot(plot: &'a Layer<VectorData>) -> Plot<'a> {
-        Plot::Box {
-            x: plot.get_x().as_ref().expect("No X axis found"),
+        Plot::boxplot_with(plot, BoxOptions::default())
+    }
+
+    pub fn try_boxplot(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        Plot::try_boxplot_with(plot, BoxOptions::default())
+    }
+
+    /// Make a box plot from a layer with the given whiskers, notches and means, panicking if the layer can't be
+    /// plotted. See `try_boxplot_with` for a version returning an error instead.
+    pub fn boxplot_with(plot: &'a Layer<VectorData>, options: BoxOptions) -> Plot<'a> {
+        Plot::try_boxplot_with(plot, options).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_boxplot_with(plot: &'a Layer<VectorData>, options: BoxOptions) -> Result<Plot<'a>, ConspireError> {
+        let x = channel(plot.get_x(), "box", "x")?;
+        if let VectorData::Temporal(_) = x {
+            return Err(ConspireError::InvalidData(String::from("box plot values must be numbers")));
+        }
+        if let Some(y) = plot.get_y() {
+            same_length(x.len(), y, "y")?;
+            match (x, y) {
+                (VectorData::Categorical(_) | VectorData::Ordinal { .. }, VectorData::Quantitative(_)) => {}
+                _ => {
+                    return Err(ConspireError::InvalidData(String::from(
+                        "box plots with a y channel need categories in x and numbers in y",
+                    )))
+                }
+            }
+        } else if let VectorData::Categorical(_) | VectorData::Ordinal { .. } = x {
+            // Categories only make boxes of the numbers in y
+            return Err(ConspireError::MissingChannel { plot: "box", channel: "y" });
+        }
+        broadcastable(x.len(), plot.get_color(), "color")?;
+        Ok(Plot::Box {
+            x,
+            y: plot.get_y(),
+            options,
+            color: plot.get_color(),
+            name: plot.get_name(),
+        })
+    }
+
+    /// Make a histogram of the x channel of a layer, binned with the Sturges rule, panicking if the layer can't be
+    /// plotted. Categorical data is counted rather than binned, giving a bar per category. See `try_histogram` for a
+    /// version returning an error instead.
+    pub fn histogram(plot: &'a Layer<VectorData>) -> Plot<'a> {
+        Plot::histogram_with(plot, Binning::default())
+    }
+
+    pub fn try_histogram(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        Plot::try_histogram_with(plot, Binning::default())
+    }
+
+    /// Make a histogram of the x channel of a layer with the given binning rule, panicking if the layer can't be
+    /// plotted or the binning is invalid. See `try_histogram_with` for a version returning an error instead.
+    pub fn histogram_with(plot: &'a Layer<VectorData>, binning: Binning) -> Plot<'a> {
+        Plot::try_histogram_with(plot, binning).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_histogram_with(plot: &'a Layer<VectorData>, binning: Binning) -> Result<Plot<'a>, ConspireError> {
+        match binning {
+            Binning::Count(0) => {
+                return Err(ConspireError::InvalidSetting(String::from("a histogram needs at least one bin")))
+            }
+            Binning::Width(width) if !(width > 0.0 && width.is_finite()) => {
+                return Err(ConspireError::InvalidSetting(format!(
+                    "histogram bins must have a positive width, not {}",
+                    width
+                )))
+            }
+            _ => {}
+        }
+        let x = channel(plot.get_x(), "histogram", "x")?;
+        let explicit = matches!(binning, Binning::Count(_) | Binning::Width(_));
+        if let (true, VectorData::Quantitative(v) | VectorData::Temporal(v)) = (explicit, x) {
+            let count = stats::bin_count(v, binning);
+            if count > stats::MAX_BINS {
+                return Err(ConspireError::InvalidSetting(format!(
+                    "a histogram can have at most {} bins, not {}",
+                    stats::MAX_BINS,
+                    count
+                )));
+            }
+        }
+
+        // The bars don't correspond to individual values, so the whole histogram gets a single color
+        if let Some(color) = plot.get_color() {
+            same_length(1, color, "color")?;
+        }
+
+        Ok(Plot::Histogram {
+            x,
+            binning,
             color: plot.get_color(),
             name: plot.get_name(),
+        })
+    }
+
+    /// Make a violin plot of the x channel of a layer, panicking if the layer can't be plotted. The density of the
+    /// values is estimated with Silverman's rule of thumb and drawn around a narrow box plot. When the layer has a
+    /// categorical y channel, each category gets a violin of its own. See `try_violin` for a version returning an error
+    /// instead.
+    pub fn violin(plot: &'a Layer<VectorData>) -> Plot<'a> {
+        Plot::violin_with(plot, Bandwidth::default(), ViolinInner::default())
+    }
+
+    pub fn try_violin(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        Plot::try_violin_with(plot, Bandwidth::default(), ViolinInner::default())
+    }
+
+    /// Make a violin plot of the x channel of a layer with the given bandwidth and inner marks, panicking if the layer
+    /// can't be plotted or the bandwidth is invalid. See `try_violin_with` for a version returning an error instead.
+    pub fn violin_with(plot: &'a Layer<VectorData>, bandwidth: Bandwidth, inner: ViolinInner) -> Plot<'a> {
+        Plot::try_violin_with(plot, bandwidth, inner).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_violin_with(
+        plot: &'a Layer<VectorData>,
+        bandwidth: Bandwidth,
+        inner: ViolinInner,
+    ) -> Result<Plot<'a>, ConspireError> {
+        if let Bandwidth::Fixed(h) = bandwidth {
+            if !(h > 0.0 && h.is_finite()) {
+                return Err(ConspireError::InvalidSetting(format!(
+                    "violin bandwidths must be positive, not {}",
+                    h
+                )));
+            }
+        }
+
+        let x = channel(plot.get_x(), "violin", "x")?;
+        if let VectorData::Categorical(_) | VectorData::Temporal(_) | VectorData::Ordinal { .. } = x {
+            return Err(ConspireError::InvalidData(String::from("violin values must be numbers")));
+        }
+        if let Some(group) = plot.get_y() {
+            same_length(x.len(), group, "y")?;
+            if let VectorData::Quantitative(_) | VectorData::Temporal(_) = group {
+                return Err(ConspireError::InvalidData(String::from("violin groups must be text")));
+            }
         }
+
+        // Like a histogram, a violin summarizes many values, so the whole layer gets a single color
+        if let Some(color) = plot.get_color() {
+            same_length(1, color, "color")?;
+        }
+
+        Ok(Plot::Violin {
+            x,
+            group: plot.get_y(),
+            bandwidth,
+            inner,
+            color: plot.get_color(),
+            name: plot.get_name(),
+        })
+    }
+
+    /// Make a treemap from a layer with label and parent channels, panicking if the layer can't be plotted. Each label
+    /// is a node of the tree, whose parent is the node with the label in the parent channel, or the root when the
+    /// parent is empty (`""`). The optional value channel sizes each node, on top of the sizes of its children. See
+    /// `try_treemap` for a version returning an error instead.
+    pub fn treemap(plot: &'a Layer<VectorData>) -> Plot<'a> {
+        Plot::try_treemap(plot).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    pub fn heatmap(plot: &'a Layer<MatrixData>) -> Plot<'a> {
-        Plot::SimpleHeatmap {
-            z: plot.get_x().as_ref().expect("No Y axis found"),
+    pub fn try_treemap(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        let label = channel(plot.get_label(), "treemap", "label")?;
+        let parent = channel(plot.get_parent(), "treemap", "parent")?;
+        same_length(label.len(), parent, "parent")?;
+        if let Some(value) = plot.get_value() {
+            same_length(label.len(), value, "value")?;
+            if let VectorData::Categorical(_) | VectorData::Temporal(_) | VectorData::Ordinal { .. } = value {
+                return Err(ConspireError::InvalidData(String::from("treemap values must be numbers")));
+            }
+        }
+        broadcastable(label.len(), plot.get_color(), "color")?;
+
+        match (label, parent) {
+            (
+                VectorData::Categorical(labels) | VectorData::Ordinal { values: labels, .. },
+                VectorData::Categorical(_) | VectorData::Ordinal { .. },
+            ) => {
+                if let Some(duplicate) = labels.iter().enumerate().find(|(i, l)| labels[..*i].contains(l)) {
+                    return Err(ConspireError::InvalidData(format!(
+                        "treemap labels must be unique, but \"{}\" appears more than once",
+                        duplicate.1
+                    )));
+                }
+            }
+            _ => return Err(ConspireError::InvalidData(String::from("treemap labels and parents must be text"))),
+        }
+
+        Ok(Plot::Treemap {
+            label,
+            parent,
+            value: plot.get_value(),
             color: plot.get_color(),
             name: plot.get_name(),
+        })
+    }
+
+    /// Make a parallel coordinates plot from the dimensions of a layer, panicking if the layer can't be plotted. Each
+    /// data point is drawn as a line crossing an axis per dimension, colored by the color channel. Categorical
+    /// dimensions place their categories at evenly spaced ticks, in the order they first appear. See
+    /// `try_parallel_coordinates` for a version returning an error instead.
+    pub fn parallel_coordinates(plot: &'a Layer<VectorData>) -> Plot<'a> {
+        Plot::try_parallel_coordinates(plot).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_parallel_coordinates(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        let dimensions = plot.get_dimensions();
+        let (_, first) = dimensions.first().ok_or(ConspireError::MissingChannel {
+            plot: "parallel coordinates",
+            channel: "dimension",
+        })?;
+        for (_, dimension) in dimensions {
+            same_length(first.len(), dimension, "dimension")?;
+        }
+        broadcastable(first.len(), plot.get_color(), "color")?;
+        Ok(Plot::ParallelCoordinates {
+            dimensions,
+            color: plot.get_color(),
+            name: plot.get_name(),
+        })
+    }
+
+    /// Make a heatmap from a layer with a matrix in its z channel, panicking if the layer can't be plotted. The x and
+    /// y channels place the columns and rows of the matrix: text gives each a band, while numbers are either the
+    /// center of each cell, or the edges between cells when there's one more of them. Cells are indexed without them.
+    /// See `try_heatmap` for a version returning an error instead.
+    pub fn heatmap(plot: &'a Layer<VectorData>) -> Plot<'a> {
+        Plot::heatmap_with(plot, HeatmapOptions::default())
+    }
+
+    pub fn try_heatmap(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        Plot::try_heatmap_with(plot, HeatmapOptions::default())
+    }
+
+    /// Make a heatmap from a layer with a matrix in its z channel, colored and labelled with the given options,
+    /// panicking if the layer can't be plotted or the options are invalid. See `try_heatmap_with` for a version
+    /// returning an error instead.
+    pub fn heatmap_with(plot: &'a Layer<VectorData>, options: HeatmapOptions) -> Plot<'a> {
+        Plot::try_heatmap_with(plot, options).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_heatmap_with(plot: &'a Layer<VectorData>, options: HeatmapOptions) -> Result<Plot<'a>, ConspireError> {
+        let z = channel(plot.get_z(), "heatmap", "z")?;
+        Plot::try_grid_heatmap(z, (plot.get_x(), "x"), (plot.get_y(), "y"), options, plot.get_name())
+    }
+
+    /// Make a heatmap from a layer of matrices, panicking if the layer can't be plotted. The matrix is read from the
+    /// z channel, or from the x channel when there's none, and the ticks of the layer place its columns and rows like
+    /// the x and y channels of `heatmap` do. See `try_matrix_heatmap` for a version returning an error instead.
+    pub fn matrix_heatmap(plot: &'a Layer<MatrixData>) -> Plot<'a> {
+        Plot::matrix_heatmap_with(plot, HeatmapOptions::default())
+    }
+
+    pub fn try_matrix_heatmap(plot: &'a Layer<MatrixData>) -> Result<Plot<'a>, ConspireError> {
+        Plot::try_matrix_heatmap_with(plot, HeatmapOptions::default())
+    }
+
+    /// Make a heatmap from a layer of matrices, colored and labelled with the given options, panicking if the layer
+    /// can't be plotted or the options are invalid. See `try_matrix_heatmap_with` for a version returning an error
+    /// instead.
+    pub fn matrix_heatmap_with(plot: &'a Layer<MatrixData>, options: HeatmapOptions) -> Plot<'a> {
+        Plot::try_matrix_heatmap_with(plot, options).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_matrix_heatmap_with(
+        plot: &'a Layer<MatrixData>,
+        options: HeatmapOptions,
+    ) -> Result<Plot<'a>, ConspireError> {
+        let z = match plot.get_z() {
+            Some(z) => z,
+            None => channel(plot.get_x(), "heatmap", "z")?,
+        };
+        let (x, y) = ((plot.get_x_ticks(), "x ticks"), (plot.get_y_ticks(), "y ticks"));
+        Plot::try_grid_heatmap(z, x, y, options, plot.get_name())
+    }
+
+    /// A heatmap of a matrix placed by the given coordinates, named after the channels they were read from
+    fn try_grid_heatmap(
+        z: &'a MatrixData,
+        (x, x_channel): (&'a Option<VectorData>, &'static str),
+        (y, y_channel): (&'a Option<VectorData>, &'static str),
+        options: HeatmapOptions,
+        name: &'a Option<String>,
+    ) -> Result<Plot<'a>, ConspireError> {
+        let (rows, columns) = grid_size(z, "heatmap")?;
+        grid_coordinates(columns, x, x_channel)?;
+        grid_coordinates(rows, y, y_channel)?;
+
+        for (setting, value) in &[("zmin", options.zmin), ("zmax", options.zmax), ("midpoint", options.midpoint)] {
+            match value {
+                Some(v) if !v.is_finite() => {
+                    return Err(ConspireError::InvalidSetting(format!(
+                        "the heatmap {} must be finite, not {}",
+                        setting, v
+                    )))
+                }
+                _ => {}
+            }
+        }
+        if let (Some(min), Some(max)) = (options.zmin, options.zmax) {
+            if min >= max {
+                return Err(ConspireError::InvalidSetting(format!(
+                    "the heatmap zmin ({}) must be below its zmax ({})",
+                    min, max
+                )));
+            }
+        }
+
+        valid_colorscale(&options.colorscale)?;
+
+        if let Annotations::Text(text) = &options.annotations {
+            if text.len() != rows || text.iter().any(|r| r.len() != columns) {
+                return Err(ConspireError::InvalidData(String::from(
+                    "heatmap annotations need a text for every cell",
+                )));
+            }
+        }
+
+        Ok(Plot::Heatmap { x, y, z, options, name })
+    }
+
+    /// Make a contour plot from a layer with a matrix in its z channel, panicking if the layer can't be plotted. Lines
+    /// join the points of the grid with the same value, with the x and y channels placing its columns and rows like
+    /// they do for `heatmap`, though at the centers of the cells. See `try_contour` for a version returning an error
+    /// instead.
+    pub fn contour(plot: &'a Layer<VectorData>) -> Plot<'a> {
+        Plot::contour_with(plot, ContourOptions::default())
+    }
+
+    pub fn try_contour(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        Plot::try_contour_with(plot, ContourOptions::default())
+    }
+
+    /// Make a contour plot from a layer with a matrix in its z channel, with the given number of lines and
+    /// colorscale, panicking if the layer can't be plotted or the options are invalid. See `try_contour_with` for a
+    /// version returning an error instead.
+    pub fn contour_with(plot: &'a Layer<VectorData>, options: ContourOptions) -> Plot<'a> {
+        Plot::try_contour_with(plot, options).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_contour_with(plot: &'a Layer<VectorData>, options: ContourOptions) -> Result<Plot<'a>, ConspireError> {
+        let z = channel(plot.get_z(), "contour", "z")?;
+        let (rows, columns) = grid_size(z, "contour")?;
+        grid_coordinates(columns, plot.get_x(), "x")?;
+        grid_coordinates(rows, plot.get_y(), "y")?;
+        if options.levels == 0 {
+            return Err(ConspireError::InvalidSetting(String::from("a contour plot needs at least one level")));
+        }
+        if options.levels > stats::MAX_BINS {
+            return Err(ConspireError::InvalidSetting(format!(
+                "a contour plot can have at most {} levels, not {}",
+                stats::MAX_BINS,
+                options.levels
+            )));
+        }
+        valid_colorscale(&options.colorscale)?;
+        Ok(Plot::Contour {
+            x: plot.get_x(),
+            y: plot.get_y(),
+            z,
+            options,
+            name: plot.get_name(),
+        })
+    }
+
+    /// Make a 3D surface from a layer with a matrix in its z channel, panicking if the layer can't be plotted. The
+    /// matrix gives the height of each point of the grid, placed by the x and y channels like for `contour`. Surfaces
+    /// are only drawn by the backends with 3D plots, and can't share a plot with flat plots. See `try_surface` for a
+    /// version returning an error instead.
+    pub fn surface(plot: &'a Layer<VectorData>) -> Plot<'a> {
+        Plot::surface_with(plot, Colorscale::default())
+    }
+
+    pub fn try_surface(plot: &'a Layer<VectorData>) -> Result<Plot<'a>, ConspireError> {
+        Plot::try_surface_with(plot, Colorscale::default())
+    }
+
+    /// Make a 3D surface from a layer with a matrix in its z channel, colored by height with the given colorscale,
+    /// panicking if the layer can't be plotted or the colorscale is invalid. See `try_surface_with` for a version
+    /// returning an error instead.
+    pub fn surface_with(plot: &'a Layer<VectorData>, colorscale: Colorscale) -> Plot<'a> {
+        Plot::try_surface_with(plot, colorscale).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_surface_with(plot: &'a Layer<VectorData>, colorscale: Colorscale) -> Result<Plot<'a>, ConspireError> {
+        let z = channel(plot.get_z(), "surface", "z")?;
+        let (rows, columns) = grid_size(z, "surface")?;
+        grid_coordinates(columns, plot.get_x(), "x")?;
+        grid_coordinates(rows, plot.get_y(), "y")?;
+        valid_colorscale(&colorscale)?;
+        Ok(Plot::Surface {
+            x: plot.get_x(),
+            y: plot.get_y(),
+            z,
+            colorscale,
+            name: plot.get_name(),
+        })
+    }
+}
+
+/// Get a channel a plot needs, or an error naming the plot and the missing channel
+fn channel<'a, T>(data: &'a Option<T>, plot: &'static str, channel: &'static str) -> Result<&'a T, ConspireError> {
+    data.as_ref().ok_or(ConspireError::MissingChannel { plot, channel })
+}
+
+/// Check that a channel holds one value per data point
+fn same_length(expected: usize, data: &VectorData, channel: &'static str) -> Result<(), ConspireError> {
+    if data.len() == expected {
+        Ok(())
+    } else {
+        Err(ConspireError::LengthMismatch {
+            channel,
+            expected,
+            found: data.len(),
+        })
+    }
+}
+
+/// Check that an optional channel holds either one value per data point, or a single value used for all of them
+fn broadcastable(expected: usize, data: &Option<VectorData>, channel: &'static str) -> Result<(), ConspireError> {
+    match data {
+        Some(data) if data.len() != 1 => same_length(expected, data, channel),
+        _ => Ok(()),
+    }
+}
+
+/// The number of rows and columns of a matrix, which must all be the same length to make a grid
+fn grid_size(z: &MatrixData, plot: &str) -> Result<(usize, usize), ConspireError> {
+    let MatrixData::Quantitative(rows) = z;
+    let columns = rows.first().map_or(0, |r| r.len());
+    if rows.iter().any(|r| r.len() != columns) {
+        return Err(ConspireError::InvalidData(format!("{} rows must all have the same length", plot)));
+    }
+    Ok((rows.len(), columns))
+}
+
+/// Check that a custom colorscale has colors at increasing positions from 0 to 1
+fn valid_colorscale(colorscale: &Colorscale) -> Result<(), ConspireError> {
+    if let Colorscale::Custom(stops) = colorscale {
+        if stops.len() < 2 {
+            return Err(ConspireError::InvalidSetting(String::from("a colorscale needs at least two colors")));
+        }
+        let (first, last) = (stops[0].0, stops[stops.len() - 1].0);
+        let increasing = |a: f64, b: f64| matches!(a.partial_cmp(&b), Some(Ordering::Less | Ordering::Equal));
+        if first != 0.0 || last != 1.0 || !stops.windows(2).all(|w| increasing(w[0].0, w[1].0)) {
+            return Err(ConspireError::InvalidSetting(String::from(
+                "colorscale positions must increase from 0 to 1",
+            )));
+        }
+        if let Some((_, color)) = stops.iter().find(|(_, c)| Rgb::parse(c).is_none()) {
+            return Err(ConspireError::InvalidSetting(format!("\"{}\" isn't a color", color)));
         }
     }
+    Ok(())
+}
+
+/// Check that an optional channel places every cell along one side of a grid, either with a value per cell or, when
+/// the values are numbers, with the edges between cells
+fn grid_coordinates(cells: usize, data: &Option<VectorData>, channel: &'static str) -> Result<(), ConspireError> {
+    match data {
+        Some(VectorData::Quantitative(v)) | Some(VectorData::Temporal(v)) if v.len() == cells + 1 => Ok(()),
+        Some(data) => same_length(cells, data, channel),
+        None => Ok(()),
+    }
 }
 
 /// A plot under construction
 pub struct PlotBuilder<'a> {
     backend: Backend,
     display: bool,
+    plotly_js: PlotlyJs,
     data: Vec<Plot<'a>>,
 }
 
@@ -187,6 +785,7 @@ impl<'a> PlotBuilder<'a> {
         Self {
             backend,
             display: false,
+            plotly_js: PlotlyJs::default(),
             data: Vec::new(),
         }
     }
@@ -206,12 +805,20 @@ impl<'a> PlotBuilder<'a> {
         self
     }
 
+    /// Set where the Plotly backend loads plotly.js from. Use `PlotlyJs::Inline` for self-contained pages that work
+    /// without a network connection.
+    pub fn plotly_js(mut self, source: PlotlyJs) -> Self {
+        self.plotly_js = source;
+        self
+    }
+
     pub fn build(self) -> PlotSystem<'a> {
-        if self.data.len() < 1 { panic!("Cannot make a plot without data") };
+        if self.data.is_empty() { panic!("Cannot make a plot without data") };
 
         PlotSystem {
             backend: self.backend,
             display: self.display,
+            plotly_js: self.plotly_js,
             data: self.data,
         }
     }
@@ -220,12 +827,339 @@ impl<'a> PlotBuilder<'a> {
 pub struct PlotSystem<'a> {
     backend: Backend,
     display: bool,
+    plotly_js: PlotlyJs,
     data: Vec<Plot<'a>>,
 }
 
 impl<'a> PlotSystem<'a> {
-    pub fn render(&self) {
-        let backend = self.backend.clone();
-        let _ = backend.to_struct().render(&self.data, self.display);
+    /// Render the plot to the backend's default output, opening it in a viewer if `display` is set
+    pub fn render(&self) -> Result<(), ConspireError> {
+        self.backend.to_struct(&self.plotly_js).render(&self.data, self.display)
+    }
+
+    /// Show the plot inline in a Jupyter notebook running the [evcxr](https://github.com/google/evcxr) kernel. Plotly
+    /// plots are shown as interactive HTML, while most other backends fall back to a static SVG image. A plot that
+    /// can't be rendered shows the error in its place.
+    pub fn evcxr_display(&self) {
+        println!("{}", self.evcxr_content());
+    }
+
+    /// The content block evcxr reads from stdout: the plot as HTML, or the error as plain text
+    fn evcxr_content(&self) -> String {
+        match self.backend.to_struct(&self.plotly_js).html_fragment(&self.data) {
+            Ok(html) => format!("EVCXR_BEGIN_CONTENT text/html\n{}\nEVCXR_END_CONTENT", html),
+            Err(e) => format!("EVCXR_BEGIN_CONTENT text/plain\nCouldn't render plot: {}\nEVCXR_END_CONTENT", e),
+        }
+    }
+
+    /// Render the plot into a writer instead of the backend's usual output, e.g. to print a terminal plot to
+    /// something other than stdout.
+    pub fn render_to_writer<W: io::Write>(&self, writer: &mut W) -> Result<(), ConspireError> {
+        self.backend.to_struct(&self.plotly_js).write(&self.data, writer)
+    }
+
+    /// Render the plot into a string, e.g. to return it in an HTTP response. Fails for backends producing binary
+    /// output, such as PNG images.
+    pub fn render_to_string(&self) -> Result<String, ConspireError> {
+        let mut buffer = Vec::new();
+        self.render_to_writer(&mut buffer)?;
+        String::from_utf8(buffer)
+            .map_err(|_| ConspireError::Render(String::from("the backend's output isn't text")))
+    }
+
+    /// Render the plot into the file at `path`, rather than the backend's default output file. The file is written
+    /// as-is and not opened in a viewer, regardless of the `display` setting. Nothing is written when the plot can't
+    /// be rendered.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConspireError> {
+        let mut buffer = Vec::new();
+        self.render_to_writer(&mut buffer)?;
+        fs::write(path, buffer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn missing(result: Result<Plot, ConspireError>) -> (&'static str, &'static str) {
+        match result {
+            Err(ConspireError::MissingChannel { plot, channel }) => (plot, channel),
+            other => panic!("expected a missing channel, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn required_channels() {
+        let empty = Layer::new();
+        let x = Layer::new().x(vec![1.0, 2.0]);
+        assert_eq!(missing(Plot::try_scatter(&empty)), ("scatter", "x"));
+        assert_eq!(missing(Plot::try_scatter(&x)), ("scatter", "y"));
+        assert_eq!(missing(Plot::try_line(&empty)), ("line", "x"));
+        assert_eq!(missing(Plot::try_line(&x)), ("line", "y"));
+        assert_eq!(missing(Plot::try_bar(&empty)), ("bar", "x"));
+        assert_eq!(missing(Plot::try_bar(&x)), ("bar", "y"));
+        assert_eq!(missing(Plot::try_horizontal_bar(&empty)), ("horizontal bar", "x"));
+        assert_eq!(missing(Plot::try_horizontal_bar(&x)), ("horizontal bar", "y"));
+        assert_eq!(missing(Plot::try_line_with(&x, Missing::Interpolate)), ("line", "y"));
+        assert_eq!(missing(Plot::try_pie(&empty)), ("pie", "x"));
+        assert_eq!(missing(Plot::try_boxplot(&empty)), ("box", "x"));
+        assert_eq!(missing(Plot::try_boxplot_with(&empty, BoxOptions::default())), ("box", "x"));
+        assert_eq!(missing(Plot::try_histogram(&empty)), ("histogram", "x"));
+        assert_eq!(missing(Plot::try_histogram_with(&empty, Binning::Sturges)), ("histogram", "x"));
+        assert_eq!(missing(Plot::try_violin(&empty)), ("violin", "x"));
+        assert_eq!(
+            missing(Plot::try_violin_with(&empty, Bandwidth::default(), ViolinInner::default())),
+            ("violin", "x")
+        );
+        assert_eq!(missing(Plot::try_treemap(&empty)), ("treemap", "label"));
+        assert_eq!(missing(Plot::try_treemap(&Layer::new().label(vec!["root"]))), ("treemap", "parent"));
+        assert_eq!(missing(Plot::try_parallel_coordinates(&x)), ("parallel coordinates", "dimension"));
+        assert_eq!(missing(Plot::try_heatmap(&x)), ("heatmap", "z"));
+        assert_eq!(missing(Plot::try_heatmap_with(&x, HeatmapOptions::default())), ("heatmap", "z"));
+    }
+
+    fn mismatch(result: Result<Plot, ConspireError>) -> (&'static str, usize, usize) {
+        match result {
+            Err(ConspireError::LengthMismatch { channel, expected, found }) => (channel, expected, found),
+            other => panic!("expected a length mismatch, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn channel_lengths() {
+        let layer = Layer::new().x(vec![1.0, 2.0, 3.0]).y(vec![1.0, 2.0]);
+        assert_eq!(mismatch(Plot::try_scatter(&layer)), ("y", 3, 2));
+        let layer = Layer::new().x(vec![1.0, 2.0, 3.0]).y(vec![1.0, 2.0, 3.0]).color(vec!["a", "b"]);
+        assert_eq!(mismatch(Plot::try_line(&layer)), ("color", 3, 2));
+        let layer = Layer::new().x(vec![1.0, 2.0, 3.0]).y(vec![1.0, 2.0, 3.0]).size(vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(mismatch(Plot::try_scatter(&layer)), ("size", 3, 4));
+        let layer = Layer::new().label(vec!["root", "leaf"]).parent(vec![""]);
+        assert_eq!(mismatch(Plot::try_treemap(&layer)), ("parent", 2, 1));
+
+        assert!(same_length(2, &VectorData::Quantitative(vec![1.0, 2.0]), "y").is_ok());
+        assert!(broadcastable(2, &None, "color").is_ok());
+    }
+
+    #[test]
+    fn single_values_are_broadcast() {
+        let layer = Layer::new().x(vec![1.0, 2.0, 3.0]).y(vec![1.0, 2.0, 3.0]).color(vec!["red"]).size(vec![4.0]);
+        assert!(Plot::try_scatter(&layer).is_ok());
+        assert!(Plot::try_line(&layer).is_ok());
+        assert!(Plot::try_bar(&Layer::new().x(vec!["a", "b"]).y(vec![1.0, 2.0]).color(vec!["red"])).is_ok());
+
+        // Only the color and size channels are broadcast, while a single x or y is a single data point
+        assert!(Plot::try_scatter(&Layer::new().x(vec![1.0]).y(vec![2.0]).color(vec!["a", "b"])).is_err());
+        assert_eq!(mismatch(Plot::try_scatter(&Layer::new().x(vec![1.0]).y(vec![1.0, 2.0]))), ("y", 1, 2));
+        assert_eq!(mismatch(Plot::try_scatter(&Layer::new().x(vec![1.0, 2.0]).y(vec![1.0]))), ("y", 2, 1));
+        assert!(Plot::try_scatter(&Layer::new().x(vec![1.0]).y(vec![2.0])).is_ok());
+    }
+
+    #[test]
+    fn render_to_string_and_writer() {
+        let layer = Layer::new().x(vec!["a", "b"]).y(vec![1.0, 2.0]);
+        let plot = PlotBuilder::new(Backend::Gnuplot(GnuplotTerminal::Svg)).add_layer(Plot::bar(&layer)).build();
+        let script = plot.render_to_string().unwrap();
+        assert!(script.contains("set terminal svg"));
+        let mut written = Vec::new();
+        plot.render_to_writer(&mut written).unwrap();
+        assert_eq!(written, script.as_bytes());
+
+        let pie = Plot::pie(&layer);
+        let plot = PlotBuilder::new(Backend::Terminal { width: 40, height: 10 }).add_layer(pie).build();
+        assert!(matches!(plot.render_to_string(), Err(ConspireError::UnsupportedPlot { .. })));
+    }
+
+    #[test]
+    fn save() {
+        let path = std::env::temp_dir().join(format!("conspire-save-{}.svg", std::process::id()));
+        let layer = Layer::new().x(vec![1.0, 2.0]).y(vec![3.0, 4.0]);
+        let plot = PlotBuilder::new(Backend::Svg).display(true).add_layer(Plot::line(&layer)).build();
+        plot.save(&path).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(saved, plot.render_to_string().unwrap());
+
+        let pie = Plot::pie(&layer);
+        let plot = PlotBuilder::new(Backend::Terminal { width: 40, height: 10 }).add_layer(pie).build();
+        assert!(plot.save(&path).is_err());
+        assert!(!path.exists());
+    }
+
+    fn invalid_setting(result: Result<Plot, ConspireError>) -> String {
+        match result {
+            Err(ConspireError::InvalidSetting(reason)) => reason,
+            other => panic!("expected an invalid setting, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn heatmap_settings() {
+        let layer = Layer::new().z(vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+        let heatmap = |options: HeatmapOptions| Plot::try_heatmap_with(&layer, options);
+
+        let midpoint = HeatmapOptions { midpoint: Some(f64::NAN), ..Default::default() };
+        assert_eq!(invalid_setting(heatmap(midpoint)), "the heatmap midpoint must be finite, not NaN");
+        let zmax = HeatmapOptions { zmax: Some(f64::INFINITY), ..Default::default() };
+        assert_eq!(invalid_setting(heatmap(zmax)), "the heatmap zmax must be finite, not inf");
+        let limits = HeatmapOptions { zmin: Some(2.0), zmax: Some(2.0), ..Default::default() };
+        assert_eq!(invalid_setting(heatmap(limits)), "the heatmap zmin (2) must be below its zmax (2)");
+        assert!(heatmap(HeatmapOptions { midpoint: Some(0.0), zmin: Some(-1.0), ..Default::default() }).is_ok());
+
+        let colorscale = |stops: &[(f64, &str)]| HeatmapOptions {
+            colorscale: Colorscale::Custom(stops.iter().map(|(t, c)| (*t, c.to_string())).collect()),
+            ..Default::default()
+        };
+        let too_few = colorscale(&[(0.0, "red")]);
+        assert_eq!(invalid_setting(heatmap(too_few)), "a colorscale needs at least two colors");
+        let unordered: [&[(f64, &str)]; 2] =
+            [&[(0.1, "red"), (1.0, "blue")], &[(0.0, "red"), (0.7, "white"), (0.5, "blue"), (1.0, "red")]];
+        for stops in &unordered {
+            assert_eq!(invalid_setting(heatmap(colorscale(stops))), "colorscale positions must increase from 0 to 1");
+        }
+        let unknown = colorscale(&[(0.0, "red"), (1.0, "sparkly")]);
+        assert_eq!(invalid_setting(heatmap(unknown)), "\"sparkly\" isn't a color");
+        assert!(heatmap(colorscale(&[(0.0, "#ff8800"), (0.5, "white"), (1.0, "blue")])).is_ok());
+    }
+
+    #[test]
+    fn heatmap_annotations_cover_every_cell() {
+        let layer = Layer::new().z(vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+        let text = |rows: Vec<Vec<&str>>| HeatmapOptions {
+            annotations: Annotations::Text(rows.iter().map(|r| r.iter().map(|t| t.to_string()).collect()).collect()),
+            ..Default::default()
+        };
+        for rows in [vec![vec!["a", "b"]], vec![vec!["a", "b"], vec!["c"]]] {
+            match Plot::try_heatmap_with(&layer, text(rows)) {
+                Err(ConspireError::InvalidData(_)) => {}
+                other => panic!("expected missing annotations to be rejected, got {:?}", other.map(|_| ())),
+            }
+        }
+        assert!(Plot::try_heatmap_with(&layer, text(vec![vec!["a", "b"], vec!["c", "d"]])).is_ok());
+    }
+
+    #[test]
+    fn grid_coordinates_of_a_matrix() {
+        let centers = Some(VectorData::Quantitative(vec![0.0, 1.0, 4.0]));
+        let edges = Some(VectorData::Temporal(vec![0.0, 1.0, 2.0, 10.0]));
+        let categories = Some(VectorData::Categorical(vec![String::from("a"), String::from("b"), String::from("c")]));
+        for coordinates in &[None, centers, edges, categories] {
+            assert!(grid_coordinates(3, coordinates, "x").is_ok());
+        }
+
+        let layer = Layer::new().z(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]).x(vec![0.0, 1.0, 4.0]).y(vec![2, 3]);
+        assert!(Plot::try_heatmap(&layer).is_ok());
+        let layer = Layer::new().z(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).x(vec![0.0, 0.5, 2.0]).y(vec!["a", "b"]);
+        assert!(Plot::try_heatmap(&layer).is_ok());
+    }
+
+    #[test]
+    fn grid_coordinates_of_the_wrong_size() {
+        let categories = Some(VectorData::Categorical(vec![String::from("a"), String::from("b")]));
+        match grid_coordinates(1, &categories, "x") {
+            Err(ConspireError::LengthMismatch { channel: "x", expected: 1, found: 2 }) => {}
+            other => panic!("expected only numbers to be taken as edges, got {:?}", other),
+        }
+        let edges = Some(VectorData::Quantitative(vec![0.0, 1.0, 2.0, 3.0]));
+        assert!(grid_coordinates(2, &edges, "y").is_err());
+
+        let layer = Layer::new().z(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).x(vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(mismatch(Plot::try_heatmap(&layer)), ("x", 2, 4));
+        let layer = Layer::new().z(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).y(vec!["a", "b", "c"]);
+        assert_eq!(mismatch(Plot::try_heatmap(&layer)), ("y", 2, 3));
+    }
+
+    #[test]
+    fn flat_triples_are_not_a_grid() {
+        // The z channel holds a matrix, so a single row of values with an x and y per value is a row of cells
+        // placed at the x coordinates, which leaves a y coordinate per cell too many
+        let layer = Layer::new()
+            .x(vec![0.0, 1.0, 0.0, 1.0])
+            .y(vec![0.0, 0.0, 1.0, 1.0])
+            .z(vec![vec![1.0, 2.0, 3.0, 4.0]]);
+        assert_eq!(mismatch(Plot::try_heatmap(&layer)), ("y", 1, 4));
+        let layer = Layer::new().x(vec![0.0, 1.0]).y(vec![0.0, 1.0]).z(vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+        assert!(Plot::try_heatmap(&layer).is_ok());
+    }
+
+    #[test]
+    fn matrix_heatmaps() {
+        let layer = Layer::new().x(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).x_ticks(vec!["a", "b"]).y_ticks(vec![1, 2]);
+        match Plot::try_matrix_heatmap(&layer) {
+            Ok(Plot::Heatmap { x: Some(_), y: Some(_), z: MatrixData::Quantitative(rows), .. }) => {
+                assert_eq!(rows.len(), 2)
+            }
+            other => panic!("expected a heatmap placed by the ticks, got {:?}", other.map(|_| ())),
+        }
+        let layer = Layer::new().z(vec![vec![1.0, 2.0]]).x_ticks(vec!["a", "b", "c"]);
+        assert_eq!(mismatch(Plot::try_matrix_heatmap(&layer)), ("x ticks", 2, 3));
+        let layer: Layer<MatrixData> = Layer::new().y_ticks(vec![1.0]);
+        assert_eq!(missing(Plot::try_matrix_heatmap(&layer)), ("heatmap", "z"));
+    }
+
+    #[test]
+    fn contour_and_surface_settings() {
+        let layer = Layer::new().z(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).x(vec![0.0, 1.0]);
+        assert!(Plot::try_contour(&layer).is_ok());
+        assert!(Plot::try_surface(&layer).is_ok());
+
+        let contour = |levels: usize| Plot::try_contour_with(&layer, ContourOptions { levels, ..Default::default() });
+        assert_eq!(invalid_setting(contour(0)), "a contour plot needs at least one level");
+        assert_eq!(
+            invalid_setting(contour(stats::MAX_BINS + 1)),
+            format!("a contour plot can have at most {} levels, not {}", stats::MAX_BINS, stats::MAX_BINS + 1)
+        );
+        let colorscale = Colorscale::Custom(vec![(0.0, String::from("red"))]);
+        assert_eq!(
+            invalid_setting(Plot::try_surface_with(&layer, colorscale)),
+            "a colorscale needs at least two colors"
+        );
+
+        assert_eq!(missing(Plot::try_contour(&Layer::new().x(vec![1.0]))), ("contour", "z"));
+        assert_eq!(missing(Plot::try_surface(&Layer::new().x(vec![1.0]))), ("surface", "z"));
+        let layer = Layer::new().z(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).y(vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(mismatch(Plot::try_surface(&layer)), ("y", 2, 4));
+        let ragged = Layer::new().z(vec![vec![1.0, 2.0], vec![3.0]]);
+        match Plot::try_contour(&ragged) {
+            Err(ConspireError::InvalidData(reason)) => assert_eq!(reason, "contour rows must all have the same length"),
+            other => panic!("expected a ragged grid to be rejected, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn box_plot_of_categories_needs_y() {
+        let layer = Layer::new().x(vec!["a", "b"]);
+        match Plot::try_boxplot(&layer) {
+            Err(ConspireError::MissingChannel { plot: "box", channel: "y" }) => {}
+            other => panic!("expected a missing y channel, got {:?}", other.map(|_| ())),
+        }
+        assert!(Plot::try_boxplot(&Layer::new().x(vec![1.0, 2.0])).is_ok());
+    }
+
+    #[test]
+    fn histogram_bins_are_limited() {
+        let layer = Layer::new().x(vec![0.0, 1e12]);
+        for binning in &[Binning::Width(1e-3), Binning::Count(usize::MAX)] {
+            match Plot::try_histogram_with(&layer, *binning) {
+                Err(ConspireError::InvalidSetting(_)) => {}
+                other => panic!("expected too many bins to be rejected, got {:?}", other.map(|_| ())),
+            }
+        }
+        assert!(Plot::try_histogram_with(&layer, Binning::Count(stats::MAX_BINS)).is_ok());
+    }
+
+    #[test]
+    fn evcxr_content() {
+        let layer = Layer::new().x(vec![1.0, 2.0]).y(vec![3.0, 4.0]);
+        let plot = PlotBuilder::new(Backend::Svg).add_layer(Plot::scatter(&layer)).build();
+        let html = Backend::Svg.to_struct(&PlotlyJs::Cdn).html_fragment(&[Plot::scatter(&layer)]).unwrap();
+        assert_eq!(plot.evcxr_content(), format!("EVCXR_BEGIN_CONTENT text/html\n{}\nEVCXR_END_CONTENT", html));
+
+        let missing = PlotlyJs::Inline(std::path::PathBuf::from("does/not/exist/plotly.min.js"));
+        let plot = PlotBuilder::new(Backend::Plotly).plotly_js(missing).add_layer(Plot::scatter(&layer)).build();
+        let content = plot.evcxr_content();
+        assert!(content.starts_with("EVCXR_BEGIN_CONTENT text/plain\nCouldn't render plot: "));
+        assert!(content.contains("does/not/exist/plotly.min.js"));
+        assert!(content.ends_with("\nEVCXR_END_CONTENT"));
     }
 }
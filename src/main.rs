@@ -1,7 +1,7 @@
 use conspire::{Backend, Layer, Plot, PlotBuilder};
 
 fn main() {
-    let layer1 = Layer::new()
+    let _layer1 = Layer::new()
         .x(vec![1.0, 1.3, 2.0, 2.7, 3.0, 4.0, 5.1, 6.2, 6.3])
         .y(vec![8.0, 8.1, 7.0, 6.4, 5.0, 4.0, 4.2, 4.2, 4.3])
         .name("No");
@@ -15,5 +15,7 @@ fn main() {
         .add_layer(Plot::boxplot(&layer2))
         .build();
 
-    plot.render();
+    if let Err(e) = plot.render() {
+        eprintln!("{}", e);
+    }
 }
@@ -0,0 +1,502 @@
+//! Statistics computed inside Conspire, so that every backend draws the same summary of the data.
+
+use crate::data::VectorData;
+
+/// The five-number summary of a box plot, plus the points lying outside the whiskers
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BoxStats {
+    pub lower_whisker: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub upper_whisker: f64,
+    pub outliers: Vec<f64>,
+    pub mean: f64,
+    /// The ends of the notch around the median, `median ± 1.57 IQR / √n`
+    pub notch: (f64, f64),
+}
+
+/// How far the whiskers of a box plot reach
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Whiskers {
+    /// To the most extreme values within 1.5 IQR of the box, with anything further out drawn as an outlier
+    #[default]
+    Tukey,
+    /// To the smallest and largest values, without any outliers
+    MinMax,
+}
+
+/// How to draw the boxes of a box plot
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BoxOptions {
+    pub whiskers: Whiskers,
+    /// Narrow each box around its median, showing a rough 95% confidence interval of the median
+    pub notched: bool,
+    /// Draw a dashed line across each box at the mean
+    pub mean: bool,
+}
+
+/// Sort a slice of numbers, dropping missing values and infinities
+pub(crate) fn sorted(values: &[f64]) -> Vec<f64> {
+    let mut sorted: Vec<f64> = values.iter().cloned().filter(|v| v.is_finite()).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    sorted
+}
+
+/// Linearly interpolated quantile of already sorted, non-empty data
+pub(crate) fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    let fraction = position - lower as f64;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * fraction
+}
+
+/// Summarize data for a box plot, with whiskers reaching as far as the rule allows
+pub(crate) fn box_stats(values: &[f64], whiskers: Whiskers) -> Option<BoxStats> {
+    let sorted = sorted(values);
+    if sorted.is_empty() {
+        return None;
+    }
+
+    let q1 = quantile(&sorted, 0.25);
+    let median = quantile(&sorted, 0.5);
+    let q3 = quantile(&sorted, 0.75);
+    let (low_fence, high_fence) = match whiskers {
+        Whiskers::Tukey => (q1 - 1.5 * (q3 - q1), q3 + 1.5 * (q3 - q1)),
+        Whiskers::MinMax => (f64::NEG_INFINITY, f64::INFINITY),
+    };
+
+    let inside = sorted.iter().filter(|v| **v >= low_fence && **v <= high_fence);
+    let lower_whisker = inside.clone().cloned().fold(q1, f64::min);
+    let upper_whisker = inside.cloned().fold(q3, f64::max);
+    let outliers = sorted
+        .iter()
+        .cloned()
+        .filter(|v| *v < low_fence || *v > high_fence)
+        .collect();
+    let notch = 1.57 * (q3 - q1) / (sorted.len() as f64).sqrt();
+
+    Some(BoxStats {
+        lower_whisker,
+        q1,
+        median,
+        q3,
+        upper_whisker,
+        outliers,
+        mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+        notch: (median - notch, median + notch),
+    })
+}
+
+/// How to divide quantitative data into the bins of a histogram
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Binning {
+    /// `log2(n) + 1` bins, which suits roughly normally distributed data
+    #[default]
+    Sturges,
+    /// Bins `3.49 σ n^(-1/3)` wide, which minimizes the error for normally distributed data
+    Scott,
+    /// Bins `2 IQR n^(-1/3)` wide. Uses the interquartile range rather than the standard deviation, so it copes better
+    /// with outliers and skewed data, such as latencies.
+    FreedmanDiaconis,
+    /// A fixed number of equally wide bins, up to 10 000
+    Count(usize),
+    /// Bins of a fixed width, starting at the smallest value. The width must leave at most 10 000 bins.
+    Width(f64),
+}
+
+/// A bin of a histogram, holding the values from `start` up to (but not including) `end`. The last bin also holds
+/// values equal to its end.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Bin {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+}
+
+/// Binned quantitative data, or counted categorical data
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Histogram {
+    Bins(Vec<Bin>),
+    Counts(Vec<(String, usize)>),
+}
+
+/// Count the data of a histogram. Quantitative data and points in time are binned, ignoring non-finite values, while
+/// each category of categorical data is counted in the order they first appear. Ordinal data counts every one of its
+/// categories in order, including those without any values.
+pub(crate) fn histogram(data: &VectorData, binning: Binning) -> Histogram {
+    match data {
+        VectorData::Quantitative(v) | VectorData::Temporal(v) => Histogram::Bins(bins(v, binning)),
+        VectorData::Categorical(v) | VectorData::Ordinal { values: v, .. } => {
+            let mut counts: Vec<(String, usize)> = match data {
+                VectorData::Ordinal { order, .. } => order.iter().map(|c| (c.to_string(), 0)).collect(),
+                _ => Vec::new(),
+            };
+            for c in v {
+                match counts.iter_mut().find(|(category, _)| category == c) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((c.to_string(), 1)),
+                }
+            }
+            Histogram::Counts(counts)
+        }
+    }
+}
+
+/// The most bins a histogram can have, which keeps a tiny width over a wide range from exhausting the memory
+pub(crate) const MAX_BINS: usize = 10_000;
+
+/// The number of bins a rule divides the finite values into, before limiting it to `MAX_BINS`. Values which are all
+/// equal get a single bin, and no values get none.
+pub(crate) fn bin_count(values: &[f64], binning: Binning) -> usize {
+    let sorted: Vec<f64> = sorted(values).into_iter().filter(|v| v.is_finite()).collect();
+    let (min, max) = match (sorted.first(), sorted.last()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return 0,
+    };
+    if min == max {
+        return 1;
+    }
+
+    let n = sorted.len() as f64;
+    let range = max - min;
+    let sturges = (n.log2().ceil() as usize + 1).max(1);
+    match binning {
+        Binning::Sturges => sturges,
+        Binning::Scott => count_for_width(range, 3.49 * standard_deviation(&sorted) * n.powf(-1.0 / 3.0), sturges),
+        Binning::FreedmanDiaconis => {
+            let iqr = quantile(&sorted, 0.75) - quantile(&sorted, 0.25);
+            count_for_width(range, 2.0 * iqr * n.powf(-1.0 / 3.0), sturges)
+        }
+        Binning::Count(count) => count.max(1),
+        Binning::Width(width) => count_for_width(range, width, sturges),
+    }
+}
+
+fn bins(values: &[f64], binning: Binning) -> Vec<Bin> {
+    let sorted: Vec<f64> = sorted(values).into_iter().filter(|v| v.is_finite()).collect();
+    let (min, max) = match (sorted.first(), sorted.last()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return Vec::new(),
+    };
+
+    // All values being equal leaves nothing to divide, so they get a single bin of width one around them
+    if min == max {
+        return vec![Bin {
+            start: min - 0.5,
+            end: max + 0.5,
+            count: sorted.len(),
+        }];
+    }
+
+    let count = bin_count(&sorted, binning);
+    let width = match binning {
+        Binning::Width(width) if width > 0.0 && width.is_finite() && count <= MAX_BINS => width,
+        _ => (max - min) / count.min(MAX_BINS) as f64,
+    };
+    let count = count.min(MAX_BINS);
+
+    let mut bins: Vec<Bin> = (0..count)
+        .map(|i| Bin {
+            start: min + i as f64 * width,
+            end: min + (i + 1) as f64 * width,
+            count: 0,
+        })
+        .collect();
+    for v in sorted {
+        let idx = (((v - min) / width).floor() as usize).min(count - 1);
+        bins[idx].count += 1;
+    }
+    bins
+}
+
+/// The number of bins of a given width needed to cover a range, falling back to `fallback` when the width is zero
+/// (e.g. when most values are equal)
+fn count_for_width(range: f64, width: f64, fallback: usize) -> usize {
+    if width > 0.0 && width.is_finite() {
+        ((range / width).ceil() as usize).max(1)
+    } else {
+        fallback
+    }
+}
+
+fn standard_deviation(values: &[f64]) -> f64 {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0)).sqrt()
+}
+
+/// How to choose the bandwidth of the kernel density estimate of a violin plot
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Bandwidth {
+    /// Silverman's rule of thumb, `0.9 min(σ, IQR / 1.34) n^(-1/5)`, which copes with skewed data and outliers
+    #[default]
+    Silverman,
+    /// Scott's rule, `1.06 σ n^(-1/5)`, which suits roughly normally distributed data
+    Scott,
+    /// A fixed bandwidth, in the units of the data
+    Fixed(f64),
+}
+
+/// What to draw inside the density of a violin plot
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ViolinInner {
+    /// A narrow box plot, from the first to the third quartile, with whiskers
+    #[default]
+    Box,
+    /// Lines across the violin at the quartiles
+    Quartiles,
+    /// Only the density
+    None,
+}
+
+/// The density of a group of values, with the box plot statistics drawn inside it
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Violin {
+    pub group: String,
+    /// The estimated density, at evenly spaced values reaching two bandwidths beyond the data
+    pub density: Vec<(f64, f64)>,
+    /// The bandwidth of the kernel the density was estimated with
+    pub bandwidth: f64,
+    pub stats: BoxStats,
+}
+
+/// The number of values the density of a violin is estimated at
+const DENSITY_POINTS: usize = 100;
+
+/// Estimate the density of the quantitative data of a violin plot with a Gaussian kernel, separately for each category
+/// of the groups. Without groups, all values make up a single group with the given name. Groups are kept in the order
+/// they first appear, or in their order for ordinal groups, and non-finite values are dropped.
+pub(crate) fn violins(x: &VectorData, group: &Option<VectorData>, name: &str, bandwidth: Bandwidth) -> Vec<Violin> {
+    let values = match x {
+        VectorData::Quantitative(v) => v,
+        VectorData::Categorical(_) | VectorData::Temporal(_) | VectorData::Ordinal { .. } => return Vec::new(),
+    };
+    let groups = match group {
+        Some(VectorData::Categorical(g) | VectorData::Ordinal { values: g, .. }) => Some(g),
+        _ => None,
+    };
+
+    let mut grouped: Vec<(String, Vec<f64>)> = match group {
+        Some(VectorData::Ordinal { order, .. }) => order.iter().map(|g| (g.to_string(), Vec::new())).collect(),
+        _ => Vec::new(),
+    };
+    for (i, v) in values.iter().enumerate() {
+        let group = groups.and_then(|g| g.get(i)).map_or(name, |g| g.as_str());
+        match grouped.iter_mut().find(|(g, _)| g == group) {
+            Some((_, values)) => values.push(*v),
+            None => grouped.push((group.to_string(), vec![*v])),
+        }
+    }
+
+    grouped
+        .into_iter()
+        .filter_map(|(group, values)| {
+            let finite: Vec<f64> = values.into_iter().filter(|v| v.is_finite()).collect();
+            let stats = box_stats(&finite, Whiskers::Tukey)?;
+            let sorted = sorted(&finite);
+            let bandwidth = kernel_bandwidth(&sorted, bandwidth);
+            Some(Violin {
+                group,
+                density: density(&sorted, bandwidth),
+                bandwidth,
+                stats,
+            })
+        })
+        .collect()
+}
+
+/// The bandwidth a rule gives for sorted, non-empty data. Data without any spread falls back to a tenth of its
+/// magnitude (or one, for zeros), so that it still gets a visible density.
+fn kernel_bandwidth(sorted: &[f64], rule: Bandwidth) -> f64 {
+    let n = sorted.len() as f64;
+    let sd = standard_deviation(sorted);
+    let h = match rule {
+        Bandwidth::Silverman => {
+            let iqr = quantile(sorted, 0.75) - quantile(sorted, 0.25);
+            let spread = if iqr > 0.0 { sd.min(iqr / 1.34) } else { sd };
+            0.9 * spread * n.powf(-0.2)
+        }
+        Bandwidth::Scott => 1.06 * sd * n.powf(-0.2),
+        Bandwidth::Fixed(h) => h,
+    };
+
+    if h > 0.0 && h.is_finite() {
+        h
+    } else {
+        let magnitude = sorted[sorted.len() - 1].abs().max(sorted[0].abs());
+        if magnitude > 0.0 {
+            magnitude / 10.0
+        } else {
+            1.0
+        }
+    }
+}
+
+fn density(sorted: &[f64], h: f64) -> Vec<(f64, f64)> {
+    let (min, max) = (sorted[0] - 2.0 * h, sorted[sorted.len() - 1] + 2.0 * h);
+    let norm = 1.0 / (sorted.len() as f64 * h * (2.0 * std::f64::consts::PI).sqrt());
+
+    (0..DENSITY_POINTS)
+        .map(|i| {
+            let x = min + (max - min) * i as f64 / (DENSITY_POINTS - 1) as f64;
+            let d: f64 = sorted.iter().map(|v| (-0.5 * ((x - v) / h).powi(2)).exp()).sum();
+            (x, d * norm)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(bins: &[Bin]) -> Vec<usize> {
+        bins.iter().map(|b| b.count).collect()
+    }
+
+    #[test]
+    fn sturges_bins() {
+        let values: Vec<f64> = (1..=8).map(f64::from).collect();
+        let bins = bins(&values, Binning::Sturges);
+        assert_eq!(counts(&bins), vec![2, 2, 2, 2]);
+        assert_eq!((bins[0].start, bins[3].end), (1.0, 8.0));
+        assert_eq!(bins[0].end, 2.75);
+    }
+
+    #[test]
+    fn scott_and_freedman_diaconis_bins() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 100.0];
+        // The standard deviation is about 28.6, so Scott's rule gives bins about 44.9 wide
+        assert_eq!(counts(&bins(&values, Binning::Scott)), vec![10, 0, 1]);
+        // The interquartile range is 5, so Freedman-Diaconis gives bins about 4.5 wide, leaving the outlier apart
+        let bins = bins(&values, Binning::FreedmanDiaconis);
+        assert_eq!(bins.len(), 23);
+        assert_eq!((bins[0].count, bins[1].count, bins[22].count), (5, 4, 1));
+    }
+
+    #[test]
+    fn count_and_width_bins() {
+        let bins_of_count = bins(&[1.0, 2.0, 2.0, 3.0], Binning::Count(2));
+        assert_eq!(counts(&bins_of_count), vec![1, 3]);
+        assert_eq!((bins_of_count[1].start, bins_of_count[1].end), (2.0, 3.0));
+
+        let bins_of_width = bins(&[0.0, 0.5, 2.5], Binning::Width(1.0));
+        assert_eq!(counts(&bins_of_width), vec![2, 0, 1]);
+        assert_eq!(bins_of_width[2].end, 3.0);
+    }
+
+    #[test]
+    fn bins_are_limited() {
+        assert_eq!(bin_count(&[0.0, 1e12], Binning::Width(1e-3)), 1_000_000_000_000_000);
+        assert_eq!(bins(&[0.0, 1e12], Binning::Width(1e-3)).len(), MAX_BINS);
+        assert_eq!(bins(&[0.0, 1.0], Binning::Count(usize::MAX)).len(), MAX_BINS);
+    }
+
+    #[test]
+    fn bins_of_degenerate_data() {
+        for binning in &[Binning::Sturges, Binning::Scott, Binning::FreedmanDiaconis, Binning::Count(3)] {
+            assert!(bins(&[], *binning).is_empty());
+            assert!(bins(&[f64::NAN, f64::NAN], *binning).is_empty());
+            assert_eq!(bins(&[4.0], *binning), vec![Bin { start: 3.5, end: 4.5, count: 1 }]);
+            assert_eq!(bins(&[4.0, 4.0, f64::NAN], *binning), vec![Bin { start: 3.5, end: 4.5, count: 2 }]);
+        }
+        assert_eq!(bin_count(&[], Binning::Sturges), 0);
+        assert_eq!(bin_count(&[4.0, 4.0], Binning::Count(5)), 1);
+    }
+
+    #[test]
+    fn sorted_drops_missing_values_and_infinities() {
+        assert_eq!(sorted(&[3.0, f64::NAN, f64::INFINITY, 1.0, f64::NEG_INFINITY, 2.0]), vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn tukey_box() {
+        let stats = box_stats(&[4.0, 1.0, 100.0, 3.0, 2.0, f64::NAN], Whiskers::Tukey).unwrap();
+        assert_eq!((stats.q1, stats.median, stats.q3), (2.0, 3.0, 4.0));
+        assert_eq!((stats.lower_whisker, stats.upper_whisker), (1.0, 4.0));
+        assert_eq!(stats.outliers, vec![100.0]);
+        assert_eq!(stats.mean, 22.0);
+    }
+
+    #[test]
+    fn min_max_box() {
+        let stats = box_stats(&[4.0, 1.0, 100.0, 3.0, 2.0], Whiskers::MinMax).unwrap();
+        assert_eq!((stats.lower_whisker, stats.upper_whisker), (1.0, 100.0));
+        assert!(stats.outliers.is_empty());
+    }
+
+    #[test]
+    fn interpolated_quartiles() {
+        let stats = box_stats(&[1.0, 2.0, 3.0, 4.0], Whiskers::Tukey).unwrap();
+        assert_eq!((stats.q1, stats.median, stats.q3), (1.75, 2.5, 3.25));
+        assert_eq!(stats.notch, (2.5 - 1.57 * 1.5 / 2.0, 2.5 + 1.57 * 1.5 / 2.0));
+    }
+
+    #[test]
+    fn boxes_of_degenerate_data() {
+        assert_eq!(box_stats(&[], Whiskers::Tukey), None);
+        assert_eq!(box_stats(&[f64::NAN, f64::NAN], Whiskers::Tukey), None);
+        let single = box_stats(&[7.0], Whiskers::Tukey).unwrap();
+        assert_eq!(
+            (single.lower_whisker, single.q1, single.median, single.q3, single.upper_whisker),
+            (7.0, 7.0, 7.0, 7.0, 7.0)
+        );
+        assert_eq!((single.mean, single.notch), (7.0, (7.0, 7.0)));
+        assert!(single.outliers.is_empty());
+    }
+
+    #[test]
+    fn kernel_bandwidths() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let sd = 2.5f64.sqrt();
+        let scale = 5f64.powf(-0.2);
+        // The interquartile range of 2 over 1.34 is less than the standard deviation, so Silverman uses it
+        assert!((kernel_bandwidth(&values, Bandwidth::Silverman) - 0.9 * 2.0 / 1.34 * scale).abs() < 1e-12);
+        assert!((kernel_bandwidth(&values, Bandwidth::Scott) - 1.06 * sd * scale).abs() < 1e-12);
+        assert_eq!(kernel_bandwidth(&values, Bandwidth::Fixed(0.3)), 0.3);
+        assert_eq!(kernel_bandwidth(&values, Bandwidth::Fixed(0.0)), 0.5);
+    }
+
+    #[test]
+    fn density_grid() {
+        let x = VectorData::Quantitative(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        let violin = &violins(&x, &None, "all", Bandwidth::Fixed(0.5))[0];
+        assert_eq!(violin.group, "all");
+        assert_eq!(violin.density.len(), DENSITY_POINTS);
+        assert_eq!(violin.density[0].0, 0.0);
+        assert_eq!(violin.density[DENSITY_POINTS - 1].0, 6.0);
+        // Cut off two bandwidths beyond the data, the density still holds nearly all of its mass
+        let step = 6.0 / (DENSITY_POINTS - 1) as f64;
+        let mass: f64 = violin.density.windows(2).map(|w| (w[0].1 + w[1].1) / 2.0 * step).sum();
+        assert!(mass > 0.95 && mass < 1.0, "{}", mass);
+    }
+
+    #[test]
+    fn violin_of_a_constant_group() {
+        let x = VectorData::Quantitative(vec![1.0, 2.0, 3.0, 5.0, 5.0, 5.0]);
+        let groups = ["a", "a", "a", "b", "b", "b"].iter().map(|g| g.to_string()).collect();
+        let groups = Some(VectorData::Categorical(groups));
+        let violins = violins(&x, &groups, "", Bandwidth::Silverman);
+        let constant = &violins[1];
+        assert_eq!(constant.group, "b");
+        // Without any spread, the bandwidth falls back to a tenth of the value
+        assert_eq!(constant.bandwidth, 0.5);
+        assert_eq!((constant.density[0].0, constant.density[DENSITY_POINTS - 1].0), (4.0, 6.0));
+        assert!(constant.density.iter().all(|(_, d)| d.is_finite() && *d > 0.0));
+        let peak = constant.density.iter().cloned().fold((0.0, 0.0), |a, b| if b.1 > a.1 { b } else { a });
+        assert!((peak.0 - 5.0).abs() < 0.05);
+        assert_eq!((constant.stats.q1, constant.stats.q3), (5.0, 5.0));
+    }
+
+    #[test]
+    fn violin_of_a_single_point() {
+        let single = &violins(&VectorData::Quantitative(vec![2.0]), &None, "one", Bandwidth::Scott)[0];
+        assert_eq!(single.bandwidth, 0.2);
+        assert!(single.density.iter().all(|(_, d)| d.is_finite()));
+        let zero = &violins(&VectorData::Quantitative(vec![0.0, f64::NAN]), &None, "zero", Bandwidth::Silverman)[0];
+        assert_eq!(zero.bandwidth, 1.0);
+        assert_eq!((zero.density[0].0, zero.density[DENSITY_POINTS - 1].0), (-2.0, 2.0));
+        assert!(violins(&VectorData::Quantitative(vec![f64::NAN]), &None, "none", Bandwidth::Silverman).is_empty());
+    }
+}